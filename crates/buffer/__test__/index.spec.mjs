@@ -0,0 +1,83 @@
+import test from 'ava'
+
+import { TextBuffer } from '../index.js'
+
+// Test: Offsets and positions use UTF-16 units and all line endings
+test('TextBuffer should map offsets, positions and lines', (t) => {
+  const buffer = new TextBuffer('héllo 😀\r\nworld\nlast')
+
+  t.is(buffer.length(), 'héllo 😀\r\nworld\nlast'.length)
+  t.is(buffer.lineCount(), 3)
+  t.is(buffer.getLine(0), 'héllo 😀')
+  t.is(buffer.getLine(2), 'last')
+  t.is(buffer.lineToOffset(1), 10)
+  t.deepEqual(buffer.offsetToPosition(12), { line: 1, column: 2 })
+  t.is(buffer.positionToOffset({ line: 0, column: 8 }), 8)
+  t.throws(() => buffer.positionToOffset({ line: 0, column: 9 }))
+  t.throws(() => buffer.getLine(3))
+})
+
+// Test: Edits by offset and position, with grouped undo and redo
+test('TextBuffer should group edits into undoable transactions', (t) => {
+  const buffer = new TextBuffer('hello world')
+
+  buffer.insert(5, ',')
+  buffer.beginTransaction()
+  buffer.replace(7, 5, 'there')
+  buffer.insertAt({ line: 0, column: 12 }, '!\nbye')
+  buffer.commitTransaction()
+  t.is(buffer.getText(), 'hello, there!\nbye')
+  t.is(buffer.version, 3)
+
+  t.is(buffer.undo(), 12)
+  t.is(buffer.getText(), 'hello, world')
+  t.true(buffer.canRedo())
+
+  t.is(buffer.redo(), 17)
+  t.is(buffer.getText(), 'hello, there!\nbye')
+
+  buffer.undo()
+  buffer.undo()
+  t.is(buffer.getText(), 'hello world')
+  t.is(buffer.undo(), null)
+
+  buffer.deleteRange({ line: 0, column: 0 }, { line: 0, column: 6 })
+  t.is(buffer.getText(), 'world')
+  t.false(buffer.canRedo())
+  t.throws(() => buffer.commitTransaction())
+
+  // An open transaction has to be committed before it can be undone
+  buffer.beginTransaction()
+  buffer.insert(0, 'hello ')
+  t.false(buffer.canUndo())
+  t.throws(() => buffer.undo(), { message: /transaction is open/ })
+  buffer.commitTransaction()
+  t.is(buffer.undo(), 0)
+  t.is(buffer.getText(), 'world')
+})
+
+// Test: Snapshots are unaffected by later edits
+test('TextBuffer snapshots should stay at their version', (t) => {
+  const buffer = new TextBuffer('one\ntwo')
+  const snapshot = buffer.snapshot()
+
+  buffer.delete(0, 4)
+  t.is(buffer.getText(), 'two')
+  t.is(snapshot.getText(), 'one\ntwo')
+  t.is(snapshot.version, 0)
+  t.is(snapshot.getLine(1), 'two')
+  t.is(snapshot.getTextInRange(2, 5), 'e\nt')
+})
+
+// Test: Large snapshots can be read as a few UTF-8 buffers
+test('BufferSnapshot chunks should cover the text on character boundaries', (t) => {
+  const text = Array.from({ length: 20000 }, (_, i) => `line ${i} é 😀`).join('\n')
+  const buffer = new TextBuffer(text)
+  const chunks = [...buffer.snapshot().chunks()]
+
+  t.true(chunks.length > 1)
+  t.true(chunks.every((chunk) => chunk.length <= 128 * 1024))
+  t.true(chunks.every((chunk) => Buffer.from(chunk.toString('utf8')).equals(chunk)))
+  t.is(Buffer.concat(chunks).toString('utf8'), text)
+  t.deepEqual([...new TextBuffer('').snapshot().chunks()], [])
+})
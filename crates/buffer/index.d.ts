@@ -0,0 +1,88 @@
+/* tslint:disable */
+/* eslint-disable */
+
+/* auto-generated by NAPI-RS */
+
+/** Zero-based line and UTF-16 column, the way editors address text. */
+export interface Position {
+  line: number
+  column: number
+}
+/**
+ * Immutable view of a buffer at one version.
+ *
+ * Ropes share their nodes, so taking a snapshot is O(1) and it stays valid
+ * while the buffer keeps changing.
+ */
+export declare class BufferSnapshot {
+  /** Version of the buffer this snapshot was taken at. */
+  get version(): number
+  /** Length in UTF-16 code units, matching JS string length. */
+  length(): number
+  lineCount(): number
+  getText(): string
+  /**
+   * The text as UTF-8 buffers of about 64 KiB, for streaming it to search or
+   * diffing without building one large string. Each buffer ends on a
+   * character boundary.
+   */
+  chunks(): SnapshotChunks
+  getTextInRange(start: number, end: number): string
+  getLine(line: number): string
+  offsetToPosition(offset: number): Position
+  positionToOffset(position: Position): number
+}
+/** Iterator over the text of a `BufferSnapshot`, from `chunks`. */
+export declare class SnapshotChunks {
+  [Symbol.iterator](): Iterator<Buffer, void, void>
+}
+/**
+ * Editable document backed by a rope, so edits and line lookups stay
+ * logarithmic in the document size.
+ *
+ * Offsets are UTF-16 code units, so they line up with JS string indices.
+ */
+export declare class TextBuffer {
+  constructor(text?: string | undefined | null)
+  /** Bumped by every change, including undo and redo. */
+  get version(): number
+  /** Length in UTF-16 code units, matching JS string length. */
+  length(): number
+  /** Number of lines, counting the empty line after a trailing newline. */
+  lineCount(): number
+  getText(): string
+  getTextInRange(start: number, end: number): string
+  /** Text of `line`, without its line terminator. */
+  getLine(line: number): string
+  /** Offset where `line` starts. */
+  lineToOffset(line: number): number
+  offsetToPosition(offset: number): Position
+  positionToOffset(position: Position): number
+  insert(offset: number, text: string): void
+  delete(offset: number, length: number): void
+  /** Replace `length` code units at `offset` with `text` as a single edit. */
+  replace(offset: number, length: number, text: string): void
+  insertAt(position: Position, text: string): void
+  deleteRange(start: Position, end: Position): void
+  /**
+   * Group every edit until the matching `commitTransaction` into one undo step.
+   * Transactions nest; only the outermost commit closes the group.
+   */
+  beginTransaction(): void
+  commitTransaction(): void
+  /**
+   * Revert the last undo step, returning the offset to put the cursor at,
+   * or `null` when there is nothing to undo. Throws while a transaction is
+   * open, since its group is not finished yet.
+   */
+  undo(): number | null
+  /**
+   * Reapply the last undone step, returning the offset to put the cursor at.
+   * Throws while a transaction is open, like `undo`.
+   */
+  redo(): number | null
+  canUndo(): boolean
+  canRedo(): boolean
+  /** Cheap immutable copy of the current contents. */
+  snapshot(): BufferSnapshot
+}
@@ -0,0 +1,74 @@
+/// One splice of the rope, enough to replay it in either direction.
+#[derive(Debug, Clone)]
+pub(crate) struct Edit {
+  /// Char index where the splice starts
+  pub char_offset: usize,
+  pub deleted: String,
+  pub inserted: String,
+}
+
+/// Undo/redo stacks of edit groups. Edits made while a transaction is open
+/// are undone together; any other edit is a group of its own.
+#[derive(Debug, Default)]
+pub(crate) struct History {
+  undo: Vec<Vec<Edit>>,
+  redo: Vec<Vec<Edit>>,
+  pending: Vec<Edit>,
+  depth: u32,
+}
+
+impl History {
+  pub fn begin(&mut self) {
+    self.depth += 1;
+  }
+
+  /// Close the innermost transaction; returns false if none was open.
+  pub fn commit(&mut self) -> bool {
+    if self.depth == 0 {
+      return false;
+    }
+    self.depth -= 1;
+    if self.depth == 0 {
+      self.seal();
+    }
+    true
+  }
+
+  pub fn record(&mut self, edit: Edit) {
+    self.redo.clear();
+    self.pending.push(edit);
+    if self.depth == 0 {
+      self.seal();
+    }
+  }
+
+  pub fn in_transaction(&self) -> bool {
+    self.depth > 0
+  }
+
+  pub fn pop_undo(&mut self) -> Option<Vec<Edit>> {
+    let group = self.undo.pop()?;
+    self.redo.push(group.clone());
+    Some(group)
+  }
+
+  pub fn pop_redo(&mut self) -> Option<Vec<Edit>> {
+    let group = self.redo.pop()?;
+    self.undo.push(group.clone());
+    Some(group)
+  }
+
+  pub fn can_undo(&self) -> bool {
+    !self.in_transaction() && !self.undo.is_empty()
+  }
+
+  pub fn can_redo(&self) -> bool {
+    !self.in_transaction() && !self.redo.is_empty()
+  }
+
+  fn seal(&mut self) {
+    if !self.pending.is_empty() {
+      self.undo.push(std::mem::take(&mut self.pending));
+    }
+  }
+}
@@ -0,0 +1,299 @@
+#![deny(clippy::all)]
+
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use ropey::{Rope, RopeSlice};
+
+mod history;
+pub mod snapshot;
+
+use history::{Edit, History};
+use snapshot::BufferSnapshot;
+
+/// Zero-based line and UTF-16 column, the way editors address text.
+#[napi(object)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Position {
+  pub line: u32,
+  pub column: u32,
+}
+
+/// Editable document backed by a rope, so edits and line lookups stay
+/// logarithmic in the document size.
+///
+/// Offsets are UTF-16 code units, so they line up with JS string indices.
+#[napi]
+pub struct TextBuffer {
+  rope: Rope,
+  history: History,
+  version: u32,
+}
+
+#[napi]
+impl TextBuffer {
+  #[napi(constructor)]
+  pub fn new(text: Option<String>) -> Self {
+    TextBuffer {
+      rope: Rope::from_str(text.as_deref().unwrap_or_default()),
+      history: History::default(),
+      version: 0,
+    }
+  }
+
+  /// Bumped by every change, including undo and redo.
+  #[napi(getter)]
+  pub fn version(&self) -> u32 {
+    self.version
+  }
+
+  /// Length in UTF-16 code units, matching JS string length.
+  #[napi]
+  pub fn length(&self) -> u32 {
+    self.rope.len_utf16_cu() as u32
+  }
+
+  /// Number of lines, counting the empty line after a trailing newline.
+  #[napi]
+  pub fn line_count(&self) -> u32 {
+    self.rope.len_lines() as u32
+  }
+
+  #[napi]
+  pub fn get_text(&self) -> String {
+    self.rope.to_string()
+  }
+
+  #[napi]
+  pub fn get_text_in_range(&self, start: u32, end: u32) -> Result<String> {
+    let (start, end) = char_range(&self.rope, start, end)?;
+    Ok(self.rope.slice(start..end).to_string())
+  }
+
+  /// Text of `line`, without its line terminator.
+  #[napi]
+  pub fn get_line(&self, line: u32) -> Result<String> {
+    line_text(&self.rope, line)
+  }
+
+  /// Offset where `line` starts.
+  #[napi]
+  pub fn line_to_offset(&self, line: u32) -> Result<u32> {
+    check_line(&self.rope, line)?;
+    Ok(offset_of_char(&self.rope, self.rope.line_to_char(line as usize)))
+  }
+
+  #[napi]
+  pub fn offset_to_position(&self, offset: u32) -> Result<Position> {
+    Ok(position_of_char(&self.rope, char_at_offset(&self.rope, offset)?))
+  }
+
+  #[napi]
+  pub fn position_to_offset(&self, position: Position) -> Result<u32> {
+    Ok(offset_of_char(&self.rope, char_at_position(&self.rope, position)?))
+  }
+
+  #[napi]
+  pub fn insert(&mut self, offset: u32, text: String) -> Result<()> {
+    let start = char_at_offset(&self.rope, offset)?;
+    self.splice(start, start, &text);
+    Ok(())
+  }
+
+  #[napi]
+  pub fn delete(&mut self, offset: u32, length: u32) -> Result<()> {
+    let (start, end) = char_range(&self.rope, offset, offset.saturating_add(length))?;
+    self.splice(start, end, "");
+    Ok(())
+  }
+
+  /// Replace `length` code units at `offset` with `text` as a single edit.
+  #[napi]
+  pub fn replace(&mut self, offset: u32, length: u32, text: String) -> Result<()> {
+    let (start, end) = char_range(&self.rope, offset, offset.saturating_add(length))?;
+    self.splice(start, end, &text);
+    Ok(())
+  }
+
+  #[napi]
+  pub fn insert_at(&mut self, position: Position, text: String) -> Result<()> {
+    let start = char_at_position(&self.rope, position)?;
+    self.splice(start, start, &text);
+    Ok(())
+  }
+
+  #[napi]
+  pub fn delete_range(&mut self, start: Position, end: Position) -> Result<()> {
+    let start = char_at_position(&self.rope, start)?;
+    let end = char_at_position(&self.rope, end)?;
+    if end < start {
+      return Err(Error::new(Status::InvalidArg, "Range end is before its start".to_string()));
+    }
+    self.splice(start, end, "");
+    Ok(())
+  }
+
+  /// Group every edit until the matching `commitTransaction` into one undo step.
+  /// Transactions nest; only the outermost commit closes the group.
+  #[napi]
+  pub fn begin_transaction(&mut self) {
+    self.history.begin();
+  }
+
+  #[napi]
+  pub fn commit_transaction(&mut self) -> Result<()> {
+    if !self.history.commit() {
+      return Err(Error::new(Status::GenericFailure, "No transaction is open".to_string()));
+    }
+    Ok(())
+  }
+
+  /// Revert the last undo step, returning the offset to put the cursor at,
+  /// or `null` when there is nothing to undo. Throws while a transaction is
+  /// open, since its group is not finished yet.
+  #[napi]
+  pub fn undo(&mut self) -> Result<Option<u32>> {
+    self.check_no_transaction("undo")?;
+    let Some(group) = self.history.pop_undo() else {
+      return Ok(None);
+    };
+    for edit in group.iter().rev() {
+      let end = edit.char_offset + edit.inserted.chars().count();
+      self.apply(edit.char_offset, end, &edit.deleted);
+    }
+    Ok(group.first().map(|first| offset_of_char(&self.rope, first.char_offset + first.deleted.chars().count())))
+  }
+
+  /// Reapply the last undone step, returning the offset to put the cursor at.
+  /// Throws while a transaction is open, like `undo`.
+  #[napi]
+  pub fn redo(&mut self) -> Result<Option<u32>> {
+    self.check_no_transaction("redo")?;
+    let Some(group) = self.history.pop_redo() else {
+      return Ok(None);
+    };
+    for edit in &group {
+      let end = edit.char_offset + edit.deleted.chars().count();
+      self.apply(edit.char_offset, end, &edit.inserted);
+    }
+    Ok(group.last().map(|last| offset_of_char(&self.rope, last.char_offset + last.inserted.chars().count())))
+  }
+
+  #[napi]
+  pub fn can_undo(&self) -> bool {
+    self.history.can_undo()
+  }
+
+  #[napi]
+  pub fn can_redo(&self) -> bool {
+    self.history.can_redo()
+  }
+
+  /// Cheap immutable copy of the current contents.
+  #[napi]
+  pub fn snapshot(&self) -> BufferSnapshot {
+    BufferSnapshot::new(self.rope.clone(), self.version)
+  }
+
+  fn check_no_transaction(&self, action: &str) -> Result<()> {
+    if self.history.in_transaction() {
+      return Err(Error::new(Status::GenericFailure, format!("Cannot {} while a transaction is open", action)));
+    }
+    Ok(())
+  }
+
+  /// Replace chars `start..end` and record the change for undo.
+  fn splice(&mut self, start: usize, end: usize, text: &str) {
+    if start == end && text.is_empty() {
+      return;
+    }
+    let deleted = self.apply(start, end, text);
+    self.history.record(Edit {
+      char_offset: start,
+      deleted,
+      inserted: text.to_string(),
+    });
+  }
+
+  /// Replace chars `start..end` without touching history, returning the removed text.
+  fn apply(&mut self, start: usize, end: usize, text: &str) -> String {
+    let deleted = self.rope.slice(start..end).to_string();
+    self.rope.remove(start..end);
+    self.rope.insert(start, text);
+    self.version = self.version.wrapping_add(1);
+    deleted
+  }
+}
+
+fn out_of_range(what: &str, value: u32, limit: usize) -> Error {
+  Error::new(
+    Status::InvalidArg,
+    format!("{} {} is out of range (limit {})", what, value, limit),
+  )
+}
+
+fn check_line(rope: &Rope, line: u32) -> Result<()> {
+  if line as usize >= rope.len_lines() {
+    return Err(out_of_range("Line", line, rope.len_lines()));
+  }
+  Ok(())
+}
+
+/// Char index for a UTF-16 offset; an offset inside a surrogate pair rounds down.
+pub(crate) fn char_at_offset(rope: &Rope, offset: u32) -> Result<usize> {
+  let length = rope.len_utf16_cu();
+  if offset as usize > length {
+    return Err(out_of_range("Offset", offset, length));
+  }
+  Ok(rope.utf16_cu_to_char(offset as usize))
+}
+
+pub(crate) fn char_range(rope: &Rope, start: u32, end: u32) -> Result<(usize, usize)> {
+  let start = char_at_offset(rope, start)?;
+  let end = char_at_offset(rope, end)?;
+  if end < start {
+    return Err(Error::new(Status::InvalidArg, "Range end is before its start".to_string()));
+  }
+  Ok((start, end))
+}
+
+pub(crate) fn offset_of_char(rope: &Rope, char_idx: usize) -> u32 {
+  rope.char_to_utf16_cu(char_idx) as u32
+}
+
+pub(crate) fn char_at_position(rope: &Rope, position: Position) -> Result<usize> {
+  check_line(rope, position.line)?;
+  let line_start = rope.line_to_char(position.line as usize);
+  let line_end = line_start + content_len(rope.line(position.line as usize));
+
+  let start = rope.char_to_utf16_cu(line_start);
+  let width = rope.char_to_utf16_cu(line_end) - start;
+  if position.column as usize > width {
+    return Err(out_of_range("Column", position.column, width));
+  }
+  Ok(rope.utf16_cu_to_char(start + position.column as usize))
+}
+
+pub(crate) fn position_of_char(rope: &Rope, char_idx: usize) -> Position {
+  let line = rope.char_to_line(char_idx);
+  let line_start = rope.line_to_char(line);
+  Position {
+    line: line as u32,
+    column: (rope.char_to_utf16_cu(char_idx) - rope.char_to_utf16_cu(line_start)) as u32,
+  }
+}
+
+pub(crate) fn line_text(rope: &Rope, line: u32) -> Result<String> {
+  check_line(rope, line)?;
+  let slice = rope.line(line as usize);
+  Ok(slice.slice(..content_len(slice)).to_string())
+}
+
+/// Chars in a line, excluding its `\n`, `\r\n` or `\r` terminator.
+fn content_len(line: RopeSlice) -> usize {
+  let len = line.len_chars();
+  match (len.checked_sub(2).map(|i| line.char(i)), len.checked_sub(1).map(|i| line.char(i))) {
+    (Some('\r'), Some('\n')) => len - 2,
+    (_, Some('\n')) | (_, Some('\r')) => len - 1,
+    _ => len,
+  }
+}
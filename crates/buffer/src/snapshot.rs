@@ -0,0 +1,114 @@
+use napi::bindgen_prelude::{Buffer, Generator};
+use napi::Result;
+use napi_derive::napi;
+use ropey::Rope;
+
+use crate::{char_at_offset, char_at_position, char_range, line_text, offset_of_char, position_of_char, Position};
+
+/// `chunks` gathers rope chunks into buffers of about this many bytes, so JS
+/// gets a few large buffers instead of one per rope leaf.
+const CHUNK_LEN: usize = 64 * 1024;
+
+/// Immutable view of a buffer at one version.
+///
+/// Ropes share their nodes, so taking a snapshot is O(1) and it stays valid
+/// while the buffer keeps changing.
+#[napi]
+pub struct BufferSnapshot {
+  rope: Rope,
+  version: u32,
+}
+
+impl BufferSnapshot {
+  pub(crate) fn new(rope: Rope, version: u32) -> Self {
+    BufferSnapshot { rope, version }
+  }
+}
+
+#[napi]
+impl BufferSnapshot {
+  /// Version of the buffer this snapshot was taken at.
+  #[napi(getter)]
+  pub fn version(&self) -> u32 {
+    self.version
+  }
+
+  /// Length in UTF-16 code units, matching JS string length.
+  #[napi]
+  pub fn length(&self) -> u32 {
+    self.rope.len_utf16_cu() as u32
+  }
+
+  #[napi]
+  pub fn line_count(&self) -> u32 {
+    self.rope.len_lines() as u32
+  }
+
+  #[napi]
+  pub fn get_text(&self) -> String {
+    self.rope.to_string()
+  }
+
+  /// The text as UTF-8 buffers of about 64 KiB, for streaming it to search or
+  /// diffing without building one large string. Each buffer ends on a
+  /// character boundary.
+  #[napi]
+  pub fn chunks(&self) -> SnapshotChunks {
+    SnapshotChunks { rope: self.rope.clone(), byte_offset: 0 }
+  }
+
+  #[napi]
+  pub fn get_text_in_range(&self, start: u32, end: u32) -> Result<String> {
+    let (start, end) = char_range(&self.rope, start, end)?;
+    Ok(self.rope.slice(start..end).to_string())
+  }
+
+  #[napi]
+  pub fn get_line(&self, line: u32) -> Result<String> {
+    line_text(&self.rope, line)
+  }
+
+  #[napi]
+  pub fn offset_to_position(&self, offset: u32) -> Result<Position> {
+    Ok(position_of_char(&self.rope, char_at_offset(&self.rope, offset)?))
+  }
+
+  #[napi]
+  pub fn position_to_offset(&self, position: Position) -> Result<u32> {
+    Ok(offset_of_char(&self.rope, char_at_position(&self.rope, position)?))
+  }
+}
+
+/// Iterator over the text of a `BufferSnapshot`, from `chunks`.
+#[napi(iterator)]
+pub struct SnapshotChunks {
+  rope: Rope,
+  byte_offset: usize,
+}
+
+#[napi]
+impl Generator for SnapshotChunks {
+  type Yield = Buffer;
+  type Next = ();
+  type Return = ();
+
+  fn next(&mut self, _value: Option<()>) -> Option<Buffer> {
+    if self.byte_offset >= self.rope.len_bytes() {
+      return None;
+    }
+
+    let (chunks, chunk_start, _, _) = self.rope.chunks_at_byte(self.byte_offset);
+    // Only the first chunk can start before the offset
+    let mut skip = self.byte_offset - chunk_start;
+    let mut bytes = Vec::with_capacity(CHUNK_LEN);
+    for chunk in chunks {
+      bytes.extend_from_slice(&chunk.as_bytes()[skip..]);
+      skip = 0;
+      if bytes.len() >= CHUNK_LEN {
+        break;
+      }
+    }
+    self.byte_offset += bytes.len();
+    Some(bytes.into())
+  }
+}
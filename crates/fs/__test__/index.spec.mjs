@@ -1,7 +1,387 @@
 import test from 'ava'
+import { join, dirname } from 'path'
+import { fileURLToPath } from 'url'
+import { existsSync, mkdirSync, writeFileSync, readFileSync, readdirSync, rmSync, chmodSync, statSync, linkSync } from 'fs'
 
-import { sum } from '../index.js'
+import {
+  readFileContents,
+  writeFileContents,
+  createFile,
+  createDirectory,
+  renamePath,
+  movePath,
+  deletePath,
+  saveFileAtomic,
+  readFileWithEncoding,
+  writeFileWithEncoding,
+  probeFile,
+  hexDump,
+  LargeFile,
+  ProjectIndex,
+  fuzzyMatch,
+  searchProject,
+  planReplace,
+  applyReplace,
+  undoReplace,
+} from '../index.js'
 
-test('sum from native', (t) => {
-  t.is(sum(1, 2), 3)
+const __dirname = dirname(fileURLToPath(import.meta.url))
+const TEST_DIR = join(__dirname, 'test-fs-dir')
+
+// Helper function to poll until a condition holds or time runs out
+async function waitFor(condition, timeout = 5000) {
+  const start = Date.now()
+  while (!condition()) {
+    if (Date.now() - start > timeout) {
+      throw new Error('Timed out waiting for condition')
+    }
+    await new Promise((resolve) => setTimeout(resolve, 20))
+  }
+}
+
+// Helper function to clean test directories
+function cleanTestDirs() {
+  if (existsSync(TEST_DIR)) {
+    rmSync(TEST_DIR, { recursive: true, force: true })
+  }
+}
+
+// Set up test environment
+test.beforeEach(() => {
+  cleanTestDirs()
+  mkdirSync(TEST_DIR, { recursive: true })
+})
+
+// Clean up test environment
+test.afterEach(() => {
+  cleanTestDirs()
+})
+
+// Test: Write and read back a file
+test('writeFileContents should write a file that readFileContents can read', (t) => {
+  const filePath = join(TEST_DIR, 'hello.txt')
+
+  writeFileContents(filePath, 'Hello, Zen!')
+
+  t.is(readFileContents(filePath), 'Hello, Zen!')
+})
+
+// Test: Create a file that already exists
+test('createFile should fail with ALREADY_EXISTS for an existing file', (t) => {
+  const filePath = join(TEST_DIR, 'new.txt')
+
+  const entry = createFile(filePath)
+  t.true(entry.isFile)
+  t.is(entry.name, 'new.txt')
+
+  t.throws(() => createFile(filePath), { code: 'ALREADY_EXISTS' })
+})
+
+// Test: Rename and move
+test('renamePath and movePath should relocate files', (t) => {
+  const filePath = join(TEST_DIR, 'a.txt')
+  writeFileSync(filePath, 'content')
+  createDirectory(join(TEST_DIR, 'nested'))
+
+  const renamed = renamePath(filePath, 'b.txt')
+  t.is(renamed.name, 'b.txt')
+  t.false(existsSync(filePath))
+
+  const moved = movePath(renamed.path, join(TEST_DIR, 'nested'))
+  t.is(moved.path, join(TEST_DIR, 'nested', 'b.txt'))
+  t.is(readFileSync(moved.path, 'utf8'), 'content')
+})
+
+// Test: Delete a non-empty directory
+test('deletePath should refuse a non-empty directory unless recursive', (t) => {
+  const dirPath = join(TEST_DIR, 'full')
+  mkdirSync(dirPath)
+  writeFileSync(join(dirPath, 'file.txt'), 'content')
+
+  t.throws(() => deletePath(dirPath), { code: 'NOT_EMPTY' })
+
+  deletePath(dirPath, true)
+  t.false(existsSync(dirPath))
+})
+
+// Test: Atomic save with backup
+test('saveFileAtomic should replace contents and keep a backup', (t) => {
+  const filePath = join(TEST_DIR, 'source.rs')
+  writeFileSync(filePath, 'fn old() {}')
+  if (process.platform !== 'win32') {
+    chmodSync(filePath, 0o640)
+  }
+
+  saveFileAtomic(filePath, 'fn new() {}', { backup: true })
+
+  t.is(readFileSync(filePath, 'utf8'), 'fn new() {}')
+  t.is(readFileSync(`${filePath}.bak`, 'utf8'), 'fn old() {}')
+  if (process.platform !== 'win32') {
+    t.is(statSync(filePath).mode & 0o777, 0o640)
+  }
+
+  // No temp files should be left next to the target
+  t.deepEqual(readdirSync(TEST_DIR).sort(), ['source.rs', 'source.rs.bak'])
+})
+
+// Test: Hard-linked files are only overwritten in place when allowed
+test('saveFileAtomic should refuse to break hard links unless allowed in place', (t) => {
+  if (process.platform === 'win32') {
+    t.pass()
+    return
+  }
+  const filePath = join(TEST_DIR, 'linked.txt')
+  const linkPath = join(TEST_DIR, 'link.txt')
+  writeFileSync(filePath, 'old')
+  linkSync(filePath, linkPath)
+
+  t.throws(() => saveFileAtomic(filePath, 'new'), { code: 'NOT_ATOMIC' })
+  t.is(readFileSync(filePath, 'utf8'), 'old')
+  t.deepEqual(readdirSync(TEST_DIR).sort(), ['link.txt', 'linked.txt'])
+
+  saveFileAtomic(filePath, 'new', { allowInPlace: true })
+  t.is(readFileSync(linkPath, 'utf8'), 'new')
+})
+
+// Test: Decode UTF-16 with BOM and CRLF line endings
+test('readFileWithEncoding should detect UTF-16LE and CRLF', (t) => {
+  const filePath = join(TEST_DIR, 'windows.txt')
+  writeFileSync(filePath, Buffer.concat([Buffer.from([0xff, 0xfe]), Buffer.from('one\r\ntwo\r\n', 'utf16le')]))
+
+  const decoded = readFileWithEncoding(filePath)
+
+  t.is(decoded.contents, 'one\r\ntwo\r\n')
+  t.is(decoded.encoding, 'utf-16le')
+  t.true(decoded.hasBom)
+  t.is(decoded.lineEnding, 'crlf')
+})
+
+// Test: Round-trip a legacy 8-bit file
+test('writeFileWithEncoding should round-trip the original encoding and EOL', (t) => {
+  const filePath = join(TEST_DIR, 'latin1.log')
+  // "café\n" in windows-1252
+  writeFileSync(filePath, Buffer.from([0x63, 0x61, 0x66, 0xe9, 0x0a]))
+
+  const decoded = readFileWithEncoding(filePath)
+  t.is(decoded.contents, 'café\n')
+  t.is(decoded.lineEnding, 'lf')
+
+  writeFileWithEncoding(filePath, 'café\nthé\n', {
+    encoding: decoded.encoding,
+    hasBom: decoded.hasBom,
+    lineEnding: 'crlf',
+  })
+
+  t.deepEqual([...readFileSync(filePath)], [0x63, 0x61, 0x66, 0xe9, 0x0d, 0x0a, 0x74, 0x68, 0xe9, 0x0d, 0x0a])
+})
+
+// Test: Classify binary and text files
+test('probeFile should classify images, binaries and text', (t) => {
+  const pngPath = join(TEST_DIR, 'logo.png')
+  writeFileSync(pngPath, Buffer.from([0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a, 0x00, 0x00]))
+  const blobPath = join(TEST_DIR, 'data.bin')
+  writeFileSync(blobPath, Buffer.from([0x01, 0x00, 0x02, 0x00, 0xff, 0x10]))
+  const textPath = join(TEST_DIR, 'notes.md')
+  writeFileSync(textPath, '# Notes\n')
+
+  t.like(probeFile(pngPath), { kind: 'image', mimeType: 'image/png' })
+  t.is(probeFile(blobPath).kind, 'binary')
+  t.like(probeFile(textPath), { kind: 'text', encoding: 'utf-8' })
+  t.is(probeFile(textPath, { maxTextSize: 4 }).kind, 'too-large')
+
+  // A two-byte character straddling the 8192-byte sniff window is still UTF-8
+  const straddlePath = join(TEST_DIR, 'straddle.txt')
+  writeFileSync(straddlePath, 'a'.repeat(8191) + 'é' + 'b'.repeat(100))
+  t.like(probeFile(straddlePath), { kind: 'text', encoding: 'utf-8' })
+})
+
+// Test: Hex preview
+test('hexDump should return offset, hex and ascii columns', (t) => {
+  const filePath = join(TEST_DIR, 'data.bin')
+  writeFileSync(filePath, Buffer.from('0123456789abcdef\x00xyz', 'latin1'))
+
+  const lines = hexDump(filePath, 0, 64)
+
+  t.is(lines.length, 2)
+  t.is(lines[0].ascii, '0123456789abcdef')
+  t.deepEqual(lines[1], { offset: 16, hex: '00 78 79 7a', ascii: '.xyz' })
+  t.throws(() => hexDump(filePath, -1, 16), { code: 'OUT_OF_RANGE' })
+})
+
+// Test: Lazy line access on a large file
+test('LargeFile should index lines and read arbitrary ranges', (t) => {
+  const filePath = join(TEST_DIR, 'big.log')
+  const lines = Array.from({ length: 5000 }, (_, i) => `line ${i}`)
+  writeFileSync(filePath, lines.join('\r\n'))
+
+  const file = new LargeFile(filePath)
+
+  t.deepEqual(file.readLines(2047, 3), ['line 2047', 'line 2048', 'line 2049'])
+  t.is(file.lineCount(), 5000)
+  t.deepEqual(file.readLines(4998, 10), ['line 4998', 'line 4999'])
+  t.is(file.readBytes(file.lineOffset(1024), 9).toString(), 'line 1024')
+  t.throws(() => file.readLines(5000, 1), { code: 'OUT_OF_RANGE' })
+  t.throws(() => file.readBytes(-1, 1), { code: 'OUT_OF_RANGE' })
+
+  file.close()
+  t.throws(() => file.lineCount(), { code: 'HANDLE_CLOSED' })
+})
+
+// Test: Indexing off the JS thread, and a file truncated while open
+test('LargeFile should index lines in the background and fail once the file shrinks', async (t) => {
+  const filePath = join(TEST_DIR, 'rotated.log')
+  writeFileSync(filePath, Array.from({ length: 3000 }, (_, i) => `entry ${i}`).join('\n') + '\n')
+
+  const file = new LargeFile(filePath)
+  t.is(await file.indexLines(), 3001)
+  t.deepEqual(file.readLines(2999, 5), ['entry 2999', ''])
+
+  writeFileSync(filePath, '')
+  t.throws(() => file.readLines(0, 1), { code: 'CONFLICT' })
+  t.throws(() => file.readBytes(0, 16), { code: 'CONFLICT' })
+
+  const empty = new LargeFile(filePath)
+  t.is(empty.lineCount(), 1)
+  t.deepEqual(empty.readLines(0, 1), [''])
+})
+
+// Test: Project index honors ignore rules and picks up new files
+test('ProjectIndex should skip ignored paths and track changes', async (t) => {
+  mkdirSync(join(TEST_DIR, 'src'))
+  mkdirSync(join(TEST_DIR, 'node_modules', 'dep'), { recursive: true })
+  mkdirSync(join(TEST_DIR, 'dist'))
+  mkdirSync(join(TEST_DIR, '.git', 'info'), { recursive: true })
+  writeFileSync(join(TEST_DIR, '.gitignore'), 'node_modules/\n*.log\n')
+  writeFileSync(join(TEST_DIR, 'src', 'main.rs'), 'fn main() {}')
+  writeFileSync(join(TEST_DIR, 'debug.log'), 'noise')
+  writeFileSync(join(TEST_DIR, 'node_modules', 'dep', 'index.js'), '')
+  writeFileSync(join(TEST_DIR, 'dist', 'bundle.js'), '')
+
+  const index = new ProjectIndex(TEST_DIR, { excludes: ['dist'] })
+  await waitFor(() => index.isReady())
+
+  t.deepEqual(index.files(), ['.gitignore', 'src/main.rs'])
+
+  writeFileSync(join(TEST_DIR, 'src', 'lib.rs'), '')
+  await waitFor(() => index.files().includes('src/lib.rs'))
+
+  t.is(index.fileCount(), 3)
+  t.is(index.findFiles('lib', 1)[0].path, 'src/lib.rs')
+
+  // Rules in .git/info/exclude are reloaded when it changes
+  writeFileSync(join(TEST_DIR, '.git', 'info', 'exclude'), 'lib.rs\n')
+  await waitFor(() => !index.files().includes('src/lib.rs'))
+  t.deepEqual(index.files(), ['.gitignore', 'src/main.rs'])
+  index.dispose()
+})
+
+// Test: Fuzzy ranking prefers word boundaries in the file name
+test('fuzzyMatch should rank file name boundary matches first and report positions', (t) => {
+  const results = fuzzyMatch('fw', [
+    'crates/fuzzy/src/window.rs',
+    'crates/mod/file_watcher.js',
+    'docs/README.md',
+    'src/fileWatcher.ts',
+  ])
+
+  t.is(results.length, 3)
+  t.is(results[2].path, 'crates/fuzzy/src/window.rs')
+
+  const camel = results.find((m) => m.path === 'src/fileWatcher.ts')
+  t.deepEqual(camel.positions, [4, 8])
+})
+
+// Helper to collect a streamed search into one list
+function runSearch(query, options) {
+  return new Promise((resolve, reject) => {
+    const files = {}
+    searchProject(TEST_DIR, query, options, (err, update) => {
+      if (err) return reject(err)
+      if (update.done) return resolve({ files, summary: update })
+      files[update.path] = update.matches
+    })
+  })
+}
+
+// Test: Project search streams matches with positions and context
+test('searchProject should stream matches and skip ignored and binary files', async (t) => {
+  mkdirSync(join(TEST_DIR, 'src'), { recursive: true })
+  mkdirSync(join(TEST_DIR, 'target'), { recursive: true })
+  writeFileSync(join(TEST_DIR, '.gitignore'), 'target/\n')
+  writeFileSync(join(TEST_DIR, 'src', 'main.rs'), 'fn main() {\n    let todo = 1; // TODO fix\n}\n')
+  writeFileSync(join(TEST_DIR, 'src', 'notes.md'), 'todo list\r\n')
+  writeFileSync(join(TEST_DIR, 'target', 'out.rs'), 'TODO\n')
+  writeFileSync(join(TEST_DIR, 'data.bin'), Buffer.from([0, 1, 2, 84, 79, 68, 79, 0]))
+
+  const { files, summary } = await runSearch('todo', { contextLines: 1 })
+  t.deepEqual(Object.keys(files).sort(), ['src/main.rs', 'src/notes.md'])
+  t.is(summary.matchCount, 3)
+  t.false(summary.cancelled)
+
+  const [first, second] = files['src/main.rs']
+  t.is(first.line, 1)
+  t.is(first.column, 8)
+  t.is(first.length, 4)
+  t.deepEqual(first.before, ['fn main() {'])
+  t.deepEqual(first.after, ['}'])
+  t.is(second.column, 21)
+  t.is(files['src/notes.md'][0].text, 'todo list')
+
+  const exact = await runSearch('TODO', { caseSensitive: true, includes: ['**/*.rs'] })
+  t.deepEqual(Object.keys(exact.files), ['src/main.rs'])
+  t.is(exact.summary.matchCount, 1)
+
+  const words = await runSearch('t[a-z]+', { isRegex: true, wholeWord: true, includes: ['*.md'] })
+  t.deepEqual(words.files['src/notes.md'].map((m) => m.text.substr(m.column, m.length)), ['todo'])
+
+  const noop = () => {}
+  t.throws(() => searchProject(TEST_DIR, '(', { isRegex: true }, noop), { code: 'INVALID_PATTERN' })
+  t.throws(() => searchProject(TEST_DIR, 'todo', { includes: ['src/[a'] }, noop), { code: 'INVALID_PATTERN' })
+})
+
+// Test: Replace previews edits, applies them in the original encoding and can be undone
+test('planReplace/applyReplace/undoReplace should round-trip a batch of edits', async (t) => {
+  mkdirSync(join(TEST_DIR, 'src'), { recursive: true })
+  const utf16 = Buffer.concat([Buffer.from([0xff, 0xfe]), Buffer.from('let fooBar = 1\r\n', 'utf16le')])
+  writeFileSync(join(TEST_DIR, 'src', 'a.ts'), 'const fooBar = fooBaz(fooBar)\n')
+  writeFileSync(join(TEST_DIR, 'src', 'b.ts'), utf16)
+
+  const plan = await planReplace(TEST_DIR, 'foo(Ba[rz])', 'qux$1', { isRegex: true, caseSensitive: true })
+  t.is(plan.matchCount, 4)
+  const [a] = plan.files.filter((f) => f.path === 'src/a.ts')
+  t.is(a.edits[1].replacement, 'quxBaz')
+  t.is(a.edits[1].after, 'const fooBar = quxBaz(fooBar)')
+
+  // Drop one edit to show plans can be pruned before applying
+  a.edits.splice(2, 1)
+  const undo = applyReplace(plan)
+  t.is(readFileSync(join(TEST_DIR, 'src', 'a.ts'), 'utf8'), 'const quxBar = quxBaz(fooBar)\n')
+  t.deepEqual(
+    readFileSync(join(TEST_DIR, 'src', 'b.ts')),
+    Buffer.concat([Buffer.from([0xff, 0xfe]), Buffer.from('let quxBar = 1\r\n', 'utf16le')]),
+  )
+
+  // A stale plan is rejected instead of clobbering newer contents
+  const error = t.throws(() => applyReplace(plan))
+  t.is(error.code, 'CONFLICT')
+
+  // A failed write leaves every file as the replace left it
+  if (process.platform !== 'win32') {
+    const [first, last] = undo.files.map((file) => join(TEST_DIR, file.path))
+    const replaced = readFileSync(first)
+    linkSync(last, join(TEST_DIR, 'link.ts'))
+    t.throws(() => undoReplace(undo), { code: 'NOT_ATOMIC' })
+    t.deepEqual(readFileSync(first), replaced)
+    rmSync(join(TEST_DIR, 'link.ts'))
+  }
+
+  t.deepEqual(undoReplace(undo).sort(), ['src/a.ts', 'src/b.ts'])
+  t.is(readFileSync(join(TEST_DIR, 'src', 'a.ts'), 'utf8'), 'const fooBar = fooBaz(fooBar)\n')
+  t.deepEqual(readFileSync(join(TEST_DIR, 'src', 'b.ts')), utf16)
+
+  // Edits that touch the same text can't both be applied
+  const again = await planReplace(TEST_DIR, 'fooBar', 'x', { caseSensitive: true, includes: ['src/a.ts'] })
+  again.files[0].edits.push({ ...again.files[0].edits[0], column: again.files[0].edits[0].column + 1, matched: 'ooBar' })
+  t.throws(() => applyReplace(again), { code: 'OVERLAPPING_EDITS' })
+  t.throws(() => planReplace(TEST_DIR, '(', 'x', { isRegex: true }), { code: 'INVALID_PATTERN' })
 })
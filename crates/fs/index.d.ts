@@ -20,4 +20,241 @@ export declare function watchProject(path: string, callback: (err: Error | null,
 export declare function stopWatching(path: string): boolean
 export declare function getDirectoryContents(path: string): Array<FileEntry>
 export declare function readFileContents(path: string): string
+export interface SaveOptions {
+  /** Keep the previous contents in `<path>.bak` before replacing the file */
+  backup?: boolean
+  /**
+   * Overwrite the file in place when replacing it would break hard links or
+   * change its owner, instead of failing with `NOT_ATOMIC`
+   */
+  allowInPlace?: boolean
+}
+export declare function saveFileAtomic(path: string, contents: string, options?: SaveOptions | undefined | null): FileEntry
 export declare function expandDirectory(path: string): Array<FileEntry>
+export interface DecodedFile {
+  contents: string
+  /** Lowercase WHATWG encoding name, e.g. `utf-8`, `utf-16le` or `windows-1252` */
+  encoding: string
+  hasBom: boolean
+  /** One of `lf`, `crlf`, `cr`, `mixed` or `none` */
+  lineEnding: string
+}
+export interface EncodeOptions {
+  /** Encoding label to write with; defaults to `utf-8` */
+  encoding?: string
+  hasBom?: boolean
+  /** Normalize line endings to `lf`, `crlf` or `cr`; anything else keeps them as-is */
+  lineEnding?: string
+  /** Keep the previous contents in `<path>.bak` before replacing the file */
+  backup?: boolean
+  /** Overwrite in place when the file can't be replaced atomically, as in `SaveOptions` */
+  allowInPlace?: boolean
+}
+export declare function readFileWithEncoding(path: string): DecodedFile
+export declare function writeFileWithEncoding(path: string, contents: string, options?: EncodeOptions | undefined | null): FileEntry
+export declare function writeFileContents(path: string, contents: string): void
+export declare function createFile(path: string): FileEntry
+export declare function createDirectory(path: string, recursive?: boolean | undefined | null): FileEntry
+export declare function renamePath(path: string, newName: string): FileEntry
+export declare function movePath(source: string, destinationDir: string): FileEntry
+export declare function deletePath(path: string, recursive?: boolean | undefined | null): void
+export interface FileProbe {
+  path: string
+  size: number
+  /** One of `text`, `binary`, `image`, `archive` or `too-large` */
+  kind: string
+  /** MIME type for recognized formats */
+  mimeType?: string
+  /** Detected encoding when the file looks like text */
+  encoding?: string
+}
+export interface ProbeOptions {
+  /** Text files larger than this many bytes are reported as `too-large` */
+  maxTextSize?: number
+}
+export interface HexDumpLine {
+  offset: number
+  /** Space separated lowercase hex bytes */
+  hex: string
+  /** Printable ASCII, with other bytes shown as `.` */
+  ascii: string
+}
+export declare function probeFile(path: string, options?: ProbeOptions | undefined | null): FileProbe
+export declare function readFileChunk(path: string, offset: number, length: number): Buffer
+export declare function hexDump(path: string, offset: number, length: number): Array<HexDumpLine>
+/**
+ * Read-only, memory-mapped view of a file that is too big to load as a string.
+ *
+ * Lines are indexed lazily as they are read; `indexLines` finishes the index
+ * off the JS thread. Appends are ignored until reopened. Each chunk read
+ * checks first that the file still covers it and fails with `CONFLICT` once
+ * it has shrunk, e.g. after log rotation with copytruncate; like any mapping,
+ * a file truncated by another process in the middle of a chunk can still
+ * crash with SIGBUS.
+ */
+export declare class LargeFile {
+  constructor(path: string)
+  get path(): string
+  byteLength(): number
+  /** Finish indexing lines on a worker thread; resolves to the line count. */
+  indexLines(): Promise<number>
+  /**
+   * Number of lines, counting the empty line after a trailing newline like an editor does.
+   *
+   * Until `indexLines` has resolved, this scans the rest of the file on the calling thread.
+   */
+  lineCount(): number
+  /** Byte offset where `line` (zero-based) starts. */
+  lineOffset(line: number): number
+  /** Read up to `count` lines starting at `start`, without their line terminators. */
+  readLines(start: number, count: number): Array<string>
+  /** Read a window of raw bytes, clamped to the end of the file. */
+  readBytes(offset: number, length: number): Buffer
+  /** Release the mapping; any further call fails. */
+  close(): void
+}
+export interface FuzzyMatch {
+  path: string
+  score: number
+  /** UTF-16 offsets of the matched characters, ready for highlighting in JS */
+  positions: Array<number>
+}
+/** Rank arbitrary strings against `query`, e.g. open editors or recent files. */
+export declare function fuzzyMatch(query: string, candidates: Array<string>, limit?: number | undefined | null): Array<FuzzyMatch>
+export interface IndexOptions {
+  /** Extra gitignore-style patterns, relative to the project root */
+  excludes?: Array<string>
+  /** Honor `.gitignore`, `.ignore` and `.git/info/exclude`; defaults to true */
+  useIgnoreFiles?: boolean
+}
+export interface IndexUpdate {
+  /** Project-relative paths, always separated by `/` */
+  added: Array<string>
+  removed: Array<string>
+  /** True once the initial scan has completed */
+  ready: boolean
+}
+/** Flat list of project files, built on a background thread and kept current by a watcher. */
+export declare class ProjectIndex {
+  constructor(root: string, options?: IndexOptions | undefined | null)
+  get root(): string
+  /** True once the initial scan has completed. */
+  isReady(): boolean
+  /** Sorted project-relative paths known so far. */
+  files(): Array<string>
+  fileCount(): number
+  /** Quick-open: fuzzy-rank indexed paths against `query`, best first. */
+  findFiles(query: string, limit?: number | undefined | null): Array<FuzzyMatch>
+  /** Receive added/removed paths as the index changes. */
+  onUpdate(callback: (err: Error | null, arg: IndexUpdate) => any): void
+  /** Rebuild the index from scratch, e.g. after changing branches. */
+  refresh(): void
+  /** Stop watching and release the update callback. */
+  dispose(): void
+}
+export interface SearchOptions {
+  /** Treat the query as a regular expression instead of literal text */
+  isRegex?: boolean
+  caseSensitive?: boolean
+  /** Only match whole words */
+  wholeWord?: boolean
+  /** Glob patterns a project-relative path must match, e.g. `src/**\/*.rs` */
+  includes?: Array<string>
+  /** Extra gitignore-style patterns to skip */
+  excludes?: Array<string>
+  /** Honor `.gitignore`, `.ignore` and `.git/info/exclude`; defaults to true */
+  useIgnoreFiles?: boolean
+  /** Lines of context reported before and after each match; defaults to 0 */
+  contextLines?: number
+  maxResults?: number
+  /** Skip files larger than this many bytes */
+  maxFileSize?: number
+}
+export interface SearchMatch {
+  /** Zero-based line number */
+  line: number
+  /** UTF-16 column of the match start within `text` */
+  column: number
+  /** UTF-16 length of the match; matches never span lines */
+  length: number
+  /** The matching line, without its terminator */
+  text: string
+  before: Array<string>
+  after: Array<string>
+}
+export interface SearchUpdate {
+  /** Project-relative path, separated by `/`; empty on the final update */
+  path: string
+  matches: Array<SearchMatch>
+  /** True on the last update of a search, whether it finished or was cancelled */
+  done: boolean
+  cancelled: boolean
+  /** True when the search stopped early because `maxResults` was reached */
+  limitHit: boolean
+  filesSearched: number
+  matchCount: number
+}
+/** Search every non-ignored text file under `root`, streaming matches per file. */
+export declare function searchProject(root: string, query: string, options: SearchOptions | undefined | null, callback: (err: Error | null, arg: SearchUpdate) => any): SearchHandle
+/** A running search; results keep arriving on the callback until `done`. */
+export declare class SearchHandle {
+  /** Stop the search; a final update with `cancelled` set follows. */
+  cancel(): void
+  isDone(): boolean
+}
+export interface ReplaceEdit {
+  /** Zero-based line number */
+  line: number
+  /** UTF-16 column of the match start within the line */
+  column: number
+  /** UTF-16 length of the matched text */
+  length: number
+  matched: string
+  /** Replacement with capture groups already expanded */
+  replacement: string
+  /** The line as it is now */
+  before: string
+  /** The line with only this edit applied */
+  after: string
+}
+export interface FileReplacement {
+  /** Project-relative path, separated by `/` */
+  path: string
+  /** Identifies the contents the edits were computed against */
+  fingerprint: string
+  edits: Array<ReplaceEdit>
+}
+/** Edits that `applyReplace` will make. Files or edits can be dropped before applying. */
+export interface ReplacePlan {
+  root: string
+  files: Array<FileReplacement>
+  matchCount: number
+}
+export interface UndoEntry {
+  path: string
+  /** Exact bytes the file had before the replace */
+  original: Buffer
+  /** Identifies the contents the replace wrote */
+  fingerprint: string
+}
+/** Everything needed to revert an applied replace. */
+export interface UndoManifest {
+  root: string
+  files: Array<UndoEntry>
+}
+/**
+ * Compute, without touching any file, what replacing `query` with `replacement` would do.
+ *
+ * With `isRegex`, `$1` or `${name}` in `replacement` expand to capture groups.
+ * The query and patterns are checked up front; the files are read on a worker thread.
+ */
+export declare function planReplace(root: string, query: string, replacement: string, options?: SearchOptions | undefined | null): Promise<ReplacePlan>
+/**
+ * Apply a plan, replacing each file atomically in its original encoding.
+ *
+ * Nothing is written if any file changed since the plan was made, and files
+ * already written are restored if a later one fails.
+ */
+export declare function applyReplace(plan: ReplacePlan): UndoManifest
+/** Revert an applied replace. Fails without writing anything if a file was edited since. */
+export declare function undoReplace(manifest: UndoManifest): Array<string>
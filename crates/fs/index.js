@@ -310,11 +310,31 @@ if (!nativeBinding) {
   throw new Error(`Failed to load native binding`)
 }
 
-const { openProject, watchProject, stopWatching, getDirectoryContents, readFileContents, expandDirectory } = nativeBinding
+const { LargeFile, ProjectIndex, SearchHandle, openProject, watchProject, stopWatching, getDirectoryContents, readFileContents, saveFileAtomic, expandDirectory, writeFileContents, createFile, createDirectory, renamePath, movePath, deletePath, readFileWithEncoding, writeFileWithEncoding, probeFile, readFileChunk, hexDump, fuzzyMatch, searchProject, planReplace, applyReplace, undoReplace } = nativeBinding
 
+module.exports.LargeFile = LargeFile
+module.exports.ProjectIndex = ProjectIndex
+module.exports.SearchHandle = SearchHandle
 module.exports.openProject = openProject
 module.exports.watchProject = watchProject
 module.exports.stopWatching = stopWatching
 module.exports.getDirectoryContents = getDirectoryContents
 module.exports.readFileContents = readFileContents
+module.exports.saveFileAtomic = saveFileAtomic
 module.exports.expandDirectory = expandDirectory
+module.exports.writeFileContents = writeFileContents
+module.exports.createFile = createFile
+module.exports.createDirectory = createDirectory
+module.exports.renamePath = renamePath
+module.exports.movePath = movePath
+module.exports.deletePath = deletePath
+module.exports.readFileWithEncoding = readFileWithEncoding
+module.exports.writeFileWithEncoding = writeFileWithEncoding
+module.exports.probeFile = probeFile
+module.exports.readFileChunk = readFileChunk
+module.exports.hexDump = hexDump
+module.exports.fuzzyMatch = fuzzyMatch
+module.exports.searchProject = searchProject
+module.exports.planReplace = planReplace
+module.exports.applyReplace = applyReplace
+module.exports.undoReplace = undoReplace
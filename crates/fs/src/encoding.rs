@@ -0,0 +1,201 @@
+use std::fs;
+use std::path::Path;
+
+use encoding_rs::{Encoding, UTF_16BE, UTF_16LE, UTF_8};
+use napi::Result;
+use napi_derive::napi;
+
+use crate::{entry_for_path, write_atomic, FileEntry, FsError, FsErrorCode};
+
+/// How many leading bytes are inspected when guessing BOM-less UTF-16.
+const UTF16_SNIFF_LEN: usize = 4096;
+
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct DecodedFile {
+    pub contents: String,
+    /// Lowercase WHATWG encoding name, e.g. `utf-8`, `utf-16le` or `windows-1252`
+    pub encoding: String,
+    pub has_bom: bool,
+    /// One of `lf`, `crlf`, `cr`, `mixed` or `none`
+    pub line_ending: String,
+}
+
+#[napi(object)]
+#[derive(Debug, Clone, Default)]
+pub struct EncodeOptions {
+    /// Encoding label to write with; defaults to `utf-8`
+    pub encoding: Option<String>,
+    pub has_bom: Option<bool>,
+    /// Normalize line endings to `lf`, `crlf` or `cr`; anything else keeps them as-is
+    pub line_ending: Option<String>,
+    /// Keep the previous contents in `<path>.bak` before replacing the file
+    pub backup: Option<bool>,
+    /// Overwrite in place when the file can't be replaced atomically, as in `SaveOptions`
+    pub allow_in_place: Option<bool>,
+}
+
+#[napi]
+pub fn read_file_with_encoding(path: String) -> Result<DecodedFile, FsErrorCode> {
+    let path = Path::new(&path);
+
+    if !path.is_file() {
+        return Err(FsError::InvalidPath(path.display().to_string()).into());
+    }
+
+    let bytes = fs::read(path).map_err(|e| FsError::from_io(e, path))?;
+
+    Ok(decode_bytes(&bytes))
+}
+
+#[napi]
+pub fn write_file_with_encoding(path: String, contents: String, options: Option<EncodeOptions>) -> Result<FileEntry, FsErrorCode> {
+    let path = Path::new(&path);
+    let options = options.unwrap_or_default();
+
+    let label = options.encoding.as_deref().unwrap_or("utf-8");
+    let encoding = Encoding::for_label(label.as_bytes())
+        .ok_or_else(|| FsError::Encoding(format!("Unknown encoding: {}", label)))?;
+
+    let contents = match options.line_ending.as_deref() {
+        Some(line_ending) => normalize_line_endings(&contents, line_ending),
+        None => contents,
+    };
+
+    let bytes = encode_text(&contents, encoding, options.has_bom.unwrap_or(false))?;
+    write_atomic(path, &bytes, options.backup.unwrap_or(false), options.allow_in_place.unwrap_or(false))?;
+
+    Ok(entry_for_path(path)?)
+}
+
+/// Detect the encoding of `bytes` and decode them, reporting the line ending style.
+pub(crate) fn decode_bytes(bytes: &[u8]) -> DecodedFile {
+    let (encoding, bom_len) = detect_encoding(bytes);
+    let (text, _) = encoding.decode_without_bom_handling(&bytes[bom_len..]);
+
+    DecodedFile {
+        line_ending: detect_line_ending(&text).to_string(),
+        contents: text.into_owned(),
+        encoding: encoding.name().to_lowercase(),
+        has_bom: bom_len > 0,
+    }
+}
+
+/// Returns the detected encoding and the length of its byte order mark, if any.
+pub(crate) fn detect_encoding(bytes: &[u8]) -> (&'static Encoding, usize) {
+    if let Some((encoding, bom_len)) = Encoding::for_bom(bytes) {
+        return (encoding, bom_len);
+    }
+
+    if let Some(encoding) = sniff_utf16(bytes) {
+        return (encoding, 0);
+    }
+
+    if std::str::from_utf8(bytes).is_ok() {
+        return (UTF_8, 0);
+    }
+
+    let mut detector = chardetng::EncodingDetector::new();
+    detector.feed(bytes, true);
+    (detector.guess(None, true), 0)
+}
+
+/// BOM-less UTF-16 text that is mostly ASCII has a zero in every other byte.
+fn sniff_utf16(bytes: &[u8]) -> Option<&'static Encoding> {
+    let sample = &bytes[..bytes.len().min(UTF16_SNIFF_LEN) & !1];
+    if sample.len() < 4 {
+        return None;
+    }
+
+    let pairs = sample.len() / 2;
+    let even_zeros = sample.iter().step_by(2).filter(|b| **b == 0).count();
+    let odd_zeros = sample.iter().skip(1).step_by(2).filter(|b| **b == 0).count();
+
+    if odd_zeros * 10 >= pairs * 7 && even_zeros * 10 <= pairs {
+        Some(UTF_16LE)
+    } else if even_zeros * 10 >= pairs * 7 && odd_zeros * 10 <= pairs {
+        Some(UTF_16BE)
+    } else {
+        None
+    }
+}
+
+pub(crate) fn detect_line_ending(text: &str) -> &'static str {
+    let bytes = text.as_bytes();
+    let (mut lf, mut crlf, mut cr) = (0usize, 0usize, 0usize);
+
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\r' if bytes.get(i + 1) == Some(&b'\n') => {
+                crlf += 1;
+                i += 1;
+            }
+            b'\r' => cr += 1,
+            b'\n' => lf += 1,
+            _ => {}
+        }
+        i += 1;
+    }
+
+    match (lf > 0, crlf > 0, cr > 0) {
+        (false, false, false) => "none",
+        (true, false, false) => "lf",
+        (false, true, false) => "crlf",
+        (false, false, true) => "cr",
+        _ => "mixed",
+    }
+}
+
+pub(crate) fn normalize_line_endings(text: &str, line_ending: &str) -> String {
+    let eol = match line_ending {
+        "lf" => "\n",
+        "crlf" => "\r\n",
+        "cr" => "\r",
+        _ => return text.to_string(),
+    };
+
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\r' => {
+                if chars.peek() == Some(&'\n') {
+                    chars.next();
+                }
+                result.push_str(eol);
+            }
+            '\n' => result.push_str(eol),
+            _ => result.push(c),
+        }
+    }
+    result
+}
+
+/// Encode `text`, failing rather than silently substituting unmappable characters.
+pub(crate) fn encode_text(text: &str, encoding: &'static Encoding, with_bom: bool) -> std::result::Result<Vec<u8>, FsError> {
+    // encoding_rs only decodes UTF-16, its encoder falls back to UTF-8
+    if encoding == UTF_16LE || encoding == UTF_16BE {
+        let mut bytes = Vec::with_capacity(text.len() * 2 + 2);
+        let little_endian = encoding == UTF_16LE;
+        let units = with_bom.then_some(0xFEFF).into_iter().chain(text.encode_utf16());
+        for unit in units {
+            let pair = if little_endian { unit.to_le_bytes() } else { unit.to_be_bytes() };
+            bytes.extend_from_slice(&pair);
+        }
+        return Ok(bytes);
+    }
+
+    let mut bytes = Vec::with_capacity(text.len() + 3);
+    if with_bom && encoding == UTF_8 {
+        bytes.extend_from_slice(b"\xEF\xBB\xBF");
+    }
+
+    let (encoded, _, had_errors) = encoding.encode(text);
+    if had_errors {
+        return Err(FsError::Encoding(format!("Text cannot be represented in {}", encoding.name())));
+    }
+    bytes.extend_from_slice(&encoded);
+
+    Ok(bytes)
+}
@@ -0,0 +1,316 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+use napi_derive::napi;
+
+/// Every matched character is worth this much before bonuses and penalties.
+const SCORE_MATCH: i32 = 16;
+const PENALTY_GAP_START: i32 = 3;
+const PENALTY_GAP_EXTENSION: i32 = 1;
+
+const BONUS_PATH_SEPARATOR: i32 = 9;
+const BONUS_BOUNDARY: i32 = 8;
+const BONUS_CAMEL_CASE: i32 = 7;
+const BONUS_CONSECUTIVE: i32 = 5;
+const BONUS_EXACT_CASE: i32 = 1;
+/// Applied when the whole query lands in the file name rather than its directories.
+const BONUS_BASENAME: i32 = 24;
+
+const DEFAULT_LIMIT: u32 = 100;
+
+/// Below this many candidates per thread, spawning is slower than scoring inline.
+const PARALLEL_CHUNK_MIN: usize = 8192;
+
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct FuzzyMatch {
+    pub path: String,
+    pub score: i32,
+    /// UTF-16 offsets of the matched characters, ready for highlighting in JS
+    pub positions: Vec<u32>,
+}
+
+/// Rank arbitrary strings against `query`, e.g. open editors or recent files.
+#[napi]
+pub fn fuzzy_match(query: String, candidates: Vec<String>, limit: Option<u32>) -> Vec<FuzzyMatch> {
+    let candidates: Vec<&str> = candidates.iter().map(String::as_str).collect();
+    rank(&query, &candidates, limit)
+}
+
+/// Score every candidate and return the best `limit` matches, best first.
+pub(crate) fn rank(query: &str, candidates: &[&str], limit: Option<u32>) -> Vec<FuzzyMatch> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT) as usize;
+    if limit == 0 {
+        return Vec::new();
+    }
+
+    let pattern = Pattern::new(query);
+
+    // Large indexes are split across cores
+    let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let chunk_size = candidates.len().div_ceil(threads).max(PARALLEL_CHUNK_MIN);
+    let mut ranked: Vec<Ranked> = if candidates.len() <= chunk_size {
+        pattern.top(candidates, limit).into_vec()
+    } else {
+        std::thread::scope(|scope| {
+            let workers: Vec<_> = candidates
+                .chunks(chunk_size)
+                .map(|chunk| scope.spawn(|| pattern.top(chunk, limit)))
+                .collect();
+            workers.into_iter().flat_map(|w| w.join().unwrap().into_vec()).collect()
+        })
+    };
+
+    ranked.sort_unstable();
+    ranked.truncate(limit);
+
+    // Highlight positions are only needed for what is actually shown
+    let mut scratch = Scratch::default();
+    ranked
+        .into_iter()
+        .map(|ranked| FuzzyMatch {
+            path: ranked.path.to_string(),
+            score: ranked.score,
+            positions: to_utf16_offsets(ranked.path, &pattern.positions(ranked.path, &mut scratch)),
+        })
+        .collect()
+}
+
+/// Ordered best first: higher score, then shorter path, then alphabetical.
+#[derive(PartialEq, Eq)]
+struct Ranked<'a> {
+    score: i32,
+    len: usize,
+    path: &'a str,
+    index: usize,
+}
+
+impl Ord for Ranked<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .score
+            .cmp(&self.score)
+            .then_with(|| self.len.cmp(&other.len))
+            .then_with(|| self.path.cmp(other.path))
+            .then_with(|| self.index.cmp(&other.index))
+    }
+}
+
+impl PartialOrd for Ranked<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Reusable buffers so scoring a candidate does not allocate.
+#[derive(Default)]
+struct Scratch {
+    chars: Vec<char>,
+    lower: Vec<char>,
+    bonus: Vec<i32>,
+    score: Vec<i32>,
+    from: Vec<usize>,
+}
+
+struct Pattern {
+    chars: Vec<char>,
+    lower: Vec<char>,
+}
+
+impl Pattern {
+    fn new(query: &str) -> Self {
+        let chars: Vec<char> = query.chars().filter(|c| !c.is_whitespace()).collect();
+        let lower = chars.iter().map(|c| fold(*c)).collect();
+        Pattern { chars, lower }
+    }
+
+    /// Best `limit` candidates of `chunk`, in a heap whose top is the worst kept entry.
+    fn top<'a>(&self, chunk: &[&'a str], limit: usize) -> BinaryHeap<Ranked<'a>> {
+        let mut scratch = Scratch::default();
+        let mut heap = BinaryHeap::with_capacity(limit + 1);
+        for (index, candidate) in chunk.iter().enumerate() {
+            let Some(score) = self.score(candidate, &mut scratch) else {
+                continue;
+            };
+            let ranked = Ranked { score, len: candidate.len(), path: candidate, index };
+            if heap.len() < limit {
+                heap.push(ranked);
+            } else if heap.peek().is_some_and(|worst| ranked < *worst) {
+                heap.pop();
+                heap.push(ranked);
+            }
+        }
+        heap
+    }
+
+    fn score(&self, candidate: &str, scratch: &mut Scratch) -> Option<i32> {
+        self.align(candidate, scratch).map(|(score, _, _)| score)
+    }
+
+    /// Indices of the matched characters in the best alignment of `candidate`.
+    fn positions(&self, candidate: &str, scratch: &mut Scratch) -> Vec<usize> {
+        let Some((_, first, end)) = self.align(candidate, scratch) else {
+            return Vec::new();
+        };
+
+        let n = scratch.score.len() / self.lower.len().max(1);
+        let mut positions = vec![0; self.lower.len()];
+        let mut j = end;
+        for i in (0..self.lower.len()).rev() {
+            positions[i] = first + j;
+            j = scratch.from[i * n + j];
+        }
+        positions
+    }
+
+    /// Returns the score plus the window start and end column needed to backtrack.
+    fn align(&self, candidate: &str, scratch: &mut Scratch) -> Option<(i32, usize, usize)> {
+        if self.lower.is_empty() {
+            return Some((0, 0, 0));
+        }
+
+        // Cheap subsequence check first; most candidates are rejected here
+        let mut needle = self.lower.iter().peekable();
+        for c in candidate.chars() {
+            if needle.peek().is_some_and(|n| **n == fold(c)) {
+                needle.next();
+            }
+        }
+        if needle.peek().is_some() {
+            return None;
+        }
+
+        scratch.chars.clear();
+        scratch.chars.extend(candidate.chars());
+        scratch.lower.clear();
+        scratch.lower.extend(scratch.chars.iter().map(|c| fold(*c)));
+
+        let basename_start = scratch
+            .chars
+            .iter()
+            .rposition(|c| *c == '/' || *c == '\\')
+            .map_or(0, |i| i + 1);
+
+        match self.best_alignment(scratch, basename_start) {
+            Some((score, first, end)) => Some((score + BONUS_BASENAME, first, end)),
+            None => self.best_alignment(scratch, 0),
+        }
+    }
+
+    /// Smith-Waterman style alignment of the query against `chars[start..]`,
+    /// rewarding word boundaries and runs while penalizing gaps.
+    fn best_alignment(&self, scratch: &mut Scratch, start: usize) -> Option<(i32, usize, usize)> {
+        let m = self.lower.len();
+
+        // Only the span between the first possible start and the last possible end can match
+        let first = start + scratch.lower[start..].iter().position(|c| *c == self.lower[0])?;
+        let last = scratch.lower.iter().rposition(|c| *c == self.lower[m - 1])?;
+        if last < first || last - first + 1 < m {
+            return None;
+        }
+
+        let Scratch { chars, lower, bonus, score, from } = scratch;
+        let text = &chars[first..=last];
+        let lower = &lower[first..=last];
+        let n = text.len();
+
+        bonus.clear();
+        bonus.extend((0..n).map(|j| {
+            let previous = (first + j).checked_sub(1).map(|p| chars[p]);
+            position_bonus(previous, text[j])
+        }));
+
+        // score[i * n + j]: best score with query[i] matched at text[j]; from: where query[i - 1] matched
+        score.clear();
+        score.resize(m * n, i32::MIN);
+        from.clear();
+        from.resize(m * n, usize::MAX);
+
+        for i in 0..m {
+            // Best predecessor at least two columns back, already charged for its gap
+            let mut gapped: Option<(i32, usize)> = None;
+
+            for j in i..n {
+                if i > 0 && j >= 2 && score[(i - 1) * n + j - 2] != i32::MIN {
+                    let candidate = score[(i - 1) * n + j - 2] - PENALTY_GAP_START;
+                    gapped = match gapped {
+                        Some((best, k)) if best - PENALTY_GAP_EXTENSION >= candidate => Some((best - PENALTY_GAP_EXTENSION, k)),
+                        _ => Some((candidate, j - 2)),
+                    };
+                } else if let Some((best, k)) = gapped {
+                    gapped = Some((best - PENALTY_GAP_EXTENSION, k));
+                }
+
+                if lower[j] != self.lower[i] {
+                    continue;
+                }
+
+                let gain = SCORE_MATCH
+                    + bonus[j]
+                    + if text[j] == self.chars[i] { BONUS_EXACT_CASE } else { 0 };
+
+                if i == 0 {
+                    // Leading unmatched characters cost a little, so earlier matches win ties
+                    let leading = (first + j - start) as i32;
+                    score[j] = gain - leading.min(PENALTY_GAP_START * 5) / PENALTY_GAP_START;
+                    continue;
+                }
+
+                let consecutive = (j >= 1 && score[(i - 1) * n + j - 1] != i32::MIN)
+                    .then(|| (score[(i - 1) * n + j - 1] + BONUS_CONSECUTIVE, j - 1));
+
+                let best = match (consecutive, gapped) {
+                    (Some(a), Some(b)) => Some(if a.0 >= b.0 { a } else { b }),
+                    (a, b) => a.or(b),
+                };
+
+                if let Some((previous, k)) = best {
+                    score[i * n + j] = previous + gain;
+                    from[i * n + j] = k;
+                }
+            }
+        }
+
+        let (end, total) = score[(m - 1) * n..]
+            .iter()
+            .enumerate()
+            .filter(|(_, s)| **s != i32::MIN)
+            .max_by_key(|(j, s)| (**s, std::cmp::Reverse(*j)))
+            .map(|(j, s)| (j, *s))?;
+
+        Some((total, first, end))
+    }
+}
+
+fn position_bonus(previous: Option<char>, current: char) -> i32 {
+    match previous {
+        None => BONUS_BOUNDARY + 2,
+        Some('/') | Some('\\') => BONUS_PATH_SEPARATOR,
+        Some('_') | Some('-') | Some('.') | Some(' ') => BONUS_BOUNDARY,
+        Some(p) if p.is_lowercase() && current.is_uppercase() => BONUS_CAMEL_CASE,
+        Some(p) if !p.is_ascii_digit() && current.is_ascii_digit() => BONUS_CAMEL_CASE,
+        _ => 0,
+    }
+}
+
+fn fold(c: char) -> char {
+    if c.is_ascii() {
+        c.to_ascii_lowercase()
+    } else {
+        c.to_lowercase().next().unwrap_or(c)
+    }
+}
+
+fn to_utf16_offsets(text: &str, positions: &[usize]) -> Vec<u32> {
+    let mut offsets = Vec::with_capacity(positions.len());
+    let mut utf16 = 0u32;
+    let mut next = positions.iter().peekable();
+    for (i, c) in text.chars().enumerate() {
+        if next.peek() == Some(&&i) {
+            offsets.push(utf16);
+            next.next();
+        }
+        utf16 += c.len_utf16() as u32;
+    }
+    offsets
+}
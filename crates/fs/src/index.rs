@@ -0,0 +1,437 @@
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::thread;
+
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::Match;
+use napi::threadsafe_function::{ThreadsafeFunction, ThreadsafeFunctionCallMode};
+use napi::Result;
+use napi_derive::napi;
+use notify::{Config, Event, RecommendedWatcher, RecursiveMode, Watcher};
+use walkdir::WalkDir;
+
+use crate::fuzzy::{self, FuzzyMatch};
+use crate::{FsError, FsErrorCode};
+
+/// Per-directory ignore files, applied like git does: deeper files override shallower ones.
+const IGNORE_FILES: &[&str] = &[".gitignore", ".ignore"];
+
+#[napi(object)]
+#[derive(Debug, Clone, Default)]
+pub struct IndexOptions {
+    /// Extra gitignore-style patterns, relative to the project root
+    pub excludes: Option<Vec<String>>,
+    /// Honor `.gitignore`, `.ignore` and `.git/info/exclude`; defaults to true
+    pub use_ignore_files: Option<bool>,
+}
+
+#[napi(object)]
+#[derive(Debug, Clone, Default)]
+pub struct IndexUpdate {
+    /// Project-relative paths, always separated by `/`
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    /// True once the initial scan has completed
+    pub ready: bool,
+}
+
+/// Gitignore-aware filter shared by the indexer and project search.
+pub(crate) struct IgnoreRules {
+    root: PathBuf,
+    excludes: Gitignore,
+    use_ignore_files: bool,
+    matchers: Mutex<HashMap<PathBuf, Option<Arc<Gitignore>>>>,
+}
+
+impl IgnoreRules {
+    pub(crate) fn new(root: &Path, excludes: &[String], use_ignore_files: bool) -> std::result::Result<Self, FsError> {
+        let mut builder = GitignoreBuilder::new(root);
+        for pattern in excludes {
+            builder
+                .add_line(None, pattern)
+                .map_err(|e| FsError::InvalidPattern(format!("Invalid exclude pattern {}: {}", pattern, e)))?;
+        }
+        let excludes = builder
+            .build()
+            .map_err(|e| FsError::InvalidPattern(format!("Invalid exclude patterns: {}", e)))?;
+
+        Ok(IgnoreRules {
+            root: root.to_path_buf(),
+            excludes,
+            use_ignore_files,
+            matchers: Mutex::new(HashMap::new()),
+        })
+    }
+
+    pub(crate) fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// `path` relative to the root, always separated by `/`.
+    pub(crate) fn relative(&self, path: &Path) -> Option<String> {
+        let relative = path.strip_prefix(&self.root).ok()?;
+        let parts: Vec<_> = relative
+            .components()
+            .filter_map(|c| match c {
+                Component::Normal(part) => Some(part.to_string_lossy()),
+                _ => None,
+            })
+            .collect();
+        (!parts.is_empty()).then(|| parts.join("/"))
+    }
+
+    /// Whether `path` or any directory between it and the root is ignored.
+    pub(crate) fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        let Ok(relative) = path.strip_prefix(&self.root) else {
+            return true;
+        };
+
+        let mut dir = self.root.clone();
+        let mut components = relative.components().peekable();
+        while let Some(component) = components.next() {
+            if components.peek().is_none() {
+                break;
+            }
+            dir.push(component);
+            if self.matches(&dir, true) {
+                return true;
+            }
+        }
+
+        self.matches(path, is_dir)
+    }
+
+    /// Check `path` itself, assuming its parents are already known not to be ignored.
+    /// Used while walking, where ignored directories are pruned before descending.
+    pub(crate) fn matches(&self, path: &Path, is_dir: bool) -> bool {
+        if path.file_name().is_some_and(|name| name == ".git") {
+            return true;
+        }
+
+        if self.excludes.matched(path, is_dir).is_ignore() {
+            return true;
+        }
+
+        if !self.use_ignore_files {
+            return false;
+        }
+
+        for dir in path.ancestors().skip(1) {
+            if let Some(matcher) = self.matcher_for(dir) {
+                match matcher.matched(path, is_dir) {
+                    Match::Ignore(_) => return true,
+                    Match::Whitelist(_) => return false,
+                    Match::None => {}
+                }
+            }
+            if dir == self.root {
+                break;
+            }
+        }
+
+        false
+    }
+
+    /// Forget cached rules for `dir`, e.g. after its `.gitignore` changed.
+    pub(crate) fn invalidate(&self, dir: &Path) {
+        self.matchers.lock().unwrap().remove(dir);
+    }
+
+    fn matcher_for(&self, dir: &Path) -> Option<Arc<Gitignore>> {
+        let mut matchers = self.matchers.lock().unwrap();
+        if let Some(matcher) = matchers.get(dir) {
+            return matcher.clone();
+        }
+
+        let mut builder = GitignoreBuilder::new(dir);
+        let mut found = false;
+        let mut candidates: Vec<PathBuf> = IGNORE_FILES.iter().map(|name| dir.join(name)).collect();
+        if dir == self.root {
+            candidates.insert(0, dir.join(".git").join("info").join("exclude"));
+        }
+        for candidate in candidates {
+            if candidate.is_file() {
+                // Unparseable lines are skipped, matching git's own leniency
+                let _ = builder.add(&candidate);
+                found = true;
+            }
+        }
+
+        let matcher = if found { builder.build().ok().map(Arc::new) } else { None };
+        matchers.insert(dir.to_path_buf(), matcher.clone());
+        matcher
+    }
+
+    /// Lazily walk `start` (inside the root), yielding every non-ignored file beneath it.
+    pub(crate) fn walk<'a>(&'a self, start: &Path) -> impl Iterator<Item = PathBuf> + 'a {
+        WalkDir::new(start)
+            .follow_links(false)
+            .into_iter()
+            .filter_entry(|entry| entry.depth() == 0 || !self.matches(entry.path(), entry.file_type().is_dir()))
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_type().is_file() || (entry.path_is_symlink() && entry.path().is_file()))
+            .map(|entry| entry.into_path())
+    }
+
+    /// Walk `start` (inside the root) and return every non-ignored file beneath it.
+    pub(crate) fn walk_files(&self, start: &Path) -> Vec<PathBuf> {
+        self.walk(start).collect()
+    }
+}
+
+struct IndexState {
+    rules: IgnoreRules,
+    files: RwLock<BTreeSet<String>>,
+    ready: AtomicBool,
+    /// Full scans in flight; while any is, watcher events are also recorded in `pending`
+    scans: AtomicUsize,
+    /// Paths the watcher reported during a scan, replayed once it has been swapped in
+    pending: Mutex<Vec<PathBuf>>,
+    /// A rescan thread has been started but has not begun walking yet
+    rescan_queued: AtomicBool,
+    callback: Mutex<Option<ThreadsafeFunction<IndexUpdate>>>,
+}
+
+impl IndexState {
+    fn relative(&self, path: &Path) -> Option<String> {
+        self.rules.relative(path)
+    }
+
+    /// Start a full rescan on a thread of its own, unless one is already waiting to start.
+    fn rescan_in_background(self: &Arc<Self>) {
+        if self.rescan_queued.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        let state = Arc::clone(self);
+        thread::spawn(move || state.rescan());
+    }
+
+    /// Full rescan, publishing the difference against the current list.
+    fn rescan(self: &Arc<Self>) {
+        self.scans.fetch_add(1, Ordering::SeqCst);
+        self.rescan_queued.store(false, Ordering::SeqCst);
+        let scanned: BTreeSet<String> = self
+            .rules
+            .walk_files(self.rules.root())
+            .iter()
+            .filter_map(|path| self.relative(path))
+            .collect();
+
+        let (update, replay) = {
+            let mut files = self.files.write().unwrap();
+            let update = IndexUpdate {
+                added: scanned.difference(&files).cloned().collect(),
+                removed: files.difference(&scanned).cloned().collect(),
+                ready: true,
+            };
+            *files = scanned;
+
+            // The walk may have passed a path before the watcher reported a change to it
+            let replay = if self.scans.fetch_sub(1, Ordering::SeqCst) == 1 {
+                std::mem::take(&mut *self.pending.lock().unwrap())
+            } else {
+                Vec::new()
+            };
+            (update, replay)
+        };
+
+        let was_ready = self.ready.swap(true, Ordering::SeqCst);
+        if !was_ready || !update.added.is_empty() || !update.removed.is_empty() {
+            self.publish(update);
+        }
+
+        for path in replay {
+            self.reconcile(&path);
+        }
+    }
+
+    /// Bring the entries at or below `path` in line with the disk.
+    ///
+    /// Ignore file changes are handed to a background rescan, so the watcher
+    /// thread never walks the whole project.
+    fn reconcile(self: &Arc<Self>, path: &Path) {
+        let Some(relative) = self.relative(path) else {
+            return;
+        };
+
+        if relative == ".git/info/exclude" {
+            self.rules.invalidate(self.rules.root());
+            self.rescan_in_background();
+            return;
+        }
+        if relative == ".git" || relative.starts_with(".git/") {
+            return;
+        }
+
+        if path.file_name().is_some_and(|name| IGNORE_FILES.iter().any(|f| name == *f)) {
+            if let Some(dir) = path.parent() {
+                self.rules.invalidate(dir);
+            }
+            self.rescan_in_background();
+            return;
+        }
+
+        let is_dir = path.is_dir();
+        let current: BTreeSet<String> = if path.exists() && !self.rules.is_ignored(path, is_dir) {
+            if is_dir {
+                self.rules.walk_files(path).iter().filter_map(|p| self.relative(p)).collect()
+            } else {
+                BTreeSet::from([relative.clone()])
+            }
+        } else {
+            BTreeSet::new()
+        };
+
+        let update = {
+            let mut files = self.files.write().unwrap();
+            if self.scans.load(Ordering::SeqCst) > 0 {
+                self.pending.lock().unwrap().push(path.to_path_buf());
+            }
+
+            let prefix = format!("{}/", relative);
+            let previous: BTreeSet<String> = files
+                .range(relative.clone()..)
+                .take_while(|p| **p == relative || p.starts_with(&prefix))
+                .cloned()
+                .collect();
+
+            let update = IndexUpdate {
+                added: current.difference(&previous).cloned().collect(),
+                removed: previous.difference(&current).cloned().collect(),
+                ready: self.ready.load(Ordering::SeqCst),
+            };
+            for path in &update.removed {
+                files.remove(path);
+            }
+            files.extend(update.added.iter().cloned());
+            update
+        };
+
+        if !update.added.is_empty() || !update.removed.is_empty() {
+            self.publish(update);
+        }
+    }
+
+    fn publish(&self, update: IndexUpdate) {
+        if let Some(callback) = self.callback.lock().unwrap().as_ref() {
+            callback.call(Ok(update), ThreadsafeFunctionCallMode::NonBlocking);
+        }
+    }
+}
+
+/// Flat list of project files, built on a background thread and kept current by a watcher.
+#[napi]
+pub struct ProjectIndex {
+    state: Arc<IndexState>,
+    watcher: Option<RecommendedWatcher>,
+}
+
+#[napi]
+impl ProjectIndex {
+    #[napi(constructor)]
+    pub fn new(root: String, options: Option<IndexOptions>) -> Result<Self, FsErrorCode> {
+        // Watchers report resolved paths on some platforms, so index under the canonical root
+        let root_path = std::fs::canonicalize(&root).map_err(|e| FsError::from_io(e, Path::new(&root)))?;
+        if !root_path.is_dir() {
+            return Err(FsError::InvalidPath(root).into());
+        }
+
+        let options = options.unwrap_or_default();
+        let rules = IgnoreRules::new(
+            &root_path,
+            &options.excludes.unwrap_or_default(),
+            options.use_ignore_files.unwrap_or(true),
+        )?;
+
+        let state = Arc::new(IndexState {
+            rules,
+            files: RwLock::new(BTreeSet::new()),
+            ready: AtomicBool::new(false),
+            scans: AtomicUsize::new(0),
+            pending: Mutex::new(Vec::new()),
+            rescan_queued: AtomicBool::new(false),
+            callback: Mutex::new(None),
+        });
+
+        let watch_state = Arc::clone(&state);
+        let mut watcher = RecommendedWatcher::new(
+            move |result: std::result::Result<Event, notify::Error>| {
+                if let Ok(event) = result {
+                    for path in &event.paths {
+                        watch_state.reconcile(path);
+                    }
+                }
+            },
+            Config::default(),
+        )
+        .map_err(FsError::Watcher)?;
+        watcher
+            .watch(&root_path, RecursiveMode::Recursive)
+            .map_err(FsError::Watcher)?;
+
+        state.rescan_in_background();
+
+        Ok(ProjectIndex {
+            state,
+            watcher: Some(watcher),
+        })
+    }
+
+    #[napi(getter)]
+    pub fn root(&self) -> String {
+        self.state.rules.root().to_string_lossy().to_string()
+    }
+
+    /// True once the initial scan has completed.
+    #[napi]
+    pub fn is_ready(&self) -> bool {
+        self.state.ready.load(Ordering::SeqCst)
+    }
+
+    /// Sorted project-relative paths known so far.
+    #[napi]
+    pub fn files(&self) -> Vec<String> {
+        self.state.files.read().unwrap().iter().cloned().collect()
+    }
+
+    #[napi]
+    pub fn file_count(&self) -> u32 {
+        self.state.files.read().unwrap().len() as u32
+    }
+
+    /// Quick-open: fuzzy-rank indexed paths against `query`, best first.
+    #[napi]
+    pub fn find_files(&self, query: String, limit: Option<u32>) -> Vec<FuzzyMatch> {
+        let files = self.state.files.read().unwrap();
+        let candidates: Vec<&str> = files.iter().map(String::as_str).collect();
+        fuzzy::rank(&query, &candidates, limit)
+    }
+
+    /// Receive added/removed paths as the index changes.
+    #[napi]
+    pub fn on_update(&self, callback: ThreadsafeFunction<IndexUpdate>) {
+        *self.state.callback.lock().unwrap() = Some(callback);
+        if self.is_ready() {
+            self.state.publish(IndexUpdate {
+                ready: true,
+                ..Default::default()
+            });
+        }
+    }
+
+    /// Rebuild the index from scratch, e.g. after changing branches.
+    #[napi]
+    pub fn refresh(&self) {
+        self.state.rules.matchers.lock().unwrap().clear();
+        self.state.rescan_in_background();
+    }
+
+    /// Stop watching and release the update callback.
+    #[napi]
+    pub fn dispose(&mut self) {
+        self.watcher = None;
+        *self.state.callback.lock().unwrap() = None;
+    }
+}
@@ -0,0 +1,270 @@
+use std::fs;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use memmap2::Mmap;
+use napi::bindgen_prelude::{AsyncTask, Buffer};
+use napi::{Env, Result, Status, Task};
+use napi_derive::napi;
+
+use crate::{FsError, FsErrorCode};
+
+/// Only every Nth line start is stored, which keeps the index for a
+/// multi-gigabyte log in the kilobytes instead of one offset per line.
+const LINES_PER_CHECKPOINT: usize = 1024;
+
+/// The line index grows by this many bytes at a time, so reading near the top
+/// of a huge file never scans the rest of it.
+const SCAN_CHUNK_LEN: usize = 4 * 1024 * 1024;
+
+/// Read-only, memory-mapped view of a file that is too big to load as a string.
+///
+/// Lines are indexed lazily as they are read; `indexLines` finishes the index
+/// off the JS thread. Appends are ignored until reopened. Each chunk read
+/// checks first that the file still covers it and fails with `CONFLICT` once
+/// it has shrunk, e.g. after log rotation with copytruncate; like any mapping,
+/// a file truncated by another process in the middle of a chunk can still
+/// crash with SIGBUS.
+#[napi]
+pub struct LargeFile {
+    path: String,
+    mapping: Option<Arc<Mapping>>,
+    index: Arc<Mutex<LineIndex>>,
+}
+
+struct Mapping {
+    file: fs::File,
+    mmap: Mmap,
+}
+
+impl Mapping {
+    fn len(&self) -> usize {
+        self.mmap.len()
+    }
+
+    /// The mapped bytes from `start` to `end`, clamped to the mapping, unless
+    /// the file no longer reaches `end` and touching the missing pages would
+    /// raise SIGBUS. Callers take at most `SCAN_CHUNK_LEN` at a time, so the
+    /// check stays close to the access.
+    fn bytes(&self, path: &str, start: usize, end: usize) -> std::result::Result<&[u8], FsError> {
+        let end = end.min(self.mmap.len());
+        let start = start.min(end);
+        let len = self.file.metadata().map_err(|e| FsError::from_io(e, Path::new(path)))?.len();
+        if len < end as u64 {
+            return Err(FsError::Conflict(format!("{} shrank while open", path)));
+        }
+        Ok(&self.mmap[start..end])
+    }
+
+    /// Offset of the first newline at or after `from`, searched one chunk at a time.
+    fn find_newline(&self, path: &str, mut from: usize) -> std::result::Result<Option<usize>, FsError> {
+        while from < self.len() {
+            let chunk = self.bytes(path, from, from.saturating_add(SCAN_CHUNK_LEN))?;
+            if let Some(i) = memchr::memchr(b'\n', chunk) {
+                return Ok(Some(from + i));
+            }
+            from += chunk.len();
+        }
+        Ok(None)
+    }
+}
+
+/// Line starts found so far, scanned from the beginning of the file.
+struct LineIndex {
+    checkpoints: Vec<usize>,
+    newlines: usize,
+    scanned: usize,
+    complete: bool,
+}
+
+impl LineIndex {
+    fn new() -> Self {
+        LineIndex { checkpoints: vec![0], newlines: 0, scanned: 0, complete: false }
+    }
+
+    /// Scan the next chunk of `mapping`.
+    fn scan_chunk(&mut self, mapping: &Mapping, path: &str) -> std::result::Result<(), FsError> {
+        let chunk = mapping.bytes(path, self.scanned, self.scanned.saturating_add(SCAN_CHUNK_LEN))?;
+        for newline in memchr::memchr_iter(b'\n', chunk) {
+            self.newlines += 1;
+            if self.newlines.is_multiple_of(LINES_PER_CHECKPOINT) {
+                self.checkpoints.push(self.scanned + newline + 1);
+            }
+        }
+        self.scanned += chunk.len();
+        self.complete = self.scanned == mapping.len();
+        Ok(())
+    }
+
+    /// Number of lines, counting the empty line after a trailing newline.
+    fn line_count(&self) -> usize {
+        self.newlines + 1
+    }
+}
+
+#[napi]
+impl LargeFile {
+    #[napi(constructor)]
+    pub fn new(path: String) -> Result<Self, FsErrorCode> {
+        let file_path = Path::new(&path);
+        let file = fs::File::open(file_path).map_err(|e| FsError::from_io(e, file_path))?;
+
+        // Safety: the mapping is read-only, and every chunk read checks first that the file still covers it
+        let mmap = unsafe { Mmap::map(&file) }.map_err(|e| FsError::from_io(e, file_path))?;
+
+        Ok(LargeFile {
+            path,
+            mapping: Some(Arc::new(Mapping { file, mmap })),
+            index: Arc::new(Mutex::new(LineIndex::new())),
+        })
+    }
+
+    #[napi(getter)]
+    pub fn path(&self) -> String {
+        self.path.clone()
+    }
+
+    #[napi]
+    pub fn byte_length(&self) -> Result<i64, FsErrorCode> {
+        Ok(self.mapping()?.len() as i64)
+    }
+
+    /// Finish indexing lines on a worker thread; resolves to the line count.
+    #[napi(ts_return_type = "Promise<number>")]
+    pub fn index_lines(&self) -> Result<AsyncTask<IndexLines>, FsErrorCode> {
+        Ok(AsyncTask::new(IndexLines {
+            path: self.path.clone(),
+            mapping: Arc::clone(self.mapping()?),
+            index: Arc::clone(&self.index),
+        }))
+    }
+
+    /// Number of lines, counting the empty line after a trailing newline like an editor does.
+    ///
+    /// Until `indexLines` has resolved, this scans the rest of the file on the calling thread.
+    #[napi]
+    pub fn line_count(&self) -> Result<i64, FsErrorCode> {
+        let mapping = self.mapping()?;
+        let mut index = self.index.lock().unwrap();
+        while !index.complete {
+            index.scan_chunk(mapping, &self.path)?;
+        }
+        Ok(index.line_count() as i64)
+    }
+
+    /// Byte offset where `line` (zero-based) starts.
+    #[napi]
+    pub fn line_offset(&self, line: i64) -> Result<i64, FsErrorCode> {
+        Ok(self.line_start(line)? as i64)
+    }
+
+    /// Read up to `count` lines starting at `start`, without their line terminators.
+    #[napi]
+    pub fn read_lines(&self, start: i64, count: u32) -> Result<Vec<String>, FsErrorCode> {
+        let mut offset = self.line_start(start)?;
+        let mapping = self.mapping()?;
+        let mut lines = Vec::with_capacity(count as usize);
+
+        while lines.len() < count as usize {
+            let newline = mapping.find_newline(&self.path, offset)?;
+            let end = newline.unwrap_or(mapping.len());
+            let line = mapping.bytes(&self.path, offset, end)?;
+            let line = line.strip_suffix(b"\r").unwrap_or(line);
+            lines.push(String::from_utf8_lossy(line).into_owned());
+            let Some(newline) = newline else {
+                break;
+            };
+            offset = newline + 1;
+        }
+
+        Ok(lines)
+    }
+
+    /// Read a window of raw bytes, clamped to the end of the file.
+    #[napi]
+    pub fn read_bytes(&self, offset: i64, length: u32) -> Result<Buffer, FsErrorCode> {
+        let mapping = self.mapping()?;
+        if offset < 0 {
+            return Err(FsError::OutOfRange(format!("Negative offset {} for {}", offset, self.path)).into());
+        }
+
+        let start = offset as usize;
+        let mut bytes = Vec::new();
+        let end = start.saturating_add(length as usize).min(mapping.len());
+        let mut from = start;
+        while from < end {
+            let chunk = mapping.bytes(&self.path, from, from.saturating_add(SCAN_CHUNK_LEN).min(end))?;
+            bytes.extend_from_slice(chunk);
+            from += chunk.len();
+        }
+        Ok(bytes.into())
+    }
+
+    /// Release the mapping; any further call fails.
+    #[napi]
+    pub fn close(&mut self) {
+        self.mapping = None;
+    }
+
+    fn mapping(&self) -> std::result::Result<&Arc<Mapping>, FsError> {
+        self.mapping.as_ref().ok_or_else(|| FsError::HandleClosed(self.path.clone()))
+    }
+
+    fn line_start(&self, line: i64) -> std::result::Result<usize, FsError> {
+        let mapping = self.mapping()?;
+        let out_of_range = || FsError::OutOfRange(format!("Line {} is out of range for {}", line, self.path));
+        if line < 0 {
+            return Err(out_of_range());
+        }
+        let line = line as usize;
+
+        let mut index = self.index.lock().unwrap();
+        while index.newlines < line && !index.complete {
+            index.scan_chunk(mapping, &self.path)?;
+        }
+        if line >= index.line_count() {
+            return Err(out_of_range());
+        }
+
+        let checkpoint = index.checkpoints[line / LINES_PER_CHECKPOINT];
+        let skip = line % LINES_PER_CHECKPOINT;
+        if skip == 0 {
+            return Ok(checkpoint);
+        }
+
+        let mut offset = checkpoint;
+        for _ in 0..skip {
+            let newline = mapping.find_newline(&self.path, offset)?
+                .ok_or_else(|| FsError::Conflict(format!("{} changed while open", self.path)))?;
+            offset = newline + 1;
+        }
+        Ok(offset)
+    }
+}
+
+pub struct IndexLines {
+    path: String,
+    mapping: Arc<Mapping>,
+    index: Arc<Mutex<LineIndex>>,
+}
+
+impl Task for IndexLines {
+    type Output = i64;
+    type JsValue = i64;
+
+    fn compute(&mut self) -> Result<Self::Output> {
+        loop {
+            // Lock per chunk so reads on the JS thread are not held up for the whole scan
+            let mut index = self.index.lock().unwrap();
+            if index.complete {
+                return Ok(index.line_count() as i64);
+            }
+            index.scan_chunk(&self.mapping, &self.path)
+                .map_err(|e| napi::Error::new(Status::GenericFailure, format!("{}", e)))?;
+        }
+    }
+
+    fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+        Ok(output)
+    }
+}
@@ -3,19 +3,27 @@
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::fs;
+use std::io::Write;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
 
-use napi::bindgen_prelude::*;
 use napi::{Result, Status};
 use napi_derive::napi;
 use napi::threadsafe_function::ThreadsafeFunction;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
-use walkdir::WalkDir;
 
 use notify::{Config, Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
 
+pub mod encoding;
+pub mod fuzzy;
+pub mod index;
+pub mod large_file;
+pub mod probe;
+pub mod replace;
+pub mod search;
+
 #[derive(Debug, Error)]
 pub enum FsError {
     #[error("IO error: {0}")]
@@ -29,6 +37,71 @@ pub enum FsError {
     
     #[error("Invalid path: {0}")]
     InvalidPath(String),
+
+    #[error("Invalid pattern: {0}")]
+    InvalidPattern(String),
+
+    #[error("Path already exists: {0}")]
+    AlreadyExists(String),
+
+    #[error("Permission denied: {0}")]
+    PermissionDenied(String),
+
+    #[error("Directory is not empty: {0}")]
+    NotEmpty(String),
+
+    #[error("Encoding error: {0}")]
+    Encoding(String),
+
+    #[error("File changed on disk: {0}")]
+    Conflict(String),
+
+    #[error("Cannot replace atomically: {0}")]
+    NotAtomic(String),
+
+    #[error("Edits overlap in {0}")]
+    OverlappingEdits(String),
+
+    #[error("File handle is closed: {0}")]
+    HandleClosed(String),
+
+    #[error("Out of range: {0}")]
+    OutOfRange(String),
+}
+
+impl FsError {
+    /// Classify an IO error for `path` into one of the typed variants so callers
+    /// can tell an existing target from a permission problem.
+    fn from_io(error: std::io::Error, path: &Path) -> Self {
+        let path = path.display().to_string();
+        match error.kind() {
+            std::io::ErrorKind::NotFound => FsError::PathNotFound(path),
+            std::io::ErrorKind::AlreadyExists => FsError::AlreadyExists(path),
+            std::io::ErrorKind::PermissionDenied => FsError::PermissionDenied(path),
+            std::io::ErrorKind::DirectoryNotEmpty => FsError::NotEmpty(path),
+            _ => FsError::Io(error),
+        }
+    }
+
+    /// Stable code exposed to JS as `error.code`.
+    fn code(&self) -> FsErrorCode {
+        FsErrorCode(match self {
+            FsError::Io(_) => "IO_ERROR",
+            FsError::Watcher(_) => "WATCHER_ERROR",
+            FsError::PathNotFound(_) => "PATH_NOT_FOUND",
+            FsError::InvalidPath(_) => "INVALID_PATH",
+            FsError::InvalidPattern(_) => "INVALID_PATTERN",
+            FsError::AlreadyExists(_) => "ALREADY_EXISTS",
+            FsError::PermissionDenied(_) => "PERMISSION_DENIED",
+            FsError::NotEmpty(_) => "NOT_EMPTY",
+            FsError::Encoding(_) => "ENCODING_ERROR",
+            FsError::Conflict(_) => "CONFLICT",
+            FsError::NotAtomic(_) => "NOT_ATOMIC",
+            FsError::OverlappingEdits(_) => "OVERLAPPING_EDITS",
+            FsError::HandleClosed(_) => "HANDLE_CLOSED",
+            FsError::OutOfRange(_) => "OUT_OF_RANGE",
+        })
+    }
 }
 
 impl From<FsError> for napi::Error {
@@ -37,6 +110,22 @@ impl From<FsError> for napi::Error {
     }
 }
 
+impl From<FsError> for napi::Error<FsErrorCode> {
+    fn from(error: FsError) -> Self {
+        napi::Error::new(error.code(), format!("{}", error))
+    }
+}
+
+/// Error status for exports that surface typed `FsError` codes to JS.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FsErrorCode(&'static str);
+
+impl AsRef<str> for FsErrorCode {
+    fn as_ref(&self) -> &str {
+        self.0
+    }
+}
+
 #[napi(object)]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileEntry {
@@ -263,6 +352,185 @@ pub fn read_file_contents(path: String) -> Result<String> {
     })
 }
 
+#[napi(object)]
+#[derive(Debug, Clone, Default)]
+pub struct SaveOptions {
+    /// Keep the previous contents in `<path>.bak` before replacing the file
+    pub backup: Option<bool>,
+    /// Overwrite the file in place when replacing it would break hard links or
+    /// change its owner, instead of failing with `NOT_ATOMIC`
+    pub allow_in_place: Option<bool>,
+}
+
+#[napi]
+pub fn save_file_atomic(path: String, contents: String, options: Option<SaveOptions>) -> Result<FileEntry, FsErrorCode> {
+    let path = Path::new(&path);
+    let options = options.unwrap_or_default();
+
+    write_atomic(
+        path,
+        contents.as_bytes(),
+        options.backup.unwrap_or(false),
+        options.allow_in_place.unwrap_or(false),
+    )?;
+
+    Ok(entry_for_path(path)?)
+}
+
+static TEMP_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Write `contents` to a sibling temp file, fsync it and rename it over `path`,
+/// so a crash mid-save leaves either the old or the new file, never a truncated one.
+///
+/// When a rename would break hard links or change the owner, the file is
+/// overwritten in place only if `allow_in_place` is set; otherwise this fails
+/// with `FsError::NotAtomic` and the file is left untouched.
+pub(crate) fn write_atomic(path: &Path, contents: &[u8], backup: bool, allow_in_place: bool) -> std::result::Result<(), FsError> {
+    // Replace the file a symlink points at rather than the link itself
+    let target = match fs::canonicalize(path) {
+        Ok(target) => target,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => path.to_path_buf(),
+        Err(e) => return Err(FsError::from_io(e, path)),
+    };
+
+    let original = match fs::metadata(&target) {
+        Ok(metadata) => Some(metadata),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+        Err(e) => return Err(FsError::from_io(e, &target)),
+    };
+
+    if original.as_ref().is_some_and(|m| m.is_dir()) {
+        return Err(FsError::InvalidPath(target.display().to_string()));
+    }
+
+    if backup && original.is_some() {
+        let mut backup_path = target.clone().into_os_string();
+        backup_path.push(".bak");
+        let backup_path = PathBuf::from(backup_path);
+        fs::copy(&target, &backup_path)
+            .and_then(|_| fs::File::open(&backup_path)?.sync_all())
+            .map_err(|e| FsError::from_io(e, &backup_path))?;
+    }
+
+    let dir = match target.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+    let name = target.file_name()
+        .ok_or_else(|| FsError::InvalidPath(target.display().to_string()))?
+        .to_string_lossy()
+        .to_string();
+
+    let (temp_path, mut file) = loop {
+        let counter = TEMP_COUNTER.fetch_add(1, Ordering::Relaxed);
+        let candidate = dir.join(format!(".{}.{}-{}.tmp", name, std::process::id(), counter));
+        match fs::OpenOptions::new().write(true).create_new(true).open(&candidate) {
+            Ok(file) => break (candidate, file),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(FsError::from_io(e, &dir)),
+        }
+    };
+
+    if let Some(metadata) = &original {
+        match preserve_metadata(&file, &temp_path, &target, metadata) {
+            Ok(Preserved::Yes) => {}
+            Ok(Preserved::No(reason)) => {
+                drop(file);
+                let _ = fs::remove_file(&temp_path);
+                // Overwriting in place is not crash-safe, so only do it when asked to
+                if !allow_in_place {
+                    return Err(FsError::NotAtomic(format!("{} ({})", target.display(), reason)));
+                }
+                return write_in_place(&target, contents).map_err(|e| FsError::from_io(e, &target));
+            }
+            Err(e) => {
+                drop(file);
+                let _ = fs::remove_file(&temp_path);
+                return Err(FsError::from_io(e, &target));
+            }
+        }
+    }
+
+    let result = (|| {
+        file.write_all(contents)?;
+        file.sync_all()?;
+        drop(file);
+
+        fs::rename(&temp_path, &target)?;
+        sync_directory(&dir)
+    })();
+
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+
+    result.map_err(|e| FsError::from_io(e, &target))
+}
+
+fn write_in_place(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    let mut file = fs::OpenOptions::new().write(true).truncate(true).open(path)?;
+    file.write_all(contents)?;
+    file.sync_all()
+}
+
+/// Whether the temp file could take over the original's metadata, and if
+/// not, why renaming it over the original would lose something.
+enum Preserved {
+    Yes,
+    No(&'static str),
+}
+
+/// Copy permissions, ownership and extended attributes of `original` onto the
+/// temp file.
+#[cfg(unix)]
+fn preserve_metadata(file: &fs::File, temp_path: &Path, target: &Path, original: &fs::Metadata) -> std::io::Result<Preserved> {
+    use std::os::unix::fs::MetadataExt;
+
+    if original.nlink() > 1 {
+        return Ok(Preserved::No("the file has other hard links"));
+    }
+
+    let temp = file.metadata()?;
+    if temp.uid() != original.uid() || temp.gid() != original.gid() {
+        if let Err(e) = std::os::unix::fs::fchown(file, Some(original.uid()), Some(original.gid())) {
+            if e.kind() == std::io::ErrorKind::PermissionDenied {
+                return Ok(Preserved::No("the owner cannot be kept"));
+            }
+            return Err(e);
+        }
+    }
+
+    file.set_permissions(original.permissions())?;
+
+    // Extended attributes are best effort: some (e.g. security labels) can't be set by users
+    if let Ok(names) = xattr::list(target) {
+        for name in names {
+            if let Ok(Some(value)) = xattr::get(target, &name) {
+                let _ = xattr::set(temp_path, &name, &value);
+            }
+        }
+    }
+
+    Ok(Preserved::Yes)
+}
+
+#[cfg(not(unix))]
+fn preserve_metadata(file: &fs::File, _temp_path: &Path, _target: &Path, original: &fs::Metadata) -> std::io::Result<Preserved> {
+    file.set_permissions(original.permissions())?;
+    Ok(Preserved::Yes)
+}
+
+#[cfg(unix)]
+fn sync_directory(dir: &Path) -> std::io::Result<()> {
+    // Make the rename itself durable
+    fs::File::open(dir)?.sync_all()
+}
+
+#[cfg(not(unix))]
+fn sync_directory(_dir: &Path) -> std::io::Result<()> {
+    Ok(())
+}
+
 fn build_file_tree(path: &Path) -> Result<FileEntry> {
     let metadata = fs::metadata(path).map_err(|e| {
         napi::Error::new(
@@ -361,3 +629,193 @@ pub fn expand_directory(path: String) -> Result<Vec<FileEntry>> {
     
     get_directory_contents(path.to_string_lossy().to_string())
 }
+
+#[napi]
+pub fn write_file_contents(path: String, contents: String) -> Result<(), FsErrorCode> {
+    let path = Path::new(&path);
+
+    if path.is_dir() {
+        return Err(FsError::InvalidPath(path.display().to_string()).into());
+    }
+
+    fs::write(path, contents).map_err(|e| FsError::from_io(e, path))?;
+    Ok(())
+}
+
+#[napi]
+pub fn create_file(path: String) -> Result<FileEntry, FsErrorCode> {
+    let path = Path::new(&path);
+
+    fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path)
+        .map_err(|e| FsError::from_io(e, path))?;
+
+    Ok(entry_for_path(path)?)
+}
+
+#[napi]
+pub fn create_directory(path: String, recursive: Option<bool>) -> Result<FileEntry, FsErrorCode> {
+    let path = Path::new(&path);
+
+    if path.exists() {
+        return Err(FsError::AlreadyExists(path.display().to_string()).into());
+    }
+
+    let result = if recursive.unwrap_or(false) {
+        fs::create_dir_all(path)
+    } else {
+        fs::create_dir(path)
+    };
+    result.map_err(|e| FsError::from_io(e, path))?;
+
+    Ok(entry_for_path(path)?)
+}
+
+#[napi]
+pub fn rename_path(path: String, new_name: String) -> Result<FileEntry, FsErrorCode> {
+    let source = Path::new(&path);
+
+    if new_name.is_empty() || new_name == "." || new_name == ".." || new_name.contains(['/', '\\']) {
+        return Err(FsError::InvalidPath(new_name).into());
+    }
+
+    let parent = source.parent()
+        .ok_or_else(|| FsError::InvalidPath(source.display().to_string()))?;
+    let target = parent.join(&new_name);
+
+    // Allow case-only renames, where the target already resolves to the source
+    if target.exists() && !is_same_file(source, &target) {
+        return Err(FsError::AlreadyExists(target.display().to_string()).into());
+    }
+
+    fs::rename(source, &target).map_err(|e| FsError::from_io(e, source))?;
+
+    Ok(entry_for_path(&target)?)
+}
+
+#[napi]
+pub fn move_path(source: String, destination_dir: String) -> Result<FileEntry, FsErrorCode> {
+    let source = Path::new(&source);
+    let destination_dir = Path::new(&destination_dir);
+
+    let name = source.file_name()
+        .ok_or_else(|| FsError::InvalidPath(source.display().to_string()))?;
+
+    if !destination_dir.is_dir() {
+        return Err(FsError::InvalidPath(destination_dir.display().to_string()).into());
+    }
+
+    // Moving a directory into itself or one of its descendants would lose it
+    if let (Ok(src), Ok(dest)) = (fs::canonicalize(source), fs::canonicalize(destination_dir)) {
+        if src.is_dir() && dest.starts_with(&src) {
+            return Err(FsError::InvalidPath(destination_dir.display().to_string()).into());
+        }
+    }
+
+    let target = destination_dir.join(name);
+
+    if target.exists() {
+        return Err(FsError::AlreadyExists(target.display().to_string()).into());
+    }
+
+    if let Err(e) = fs::rename(source, &target) {
+        if e.kind() != std::io::ErrorKind::CrossesDevices {
+            return Err(FsError::from_io(e, source).into());
+        }
+
+        // rename(2) cannot cross filesystems, so fall back to copy + delete
+        if let Err(e) = copy_recursive(source, &target) {
+            // The source is untouched, so drop the partial copy rather than leave two halves
+            let _ = remove_path(&target, true);
+            return Err(e.into());
+        }
+        remove_path(source, true)?;
+    }
+
+    Ok(entry_for_path(&target)?)
+}
+
+#[napi]
+pub fn delete_path(path: String, recursive: Option<bool>) -> Result<(), FsErrorCode> {
+    let path = Path::new(&path);
+
+    remove_path(path, recursive.unwrap_or(false))?;
+    Ok(())
+}
+
+fn remove_path(path: &Path, recursive: bool) -> std::result::Result<(), FsError> {
+    // Use symlink_metadata so a link to a directory is removed, not followed
+    let metadata = fs::symlink_metadata(path).map_err(|e| FsError::from_io(e, path))?;
+
+    let result = if !metadata.is_dir() {
+        fs::remove_file(path)
+    } else if recursive {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_dir(path)
+    };
+
+    result.map_err(|e| FsError::from_io(e, path))
+}
+
+fn copy_recursive(source: &Path, target: &Path) -> std::result::Result<(), FsError> {
+    let metadata = fs::symlink_metadata(source).map_err(|e| FsError::from_io(e, source))?;
+
+    if metadata.file_type().is_symlink() {
+        return copy_symlink(source, target);
+    }
+    if !metadata.is_dir() {
+        fs::copy(source, target).map_err(|e| FsError::from_io(e, target))?;
+        return Ok(());
+    }
+
+    fs::create_dir(target).map_err(|e| FsError::from_io(e, target))?;
+    fs::set_permissions(target, metadata.permissions()).map_err(|e| FsError::from_io(e, target))?;
+
+    for entry in fs::read_dir(source).map_err(|e| FsError::from_io(e, source))? {
+        let entry = entry.map_err(|e| FsError::from_io(e, source))?;
+        copy_recursive(&entry.path(), &target.join(entry.file_name()))?;
+    }
+
+    Ok(())
+}
+
+/// Recreate the link itself, so it keeps pointing where it did instead of
+/// becoming a copy of its target.
+#[cfg(unix)]
+fn copy_symlink(source: &Path, target: &Path) -> std::result::Result<(), FsError> {
+    let link = fs::read_link(source).map_err(|e| FsError::from_io(e, source))?;
+    std::os::unix::fs::symlink(link, target).map_err(|e| FsError::from_io(e, target))
+}
+
+#[cfg(not(unix))]
+fn copy_symlink(source: &Path, target: &Path) -> std::result::Result<(), FsError> {
+    fs::copy(source, target).map_err(|e| FsError::from_io(e, target))?;
+    Ok(())
+}
+
+fn is_same_file(a: &Path, b: &Path) -> bool {
+    match (fs::canonicalize(a), fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+fn entry_for_path(path: &Path) -> std::result::Result<FileEntry, FsError> {
+    let metadata = fs::metadata(path).map_err(|e| FsError::from_io(e, path))?;
+
+    let name = path.file_name()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_string_lossy().to_string());
+
+    Ok(FileEntry {
+        name,
+        path: path.to_string_lossy().to_string(),
+        is_dir: metadata.is_dir(),
+        is_file: metadata.is_file(),
+        size: if metadata.is_file() { Some(metadata.len() as i64) } else { None },
+        children: None,
+    })
+}
@@ -0,0 +1,216 @@
+use std::fs;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+use encoding_rs::{UTF_16BE, UTF_16LE};
+use napi::bindgen_prelude::Buffer;
+use napi::Result;
+use napi_derive::napi;
+
+use crate::encoding::detect_encoding;
+use crate::{FsError, FsErrorCode};
+
+/// How many leading bytes are read to classify a file.
+const SNIFF_LEN: usize = 8192;
+
+/// Text files above this size are reported as `too-large` unless overridden.
+const DEFAULT_MAX_TEXT_SIZE: i64 = 50 * 1024 * 1024;
+
+/// Upper bound for a single preview read, so the UI pages through big files.
+const MAX_CHUNK_LEN: u32 = 1024 * 1024;
+
+const BYTES_PER_HEX_LINE: usize = 16;
+
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct FileProbe {
+    pub path: String,
+    pub size: i64,
+    /// One of `text`, `binary`, `image`, `archive` or `too-large`
+    pub kind: String,
+    /// MIME type for recognized formats
+    pub mime_type: Option<String>,
+    /// Detected encoding when the file looks like text
+    pub encoding: Option<String>,
+}
+
+#[napi(object)]
+#[derive(Debug, Clone, Default)]
+pub struct ProbeOptions {
+    /// Text files larger than this many bytes are reported as `too-large`
+    pub max_text_size: Option<i64>,
+}
+
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct HexDumpLine {
+    pub offset: i64,
+    /// Space separated lowercase hex bytes
+    pub hex: String,
+    /// Printable ASCII, with other bytes shown as `.`
+    pub ascii: String,
+}
+
+#[napi]
+pub fn probe_file(path: String, options: Option<ProbeOptions>) -> Result<FileProbe, FsErrorCode> {
+    let path = Path::new(&path);
+    let max_text_size = options
+        .and_then(|o| o.max_text_size)
+        .unwrap_or(DEFAULT_MAX_TEXT_SIZE);
+
+    let metadata = fs::metadata(path).map_err(|e| FsError::from_io(e, path))?;
+    if !metadata.is_file() {
+        return Err(FsError::InvalidPath(path.display().to_string()).into());
+    }
+
+    let mut head = Vec::with_capacity(SNIFF_LEN);
+    fs::File::open(path)
+        .and_then(|file| file.take(SNIFF_LEN as u64).read_to_end(&mut head))
+        .map_err(|e| FsError::from_io(e, path))?;
+
+    // The sniff window can end inside a multi-byte character of a longer file
+    if metadata.len() > head.len() as u64 {
+        let complete = trim_partial_utf8(&head).len();
+        head.truncate(complete);
+    }
+
+    let size = metadata.len() as i64;
+    let mut probe = FileProbe {
+        path: path.to_string_lossy().to_string(),
+        size,
+        kind: "binary".to_string(),
+        mime_type: None,
+        encoding: None,
+    };
+
+    if let Some((kind, mime_type)) = sniff_magic(&head) {
+        probe.kind = kind.to_string();
+        probe.mime_type = Some(mime_type.to_string());
+    } else if !looks_binary(&head) {
+        let (encoding, _) = detect_encoding(&head);
+        probe.kind = if size > max_text_size { "too-large" } else { "text" }.to_string();
+        probe.encoding = Some(encoding.name().to_lowercase());
+    }
+
+    Ok(probe)
+}
+
+#[napi]
+pub fn read_file_chunk(path: String, offset: i64, length: u32) -> Result<Buffer, FsErrorCode> {
+    let path = Path::new(&path);
+    Ok(read_chunk(path, offset, length)?.into())
+}
+
+#[napi]
+pub fn hex_dump(path: String, offset: i64, length: u32) -> Result<Vec<HexDumpLine>, FsErrorCode> {
+    let path = Path::new(&path);
+    let bytes = read_chunk(path, offset, length)?;
+
+    Ok(bytes
+        .chunks(BYTES_PER_HEX_LINE)
+        .enumerate()
+        .map(|(i, line)| HexDumpLine {
+            offset: offset + (i * BYTES_PER_HEX_LINE) as i64,
+            hex: line.iter().map(|b| format!("{:02x}", b)).collect::<Vec<_>>().join(" "),
+            ascii: line
+                .iter()
+                .map(|&b| if b.is_ascii_graphic() || b == b' ' { b as char } else { '.' })
+                .collect(),
+        })
+        .collect())
+}
+
+fn read_chunk(path: &Path, offset: i64, length: u32) -> std::result::Result<Vec<u8>, FsError> {
+    if offset < 0 {
+        return Err(FsError::OutOfRange(format!("Negative offset {} for {}", offset, path.display())));
+    }
+
+    let mut file = fs::File::open(path).map_err(|e| FsError::from_io(e, path))?;
+    file.seek(SeekFrom::Start(offset as u64)).map_err(|e| FsError::from_io(e, path))?;
+
+    let mut bytes = Vec::new();
+    file.take(length.min(MAX_CHUNK_LEN) as u64)
+        .read_to_end(&mut bytes)
+        .map_err(|e| FsError::from_io(e, path))?;
+
+    Ok(bytes)
+}
+
+/// Drop a UTF-8 sequence cut off at the end of `bytes`, so valid UTF-8 still validates.
+fn trim_partial_utf8(bytes: &[u8]) -> &[u8] {
+    match std::str::from_utf8(bytes) {
+        Err(e) if e.error_len().is_none() => &bytes[..e.valid_up_to()],
+        _ => bytes,
+    }
+}
+
+/// Recognize common formats by their magic numbers.
+fn sniff_magic(head: &[u8]) -> Option<(&'static str, &'static str)> {
+    const SIGNATURES: &[(&[u8], &str, &str)] = &[
+        (b"\x89PNG\r\n\x1a\n", "image", "image/png"),
+        (b"\xFF\xD8\xFF", "image", "image/jpeg"),
+        (b"GIF87a", "image", "image/gif"),
+        (b"GIF89a", "image", "image/gif"),
+        (b"\x00\x00\x01\x00", "image", "image/x-icon"),
+        (b"II*\x00", "image", "image/tiff"),
+        (b"MM\x00*", "image", "image/tiff"),
+        (b"PK\x03\x04", "archive", "application/zip"),
+        (b"PK\x05\x06", "archive", "application/zip"),
+        (b"\x1F\x8B", "archive", "application/gzip"),
+        (b"BZh", "archive", "application/x-bzip2"),
+        (b"\xFD7zXZ\x00", "archive", "application/x-xz"),
+        (b"7z\xBC\xAF\x27\x1C", "archive", "application/x-7z-compressed"),
+        (b"Rar!\x1A\x07", "archive", "application/vnd.rar"),
+        (b"\x28\xB5\x2F\xFD", "archive", "application/zstd"),
+        (b"\x7FELF", "binary", "application/x-elf"),
+        (b"\xFE\xED\xFA\xCE", "binary", "application/x-mach-binary"),
+        (b"\xFE\xED\xFA\xCF", "binary", "application/x-mach-binary"),
+        (b"\xCE\xFA\xED\xFE", "binary", "application/x-mach-binary"),
+        (b"\xCF\xFA\xED\xFE", "binary", "application/x-mach-binary"),
+        (b"SQLite format 3\x00", "binary", "application/vnd.sqlite3"),
+        (b"\x00asm", "binary", "application/wasm"),
+        (b"%PDF-", "binary", "application/pdf"),
+    ];
+
+    if head.len() >= 12 && &head[..4] == b"RIFF" && &head[8..12] == b"WEBP" {
+        return Some(("image", "image/webp"));
+    }
+
+    if head.len() >= 262 && &head[257..262] == b"ustar" {
+        return Some(("archive", "application/x-tar"));
+    }
+
+    // Two-letter signatures also start plain text, so require binary header fields
+    if head.len() >= 10 && head.starts_with(b"BM") && head[6..10] == [0, 0, 0, 0] {
+        return Some(("image", "image/bmp"));
+    }
+
+    if head.starts_with(b"MZ") && head.contains(&0) {
+        return Some(("binary", "application/vnd.microsoft.portable-executable"));
+    }
+
+    SIGNATURES
+        .iter()
+        .find(|(magic, _, _)| head.starts_with(magic))
+        .map(|(_, kind, mime_type)| (*kind, *mime_type))
+}
+
+/// Heuristic used by most editors: text has no NUL bytes and few control characters.
+pub(crate) fn looks_binary(head: &[u8]) -> bool {
+    // UTF-16 text is full of NUL bytes, so trust the encoding sniffer first
+    let (encoding, _) = detect_encoding(head);
+    if encoding == UTF_16LE || encoding == UTF_16BE {
+        return false;
+    }
+
+    if head.contains(&0) {
+        return true;
+    }
+
+    let control = head
+        .iter()
+        .filter(|&&b| b < 0x20 && !matches!(b, b'\n' | b'\r' | b'\t' | 0x0C | 0x1B))
+        .count();
+
+    control * 10 > head.len()
+}
@@ -0,0 +1,297 @@
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use encoding_rs::Encoding;
+use napi::bindgen_prelude::{AsyncTask, Buffer};
+use napi::{Env, Result, Task};
+use napi_derive::napi;
+use xxhash_rust::xxh3::xxh3_64;
+
+use crate::encoding::{decode_bytes, encode_text};
+use crate::search::{split_lines, utf16_len, SearchOptions, SearchScope};
+use crate::{write_atomic, FsError, FsErrorCode};
+
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct ReplaceEdit {
+    /// Zero-based line number
+    pub line: u32,
+    /// UTF-16 column of the match start within the line
+    pub column: u32,
+    /// UTF-16 length of the matched text
+    pub length: u32,
+    pub matched: String,
+    /// Replacement with capture groups already expanded
+    pub replacement: String,
+    /// The line as it is now
+    pub before: String,
+    /// The line with only this edit applied
+    pub after: String,
+}
+
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct FileReplacement {
+    /// Project-relative path, separated by `/`
+    pub path: String,
+    /// Identifies the contents the edits were computed against
+    pub fingerprint: String,
+    pub edits: Vec<ReplaceEdit>,
+}
+
+/// Edits that `applyReplace` will make. Files or edits can be dropped before applying.
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct ReplacePlan {
+    pub root: String,
+    pub files: Vec<FileReplacement>,
+    pub match_count: u32,
+}
+
+#[napi(object)]
+#[derive(Clone)]
+pub struct UndoEntry {
+    pub path: String,
+    /// Exact bytes the file had before the replace
+    pub original: Buffer,
+    /// Identifies the contents the replace wrote
+    pub fingerprint: String,
+}
+
+/// Everything needed to revert an applied replace.
+#[napi(object)]
+#[derive(Clone)]
+pub struct UndoManifest {
+    pub root: String,
+    pub files: Vec<UndoEntry>,
+}
+
+/// Compute, without touching any file, what replacing `query` with `replacement` would do.
+///
+/// With `isRegex`, `$1` or `${name}` in `replacement` expand to capture groups.
+/// The query and patterns are checked up front; the files are read on a worker thread.
+#[napi(ts_return_type = "Promise<ReplacePlan>")]
+pub fn plan_replace(
+    root: String,
+    query: String,
+    replacement: String,
+    options: Option<SearchOptions>,
+) -> Result<AsyncTask<PlanReplace>, FsErrorCode> {
+    let options = options.unwrap_or_default();
+    Ok(AsyncTask::new(PlanReplace {
+        scope: SearchScope::new(&root, &query, &options)?,
+        replacement,
+        expand: options.is_regex.unwrap_or(false),
+    }))
+}
+
+pub struct PlanReplace {
+    scope: SearchScope,
+    replacement: String,
+    expand: bool,
+}
+
+impl Task for PlanReplace {
+    type Output = ReplacePlan;
+    type JsValue = ReplacePlan;
+
+    fn compute(&mut self) -> Result<Self::Output> {
+        let scope = &self.scope;
+        let mut files = Vec::new();
+        let mut match_count = 0;
+        for (path, relative) in scope.files() {
+            let Some((bytes, decoded)) = scope.read_text(&path) else {
+                continue;
+            };
+
+            let mut edits = Vec::new();
+            for (number, line) in split_lines(&decoded.contents).into_iter().enumerate() {
+                for captures in scope.regex.captures_iter(line) {
+                    let found = captures.get(0).expect("group 0 is always present");
+                    if found.is_empty() {
+                        continue;
+                    }
+
+                    let mut replaced = String::new();
+                    if self.expand {
+                        captures.expand(&self.replacement, &mut replaced);
+                    } else {
+                        replaced.push_str(&self.replacement);
+                    }
+
+                    edits.push(ReplaceEdit {
+                        line: number as u32,
+                        column: utf16_len(&line[..found.start()]),
+                        length: utf16_len(found.as_str()),
+                        matched: found.as_str().to_string(),
+                        before: line.to_string(),
+                        after: format!("{}{}{}", &line[..found.start()], replaced, &line[found.end()..]),
+                        replacement: replaced,
+                    });
+                }
+            }
+
+            if !edits.is_empty() {
+                match_count += edits.len() as u32;
+                files.push(FileReplacement {
+                    path: relative,
+                    fingerprint: fingerprint(&bytes),
+                    edits,
+                });
+            }
+        }
+
+        Ok(ReplacePlan {
+            root: scope.root().to_string_lossy().to_string(),
+            files,
+            match_count,
+        })
+    }
+
+    fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+        Ok(output)
+    }
+}
+
+/// Apply a plan, replacing each file atomically in its original encoding.
+///
+/// Nothing is written if any file changed since the plan was made, and files
+/// already written are restored if a later one fails.
+#[napi]
+pub fn apply_replace(plan: ReplacePlan) -> Result<UndoManifest, FsErrorCode> {
+    let root = PathBuf::from(&plan.root);
+
+    let mut writes = Vec::with_capacity(plan.files.len());
+    for file in &plan.files {
+        let path = resolve(&root, &file.path)?;
+        let original = fs::read(&path).map_err(|e| FsError::from_io(e, &path))?;
+        if fingerprint(&original) != file.fingerprint {
+            return Err(FsError::Conflict(file.path.clone()).into());
+        }
+
+        let updated = apply_edits(&original, file)?;
+        writes.push((path, file.path.clone(), original, updated));
+    }
+
+    let mut written: Vec<(&Path, &[u8])> = Vec::with_capacity(writes.len());
+    for (path, _, original, updated) in &writes {
+        if let Err(error) = write_atomic(path, updated, false, false) {
+            restore(&written);
+            return Err(error.into());
+        }
+        written.push((path, original));
+    }
+
+    Ok(UndoManifest {
+        root: plan.root,
+        files: writes
+            .into_iter()
+            .map(|(_, relative, original, updated)| UndoEntry {
+                path: relative,
+                original: original.into(),
+                fingerprint: fingerprint(&updated),
+            })
+            .collect(),
+    })
+}
+
+/// Revert an applied replace. Fails without writing anything if a file was edited
+/// since, and puts back the replaced contents if a write fails part way.
+#[napi]
+pub fn undo_replace(manifest: UndoManifest) -> Result<Vec<String>, FsErrorCode> {
+    let root = PathBuf::from(&manifest.root);
+
+    let mut reverts = Vec::with_capacity(manifest.files.len());
+    for entry in &manifest.files {
+        let path = resolve(&root, &entry.path)?;
+        let current = fs::read(&path).map_err(|e| FsError::from_io(e, &path))?;
+        if fingerprint(&current) != entry.fingerprint {
+            return Err(FsError::Conflict(entry.path.clone()).into());
+        }
+        reverts.push((path, current));
+    }
+
+    let mut written: Vec<(&Path, &[u8])> = Vec::with_capacity(reverts.len());
+    for ((path, current), entry) in reverts.iter().zip(&manifest.files) {
+        if let Err(error) = write_atomic(path, &entry.original, false, false) {
+            restore(&written);
+            return Err(error.into());
+        }
+        written.push((path, current));
+    }
+
+    Ok(manifest.files.into_iter().map(|entry| entry.path).collect())
+}
+
+/// Apply `file.edits` to the decoded `bytes` and encode the result like the original.
+fn apply_edits(bytes: &[u8], file: &FileReplacement) -> std::result::Result<Vec<u8>, FsError> {
+    let decoded = decode_bytes(bytes);
+    let text = &decoded.contents;
+
+    let mut line_starts = vec![0];
+    line_starts.extend(memchr::memchr_iter(b'\n', text.as_bytes()).map(|i| i + 1));
+
+    let mut spans = Vec::with_capacity(file.edits.len());
+    for edit in &file.edits {
+        let start = line_starts
+            .get(edit.line as usize)
+            .and_then(|&line_start| byte_offset(text, line_start, edit.column))
+            .filter(|&start| text[start..].starts_with(&edit.matched))
+            .ok_or_else(|| FsError::Conflict(file.path.clone()))?;
+        spans.push((start, start + edit.matched.len(), edit.replacement.as_str()));
+    }
+
+    spans.sort_by_key(|(start, _, _)| *start);
+    if spans.windows(2).any(|pair| pair[1].0 < pair[0].1) {
+        return Err(FsError::OverlappingEdits(file.path.clone()));
+    }
+
+    let mut result = String::with_capacity(text.len());
+    let mut cursor = 0;
+    for (start, end, replacement) in spans {
+        result.push_str(&text[cursor..start]);
+        result.push_str(replacement);
+        cursor = end;
+    }
+    result.push_str(&text[cursor..]);
+
+    let encoding = Encoding::for_label(decoded.encoding.as_bytes())
+        .ok_or_else(|| FsError::Encoding(format!("Unknown encoding: {}", decoded.encoding)))?;
+    encode_text(&result, encoding, decoded.has_bom)
+}
+
+/// Best-effort rollback of files already replaced when a later write fails.
+fn restore(written: &[(&Path, &[u8])]) {
+    for (path, original) in written {
+        let _ = write_atomic(path, original, false, false);
+    }
+}
+
+/// Join a project-relative path onto `root`, refusing anything that escapes it.
+fn resolve(root: &Path, relative: &str) -> std::result::Result<PathBuf, FsError> {
+    let relative_path = Path::new(relative);
+    if relative.is_empty() || !relative_path.components().all(|c| matches!(c, Component::Normal(_))) {
+        return Err(FsError::InvalidPath(relative.to_string()));
+    }
+    Ok(root.join(relative_path))
+}
+
+/// Byte offset of the UTF-16 `column` in the line starting at `line_start`.
+fn byte_offset(text: &str, line_start: usize, column: u32) -> Option<usize> {
+    let mut utf16 = 0;
+    for (i, c) in text[line_start..].char_indices() {
+        if utf16 == column {
+            return Some(line_start + i);
+        }
+        if c == '\n' {
+            return None;
+        }
+        utf16 += c.len_utf16() as u32;
+    }
+    (utf16 == column).then_some(text.len())
+}
+
+/// Stable across processes and Rust versions, so plans and undo manifests can be persisted.
+fn fingerprint(bytes: &[u8]) -> String {
+    format!("{:016x}-{}", xxh3_64(bytes), bytes.len())
+}
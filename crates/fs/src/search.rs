@@ -0,0 +1,353 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+
+use globset::{GlobSet, GlobSetBuilder};
+use napi::threadsafe_function::{ThreadsafeFunction, ThreadsafeFunctionCallMode};
+use napi::Result;
+use napi_derive::napi;
+use regex::{Regex, RegexBuilder};
+
+use crate::encoding::{decode_bytes, DecodedFile};
+use crate::index::IgnoreRules;
+use crate::probe::looks_binary;
+use crate::{FsError, FsErrorCode};
+
+/// Bytes inspected when deciding whether a file is binary.
+const SNIFF_LEN: usize = 8192;
+
+/// Files above this size are skipped; they are almost always generated or data.
+const DEFAULT_MAX_FILE_SIZE: i64 = 10 * 1024 * 1024;
+
+/// Stop after this many matches unless the caller asks for more.
+const DEFAULT_MAX_RESULTS: u32 = 20_000;
+
+/// Preview lines are cut to this many characters so minified files stay cheap to ship.
+const MAX_PREVIEW_CHARS: usize = 500;
+
+#[napi(object)]
+#[derive(Debug, Clone, Default)]
+pub struct SearchOptions {
+    /// Treat the query as a regular expression instead of literal text
+    pub is_regex: Option<bool>,
+    pub case_sensitive: Option<bool>,
+    /// Only match whole words
+    pub whole_word: Option<bool>,
+    /// Glob patterns a project-relative path must match, e.g. `src/**/*.rs`
+    pub includes: Option<Vec<String>>,
+    /// Extra gitignore-style patterns to skip
+    pub excludes: Option<Vec<String>>,
+    /// Honor `.gitignore`, `.ignore` and `.git/info/exclude`; defaults to true
+    pub use_ignore_files: Option<bool>,
+    /// Lines of context reported before and after each match; defaults to 0
+    pub context_lines: Option<u32>,
+    pub max_results: Option<u32>,
+    /// Skip files larger than this many bytes
+    pub max_file_size: Option<i64>,
+}
+
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct SearchMatch {
+    /// Zero-based line number
+    pub line: u32,
+    /// UTF-16 column of the match start within `text`
+    pub column: u32,
+    /// UTF-16 length of the match; matches never span lines
+    pub length: u32,
+    /// The matching line, without its terminator
+    pub text: String,
+    pub before: Vec<String>,
+    pub after: Vec<String>,
+}
+
+#[napi(object)]
+#[derive(Debug, Clone, Default)]
+pub struct SearchUpdate {
+    /// Project-relative path, separated by `/`; empty on the final update
+    pub path: String,
+    pub matches: Vec<SearchMatch>,
+    /// True on the last update of a search, whether it finished or was cancelled
+    pub done: bool,
+    pub cancelled: bool,
+    /// True when the search stopped early because `maxResults` was reached
+    pub limit_hit: bool,
+    pub files_searched: u32,
+    pub match_count: u32,
+}
+
+/// A running search; results keep arriving on the callback until `done`.
+#[napi]
+pub struct SearchHandle {
+    cancelled: Arc<AtomicBool>,
+    done: Arc<AtomicBool>,
+}
+
+#[napi]
+impl SearchHandle {
+    /// Stop the search; a final update with `cancelled` set follows.
+    #[napi]
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    #[napi]
+    pub fn is_done(&self) -> bool {
+        self.done.load(Ordering::SeqCst)
+    }
+}
+
+/// Search every non-ignored text file under `root`, streaming matches per file.
+#[napi]
+pub fn search_project(
+    root: String,
+    query: String,
+    options: Option<SearchOptions>,
+    callback: ThreadsafeFunction<SearchUpdate>,
+) -> Result<SearchHandle, FsErrorCode> {
+    let options = options.unwrap_or_default();
+    let search = Arc::new(Search {
+        scope: SearchScope::new(&root, &query, &options)?,
+        context_lines: options.context_lines.unwrap_or(0) as usize,
+        max_results: options.max_results.unwrap_or(DEFAULT_MAX_RESULTS) as usize,
+        cancelled: Arc::new(AtomicBool::new(false)),
+        files_searched: AtomicUsize::new(0),
+        match_count: AtomicUsize::new(0),
+        callback,
+    });
+
+    let handle = SearchHandle {
+        cancelled: Arc::clone(&search.cancelled),
+        done: Arc::new(AtomicBool::new(false)),
+    };
+
+    let done = Arc::clone(&handle.done);
+    thread::spawn(move || {
+        search.run();
+        done.store(true, Ordering::SeqCst);
+        search.finish();
+    });
+
+    Ok(handle)
+}
+
+/// Which files a search or replace covers and the pattern it looks for.
+pub(crate) struct SearchScope {
+    rules: IgnoreRules,
+    includes: Option<GlobSet>,
+    pub(crate) regex: Regex,
+    max_file_size: i64,
+}
+
+impl SearchScope {
+    pub(crate) fn new(root: &str, query: &str, options: &SearchOptions) -> std::result::Result<Self, FsError> {
+        let root_path = fs::canonicalize(root).map_err(|e| FsError::from_io(e, Path::new(root)))?;
+        if !root_path.is_dir() {
+            return Err(FsError::InvalidPath(root.to_string()));
+        }
+
+        Ok(SearchScope {
+            rules: IgnoreRules::new(
+                &root_path,
+                options.excludes.as_deref().unwrap_or_default(),
+                options.use_ignore_files.unwrap_or(true),
+            )?,
+            includes: build_includes(options.includes.as_deref().unwrap_or_default())?,
+            regex: build_regex(query, options)?,
+            max_file_size: options.max_file_size.unwrap_or(DEFAULT_MAX_FILE_SIZE),
+        })
+    }
+
+    pub(crate) fn root(&self) -> &Path {
+        self.rules.root()
+    }
+
+    /// Absolute and project-relative paths of every file the search covers, found as the walk goes.
+    pub(crate) fn files(&self) -> impl Iterator<Item = (PathBuf, String)> + '_ {
+        self.rules.walk(self.rules.root()).filter_map(|path| {
+            let relative = self.rules.relative(&path)?;
+            let included = self.includes.as_ref().is_none_or(|globs| globs.is_match(&relative));
+            included.then_some((path, relative))
+        })
+    }
+
+    /// Raw bytes and decoded text of `path`, or `None` for oversized, unreadable or binary files.
+    pub(crate) fn read_text(&self, path: &Path) -> Option<(Vec<u8>, DecodedFile)> {
+        let metadata = fs::metadata(path).ok()?;
+        if metadata.len() as i64 > self.max_file_size {
+            return None;
+        }
+        let bytes = fs::read(path).ok()?;
+        if looks_binary(&bytes[..bytes.len().min(SNIFF_LEN)]) {
+            return None;
+        }
+        let decoded = decode_bytes(&bytes);
+        Some((bytes, decoded))
+    }
+}
+
+/// Lines of `text` without their terminators.
+pub(crate) fn split_lines(text: &str) -> Vec<&str> {
+    text.split('\n').map(|line| line.strip_suffix('\r').unwrap_or(line)).collect()
+}
+
+fn build_regex(query: &str, options: &SearchOptions) -> std::result::Result<Regex, FsError> {
+    if query.is_empty() {
+        return Err(FsError::InvalidPattern("Search query is empty".to_string()));
+    }
+
+    let mut pattern = if options.is_regex.unwrap_or(false) {
+        query.to_string()
+    } else {
+        regex::escape(query)
+    };
+    if options.whole_word.unwrap_or(false) {
+        pattern = format!(r"\b(?:{})\b", pattern);
+    }
+
+    RegexBuilder::new(&pattern)
+        .case_insensitive(!options.case_sensitive.unwrap_or(false))
+        .build()
+        .map_err(|e| FsError::InvalidPattern(format!("Invalid search pattern: {}", e)))
+}
+
+fn build_includes(patterns: &[String]) -> std::result::Result<Option<GlobSet>, FsError> {
+    if patterns.is_empty() {
+        return Ok(None);
+    }
+
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let glob = globset::Glob::new(pattern)
+            .map_err(|e| FsError::InvalidPattern(format!("Invalid include pattern {}: {}", pattern, e)))?;
+        builder.add(glob);
+    }
+    builder
+        .build()
+        .map(Some)
+        .map_err(|e| FsError::InvalidPattern(format!("Invalid include patterns: {}", e)))
+}
+
+struct Search {
+    scope: SearchScope,
+    context_lines: usize,
+    max_results: usize,
+    cancelled: Arc<AtomicBool>,
+    files_searched: AtomicUsize,
+    match_count: AtomicUsize,
+    callback: ThreadsafeFunction<SearchUpdate>,
+}
+
+impl Search {
+    fn run(&self) {
+        // Files are handed to the workers as the walk finds them, one at a time so a huge
+        // file does not stall a whole chunk; the bound keeps the walk from racing ahead
+        let threads = thread::available_parallelism().map_or(1, |n| n.get());
+        let (sender, receiver) = mpsc::sync_channel::<(PathBuf, String)>(threads * 4);
+        let receiver = Mutex::new(receiver);
+        thread::scope(|scope| {
+            for _ in 0..threads {
+                scope.spawn(|| loop {
+                    let Ok((path, relative)) = receiver.lock().unwrap().recv() else {
+                        break;
+                    };
+                    if !self.should_stop() {
+                        self.search_file(&path, &relative);
+                    }
+                });
+            }
+
+            for file in self.scope.files() {
+                if self.should_stop() || sender.send(file).is_err() {
+                    break;
+                }
+            }
+            drop(sender);
+        });
+    }
+
+    fn should_stop(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst) || self.match_count.load(Ordering::SeqCst) >= self.max_results
+    }
+
+    fn search_file(&self, path: &Path, relative: &str) {
+        let Some((_, decoded)) = self.scope.read_text(path) else {
+            return;
+        };
+        self.files_searched.fetch_add(1, Ordering::SeqCst);
+        let lines = split_lines(&decoded.contents);
+
+        let mut matches = Vec::new();
+        for (number, line) in lines.iter().enumerate() {
+            for found in self.scope.regex.find_iter(line) {
+                if found.start() == found.end() {
+                    continue;
+                }
+                if self.match_count.fetch_add(1, Ordering::SeqCst) >= self.max_results {
+                    self.match_count.fetch_sub(1, Ordering::SeqCst);
+                    break;
+                }
+                matches.push(self.to_match(&lines, number, found.start(), found.end()));
+            }
+            if self.should_stop() {
+                break;
+            }
+        }
+
+        if !matches.is_empty() && !self.cancelled.load(Ordering::SeqCst) {
+            self.callback.call(
+                Ok(SearchUpdate {
+                    path: relative.to_string(),
+                    matches,
+                    ..Default::default()
+                }),
+                ThreadsafeFunctionCallMode::NonBlocking,
+            );
+        }
+    }
+
+    fn to_match(&self, lines: &[&str], number: usize, start: usize, end: usize) -> SearchMatch {
+        let line = lines[number];
+        let before = number.saturating_sub(self.context_lines);
+        let after = (number + 1 + self.context_lines).min(lines.len());
+
+        SearchMatch {
+            line: number as u32,
+            column: utf16_len(&line[..start]),
+            length: utf16_len(&line[start..end]),
+            text: preview(line, start),
+            before: lines[before..number].iter().map(|l| preview(l, 0)).collect(),
+            after: lines[number + 1..after].iter().map(|l| preview(l, 0)).collect(),
+        }
+    }
+
+    fn finish(&self) {
+        let match_count = self.match_count.load(Ordering::SeqCst);
+        self.callback.call(
+            Ok(SearchUpdate {
+                done: true,
+                cancelled: self.cancelled.load(Ordering::SeqCst),
+                limit_hit: match_count >= self.max_results,
+                files_searched: self.files_searched.load(Ordering::SeqCst) as u32,
+                match_count: match_count as u32,
+                ..Default::default()
+            }),
+            ThreadsafeFunctionCallMode::Blocking,
+        );
+    }
+}
+
+/// Cut very long lines, keeping everything up to the match so `column` stays valid.
+fn preview(line: &str, match_start: usize) -> String {
+    let keep = line[match_start..]
+        .char_indices()
+        .nth(MAX_PREVIEW_CHARS)
+        .map_or(line.len(), |(i, _)| match_start + i);
+    line[..keep].to_string()
+}
+
+pub(crate) fn utf16_len(text: &str) -> u32 {
+    text.chars().map(|c| c.len_utf16() as u32).sum()
+}
@@ -0,0 +1,61 @@
+import test from 'ava'
+import { join } from 'path'
+import { writeFileSync } from 'fs'
+
+import { initRepository } from '../index.js'
+import { testRepo } from './helpers.mjs'
+
+const { dir: TEST_DIR, git } = testRepo('test-git-blame')
+
+function setupRepo() {
+  const repo = initRepository(TEST_DIR)
+  writeFileSync(join(TEST_DIR, 'a.txt'), 'one\ntwo\nthree\n')
+  git('add -A')
+  git('commit -q -m "Initial commit"', 'Alice')
+  writeFileSync(join(TEST_DIR, 'a.txt'), 'one\nTWO\nthree\n')
+  git('commit -q -am "Shout two" -m "With a body"', 'Bob')
+  return repo
+}
+
+// Test: Each line range is attributed to the commit that last changed it
+test('blameFile should attribute line ranges to commits', (t) => {
+  const repo = setupRepo()
+  const head = git('rev-parse HEAD').trim()
+
+  const ranges = repo.blameFile('a.txt')
+  t.deepEqual(ranges.map((r) => [r.startLine, r.endLine, r.authorName, r.summary]), [
+    [1, 1, 'Alice', 'Initial commit'],
+    [2, 2, 'Bob', 'Shout two'],
+    [3, 3, 'Alice', 'Initial commit'],
+  ])
+  t.is(ranges[1].commitHash, head)
+  t.true(ranges.every((r) => r.isCommitted && r.originalPath === 'a.txt'))
+
+  const older = repo.blameFile('a.txt', { revision: 'HEAD~1' })
+  t.deepEqual(older.map((r) => [r.startLine, r.endLine, r.authorName]), [[1, 3, 'Alice']])
+
+  const clipped = repo.blameFile('a.txt', { startLine: 2, endLine: 3 })
+  t.deepEqual(clipped.map((r) => [r.startLine, r.endLine]), [[2, 2], [3, 3]])
+})
+
+// Test: Uncommitted working tree lines are reported as such
+test('blameFile should mark uncommitted lines', (t) => {
+  const repo = setupRepo()
+  writeFileSync(join(TEST_DIR, 'a.txt'), 'zero\none\nTWO\nthree\n')
+
+  const ranges = repo.blameFile('a.txt')
+  t.is(ranges[0].startLine, 1)
+  t.false(ranges[0].isCommitted)
+  t.is(ranges[0].commitHash, '0'.repeat(40))
+  t.deepEqual(ranges.slice(1).map((r) => [r.startLine, r.authorName]), [[2, 'Alice'], [3, 'Bob'], [4, 'Alice']])
+})
+
+// Test: Whitespace-only changes can be skipped
+test('blameFile should ignore whitespace changes when asked', (t) => {
+  const repo = setupRepo()
+  writeFileSync(join(TEST_DIR, 'a.txt'), 'one \nTWO\nthree\n')
+  git('commit -q -am "Trailing space"', 'Carol')
+
+  t.is(repo.blameFile('a.txt')[0].authorName, 'Carol')
+  t.is(repo.blameFile('a.txt', { ignoreWhitespace: true })[0].authorName, 'Alice')
+})
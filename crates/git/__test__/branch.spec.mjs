@@ -0,0 +1,87 @@
+import test from 'ava'
+
+import { GitRepo } from '../index.js'
+import { gitIn, testRepo, useTestDir } from './helpers.mjs'
+
+const { dir: TEST_DIR, git, commitFile } = testRepo('test-git-branch')
+const ORIGIN_DIR = useTestDir('test-git-branch-origin')
+
+// A clone of a bare origin: main tracks origin/main and is one commit ahead and one behind
+function setupRepo() {
+  gitIn(ORIGIN_DIR, 'init -q --bare -b main')
+  git(`clone -q ${ORIGIN_DIR} .`)
+  git('checkout -q -b main')
+  commitFile('a.txt', 'a\n', 'Initial commit')
+  git('push -q -u origin main')
+
+  commitFile('b.txt', 'b\n', 'Upstream change')
+  git('push -q origin main')
+  git('reset -q --hard HEAD~1')
+  commitFile('c.txt', 'c\n', 'Local change')
+  return new GitRepo(TEST_DIR)
+}
+
+// Test: Local and remote branches come with upstream and ahead/behind counts
+test('listBranches should report upstream tracking', (t) => {
+  const repo = setupRepo()
+  git('branch feature')
+
+  const branches = repo.listBranches()
+  t.deepEqual(branches.map((b) => [b.name, b.isRemote, b.isHead]), [
+    ['feature', false, false],
+    ['main', false, true],
+    ['origin/main', true, false],
+  ])
+
+  const main = branches[1]
+  t.is(main.fullName, 'refs/heads/main')
+  t.is(main.upstream, 'origin/main')
+  t.deepEqual([main.ahead, main.behind], [1, 1])
+  t.is(main.lastCommitSummary, 'Local change')
+  t.is(branches[0].upstream, undefined)
+
+  t.deepEqual(repo.listBranches(false).map((b) => b.name), ['feature', 'main'])
+})
+
+// Test: Upstreams can be set, cleared and reported as gone
+test('setUpstream should change tracking', (t) => {
+  const repo = setupRepo()
+  git('branch feature')
+
+  repo.setUpstream('feature', 'origin/main')
+  t.is(repo.listBranches().find((b) => b.name === 'feature').upstream, 'origin/main')
+  repo.setUpstream('feature', null)
+  t.is(repo.listBranches().find((b) => b.name === 'feature').upstream, undefined)
+
+  git('branch -d -r origin/main')
+  const main = repo.listBranches().find((b) => b.name === 'main')
+  t.true(main.isUpstreamGone)
+  t.is(main.upstream, 'origin/main')
+})
+
+// Test: Renaming the current branch keeps HEAD on it
+test('renameBranch should rename branches and follow HEAD', (t) => {
+  const repo = setupRepo()
+
+  repo.renameBranch('main', 'trunk')
+  t.is(repo.getCurrentBranch(), 'trunk')
+  git('branch other')
+  t.throws(() => repo.renameBranch('other', 'trunk'))
+  repo.renameBranch('other', 'trunk', true)
+  t.deepEqual(repo.listBranches(false).map((b) => b.name), ['trunk'])
+})
+
+// Test: Unmerged branches are only deleted with force
+test('deleteBranch should refuse unmerged branches without force', (t) => {
+  const repo = setupRepo()
+  git('branch merged')
+  git('checkout -q -b unmerged')
+  commitFile('d.txt', 'd\n', 'Unmerged work')
+  git('checkout -q main')
+
+  t.throws(() => repo.deleteBranch('main'), { message: /checked out/ })
+  t.throws(() => repo.deleteBranch('unmerged'), { message: /not fully merged/ })
+  repo.deleteBranch('merged')
+  repo.deleteBranch('unmerged', true)
+  t.deepEqual(repo.listBranches(false).map((b) => b.name), ['main'])
+})
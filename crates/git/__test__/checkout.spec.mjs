@@ -0,0 +1,106 @@
+import test from 'ava'
+import { join } from 'path'
+import { writeFileSync } from 'fs'
+
+import { initRepository } from '../index.js'
+import { testRepo } from './helpers.mjs'
+
+const { dir: TEST_DIR, git, read } = testRepo('test-git-checkout')
+
+// main and other differ in a.txt; b.txt is the same on both
+function setupRepo() {
+  const repo = initRepository(TEST_DIR)
+  writeFileSync(join(TEST_DIR, 'a.txt'), 'one\ntwo\nthree\nfour\nfive\n')
+  writeFileSync(join(TEST_DIR, 'b.txt'), 'b\n')
+  git('add -A')
+  git('commit -q -m "Initial commit"')
+  git('branch -M main')
+  git('checkout -q -b other')
+  writeFileSync(join(TEST_DIR, 'a.txt'), 'ONE\ntwo\nthree\nfour\nfive\n')
+  git('commit -q -am "Change line one"')
+  git('checkout -q main')
+  return repo
+}
+
+// Test: Safe checkout refuses to overwrite local changes and reports them
+test('checkout should report conflicting paths with the safe strategy', async (t) => {
+  const repo = setupRepo()
+  writeFileSync(join(TEST_DIR, 'a.txt'), 'one\ntwo\nthree\nfour\nFIVE\n')
+
+  const result = await repo.checkout('other')
+  t.false(result.success)
+  t.deepEqual(result.conflicts, ['a.txt'])
+  t.is(repo.getCurrentBranch(), 'main')
+  t.is(read('a.txt'), 'one\ntwo\nthree\nfour\nFIVE\n')
+
+  t.throws(() => repo.checkoutBranch('other'), { message: /overwrite local changes: a\.txt/ })
+
+  // Changes to files the checkout does not touch are carried along
+  writeFileSync(join(TEST_DIR, 'a.txt'), 'one\ntwo\nthree\nfour\nfive\n')
+  writeFileSync(join(TEST_DIR, 'b.txt'), 'local b\n')
+  t.true((await repo.checkout('other')).success)
+  t.is(repo.getCurrentBranch(), 'other')
+  t.is(read('b.txt'), 'local b\n')
+})
+
+// Test: Force discards local changes and merge carries them over
+test('checkout should support force and merge strategies', async (t) => {
+  const repo = setupRepo()
+  writeFileSync(join(TEST_DIR, 'a.txt'), 'one\ntwo\nthree\nfour\nFIVE\n')
+
+  const merged = await repo.checkout('other', { strategy: 'merge' })
+  t.true(merged.success)
+  t.deepEqual(merged.conflicts, [])
+  t.is(read('a.txt'), 'ONE\ntwo\nthree\nfour\nFIVE\n')
+  t.is(git('stash list'), '')
+
+  t.true((await repo.checkout('main', { strategy: 'force' })).success)
+  t.is(read('a.txt'), 'one\ntwo\nthree\nfour\nfive\n')
+
+  writeFileSync(join(TEST_DIR, 'a.txt'), 'uno\ntwo\nthree\nfour\nfive\n')
+  const conflicted = await repo.checkout('other', { strategy: 'merge' })
+  t.true(conflicted.success)
+  t.deepEqual(conflicted.conflicts, ['a.txt'])
+  t.regex(read('a.txt'), /<<<<<<<[\s\S]*uno[\s\S]*>>>>>>>/)
+  t.is(git('status --porcelain'), 'UU a.txt\n')
+
+  const e = await t.throwsAsync(repo.checkout('main', { strategy: 'yolo' }))
+  t.regex(e.message, /Unknown checkout strategy/)
+})
+
+// Test: Revisions detach HEAD and progress is reported
+test('checkout should detach at revisions and report progress', async (t) => {
+  const repo = setupRepo()
+  const target = git('rev-parse other').trim()
+
+  const progress = []
+  let finish
+  const finished = new Promise((resolve) => (finish = resolve))
+  const result = await repo.checkout('other~0', null, (err, p) => {
+    progress.push(p)
+    if (p.completed === p.total) finish()
+  })
+  t.true(result.success)
+  t.is(repo.getHeadCommitHash(), target)
+  t.throws(() => repo.getCurrentBranch())
+
+  await finished
+  const last = progress[progress.length - 1]
+  t.is(last.completed, last.total)
+})
+
+// Test: Merging local changes leaves the stash and untracked files alone
+test('checkout with merge should not touch stashes or untracked files', async (t) => {
+  const repo = setupRepo()
+  writeFileSync(join(TEST_DIR, 'b.txt'), 'stashed b\n')
+  git('stash -q')
+  writeFileSync(join(TEST_DIR, 'a.txt'), 'one\ntwo\nthree\nfour\nFIVE\n')
+  writeFileSync(join(TEST_DIR, 'notes.txt'), 'untracked\n')
+
+  const result = await repo.checkout('other', { strategy: 'merge' })
+  t.true(result.success)
+  t.is(read('a.txt'), 'ONE\ntwo\nthree\nfour\nFIVE\n')
+  t.is(read('notes.txt'), 'untracked\n')
+  t.is(git('stash list').trim().split('\n').length, 1)
+  t.is(git('status --porcelain').trim(), 'M a.txt\n?? notes.txt')
+})
@@ -0,0 +1,120 @@
+import test from 'ava'
+import { join } from 'path'
+import { writeFileSync } from 'fs'
+
+import { initRepository } from '../index.js'
+import { testRepo } from './helpers.mjs'
+
+const { dir: TEST_DIR, git } = testRepo('test-git-commit')
+
+const SIGNATURE = '-----BEGIN SSH SIGNATURE-----\nc2lnbmVk\n-----END SSH SIGNATURE-----'
+
+function stage(name, content) {
+  writeFileSync(join(TEST_DIR, name), content)
+  git(`add ${name}`)
+}
+
+function setupRepo() {
+  const repo = initRepository(TEST_DIR)
+  git('config user.name "Config User"')
+  git('config user.email config@example.com')
+  return repo
+}
+
+// Test: The first commit of an unborn branch uses the identity from config
+test('commit should create the initial commit', (t) => {
+  const repo = setupRepo()
+  t.throws(() => repo.commit('Empty'), { message: /Nothing to commit/ })
+
+  stage('a.txt', 'a\n')
+  const hash = repo.commit('Initial commit')
+
+  t.is(git('rev-parse HEAD').trim(), hash)
+  t.is(git('log -1 --format="%an <%ae>|%cn <%ce>|%P"').trim(), 'Config User <config@example.com>|Config User <config@example.com>|')
+  t.regex(git('reflog -1 --format=%gs'), /^commit \(initial\): Initial commit/)
+  t.throws(() => repo.commit('Nothing new'), { message: /Nothing to commit/ })
+
+  repo.commit('Empty on purpose', null, null, { allowEmpty: true })
+  t.is(git('rev-list --count HEAD').trim(), '2')
+})
+
+test('commit should record a separate author and committer', (t) => {
+  const repo = setupRepo()
+  stage('a.txt', 'a\n')
+  repo.commit('Initial commit', 'Alice', 'alice@example.com', { committerName: 'Bob', committerEmail: 'bob@example.com' })
+
+  t.is(git('log -1 --format="%an <%ae>|%cn <%ce>"').trim(), 'Alice <alice@example.com>|Bob <bob@example.com>')
+})
+
+// Test: Amending replaces HEAD, keeping its author and, unless given, its message
+test('commit should amend HEAD', (t) => {
+  const repo = setupRepo()
+  stage('a.txt', 'a\n')
+  const first = repo.commit('Initial commit')
+  stage('b.txt', 'b\n')
+  repo.commit('Add b', 'Alice', 'alice@example.com')
+
+  stage('c.txt', 'c\n')
+  const amended = repo.commit(null, null, null, { amend: true })
+  t.is(git('rev-parse HEAD~1').trim(), first)
+  t.is(git('log -1 --format="%s|%an|%cn"').trim(), 'Add b|Alice|Config User')
+  t.is(git('show --format= --name-only HEAD').trim(), 'b.txt\nc.txt')
+
+  repo.commit('Add b and c', null, null, { amend: true })
+  t.not(git('rev-parse HEAD').trim(), amended)
+  t.is(git('log -1 --format=%s').trim(), 'Add b and c')
+  t.is(git('rev-list --count HEAD').trim(), '2')
+  t.regex(git('reflog -1 --format=%gs'), /^commit \(amend\): Add b and c/)
+})
+
+// Test: Without an identity in config, the author commits an amend, at the current time
+test('commit should date the committer of an amend now', (t) => {
+  const repo = setupRepo()
+  stage('a.txt', 'a\n')
+  git('commit -q --author="Alice <alice@example.com>" --date="2001-01-01T00:00:00Z" -m "Old"')
+  git('config --unset user.name')
+  git('config --unset user.email')
+
+  repo.commit(null, null, null, { amend: true })
+  const [author, committer, time] = git('log -1 --format="%an <%ae> %at|%cn <%ce>|%ct"').trim().split('|')
+  t.is(author, 'Alice <alice@example.com> 978307200')
+  t.is(committer, 'Alice <alice@example.com>')
+  t.true(Number(time) > Date.now() / 1000 - 60)
+})
+
+test('commitSigned should sign through the callback', async (t) => {
+  const repo = setupRepo()
+  stage('a.txt', 'a\n')
+  let signed = null
+  const hash = await repo.commitSigned('Signed commit', null, null, null, async (err, content) => {
+    signed = content
+    return SIGNATURE
+  })
+
+  t.regex(signed, /^tree [0-9a-f]{40}\nauthor Config User <config@example.com> /)
+  t.true(signed.endsWith('\nSigned commit'))
+  t.is(git('rev-parse HEAD').trim(), hash)
+  t.regex(git('cat-file commit HEAD'), /\ngpgsig -----BEGIN SSH SIGNATURE-----\n c2lnbmVk\n -----END SSH SIGNATURE-----\n/)
+  t.is(git('log -1 --format=%s').trim(), 'Signed commit')
+
+  const error = await t.throwsAsync(repo.commitSigned('Unsigned', null, null, { allowEmpty: true }, () => {
+    throw new Error('no key')
+  }))
+  t.regex(error.message, /Failed to sign commit: .*no key/)
+  t.is(git('rev-parse HEAD').trim(), hash)
+})
+
+// Test: A commit made elsewhere while signing is not overwritten
+test('commitSigned should fail when HEAD moved while signing', async (t) => {
+  const repo = setupRepo()
+  stage('a.txt', 'a\n')
+  repo.commit('First')
+  stage('b.txt', 'b\n')
+
+  const error = await t.throwsAsync(repo.commitSigned('Signed', null, null, null, (err, content) => {
+    git('commit -q -m "Meanwhile"')
+    return SIGNATURE
+  }))
+  t.regex(error.message, /Failed to update refs\/heads\/\w+/)
+  t.is(git('log -1 --format=%s').trim(), 'Meanwhile')
+})
@@ -0,0 +1,72 @@
+import test from 'ava'
+import { join } from 'path'
+import { writeFileSync, renameSync } from 'fs'
+
+import { initRepository } from '../index.js'
+import { testRepo } from './helpers.mjs'
+
+const { dir: TEST_DIR, git } = testRepo('test-git-diff')
+
+function setupRepo() {
+  const repo = initRepository(TEST_DIR)
+  writeFileSync(join(TEST_DIR, 'a.txt'), 'one\ntwo\nthree\n')
+  writeFileSync(join(TEST_DIR, 'moved.txt'), 'a file long enough\nto be detected\nas a rename\n')
+  writeFileSync(join(TEST_DIR, 'image.bin'), Buffer.from([0, 1, 2, 3]))
+  git('add -A')
+  git('commit -q -m "Initial commit"')
+  return repo
+}
+
+// Test: Unstaged, staged and combined diffs report structured hunks and lines
+test('diff APIs should return files, hunks and numbered lines', (t) => {
+  const repo = setupRepo()
+  writeFileSync(join(TEST_DIR, 'a.txt'), 'one\n2\nthree\n')
+  git('add a.txt')
+  writeFileSync(join(TEST_DIR, 'a.txt'), 'one\n2\nthree\nfour\n')
+  writeFileSync(join(TEST_DIR, 'new.txt'), 'fresh\n')
+
+  const staged = repo.diffHeadToIndex()
+  t.is(staged.length, 1)
+  t.is(staged[0].status, 'modified')
+  t.is(staged[0].hunks[0].header, '@@ -1,3 +1,3 @@')
+  t.deepEqual(
+    staged[0].hunks[0].lines.map((l) => [l.origin, l.oldLineNumber ?? null, l.newLineNumber ?? null, l.content]),
+    [
+      [' ', 1, 1, 'one'],
+      ['-', 2, null, 'two'],
+      ['+', null, 2, '2'],
+      [' ', 3, 3, 'three'],
+    ],
+  )
+
+  const unstaged = repo.diffIndexToWorkdir()
+  t.deepEqual(unstaged.map((f) => [f.newPath, f.status, f.additions, f.deletions]), [
+    ['a.txt', 'modified', 1, 0],
+    ['new.txt', 'untracked', 1, 0],
+  ])
+
+  const combined = repo.diffHeadToWorkdir({ paths: ['a.txt'], contextLines: 0 })
+  t.is(combined.length, 1)
+  t.deepEqual(combined[0].hunks.map((h) => h.header), ['@@ -2 +2 @@ one', '@@ -3,0 +4 @@ three'])
+})
+
+// Test: Commit diffs flag renames and binary files
+test('diffCommits should detect renames and binary changes', (t) => {
+  const repo = setupRepo()
+  renameSync(join(TEST_DIR, 'moved.txt'), join(TEST_DIR, 'renamed.txt'))
+  writeFileSync(join(TEST_DIR, 'image.bin'), Buffer.from([0, 9, 9, 9]))
+  git('add -A')
+  git('commit -q -m "Move and edit"')
+
+  const files = repo.diffCommits('HEAD~1', 'HEAD')
+  const renamed = files.find((f) => f.isRenamed)
+  t.is(renamed.oldPath, 'moved.txt')
+  t.is(renamed.newPath, 'renamed.txt')
+  t.is(renamed.hunks.length, 0)
+
+  const binary = files.find((f) => f.newPath === 'image.bin')
+  t.true(binary.isBinary)
+  t.deepEqual(binary.hunks, [])
+
+  t.is(repo.diffCommits('HEAD~1', 'HEAD', { detectRenames: false }).length, 3)
+})
@@ -0,0 +1,53 @@
+import test from 'ava'
+import { join } from 'path'
+import { writeFileSync, readFileSync } from 'fs'
+
+import { initRepository } from '../index.js'
+import { testRepo } from './helpers.mjs'
+
+const { dir: TEST_DIR, git } = testRepo('test-git-gutter')
+
+// Test: Unsaved buffer text is compared to HEAD without touching the file on disk
+test('getLineChanges should report added, modified and deleted ranges', (t) => {
+  const repo = initRepository(TEST_DIR)
+  writeFileSync(join(TEST_DIR, 'a.txt'), 'one\ntwo\nthree\nfour\nfive\n')
+  git('add -A')
+  git('commit -q -m "Initial commit"')
+
+  const buffer = 'zero\none\nTWO\nthree\nfive\n'
+  const changes = repo.getLineChanges('a.txt', buffer)
+  t.deepEqual(changes.map((c) => [c.kind, c.startLine, c.endLine]), [
+    ['added', 0, 1],
+    ['modified', 2, 3],
+    ['deleted', 4, 4],
+  ])
+  t.is(readFileSync(join(TEST_DIR, 'a.txt'), 'utf8'), 'one\ntwo\nthree\nfour\nfive\n')
+
+  t.deepEqual(repo.getLineChanges('a.txt', 'one\ntwo\nthree\nfour\nfive\n'), [])
+  t.deepEqual(repo.getLineChanges('new.txt', 'a\nb'), [{ kind: 'added', startLine: 0, endLine: 2 }])
+})
+
+// Test: The index can be used as the base instead of HEAD
+test('getLineChanges should compare against the index when asked', (t) => {
+  const repo = initRepository(TEST_DIR)
+  writeFileSync(join(TEST_DIR, 'a.txt'), 'one\ntwo\n')
+  git('add -A')
+  git('commit -q -m "Initial commit"')
+  writeFileSync(join(TEST_DIR, 'a.txt'), 'one\ntwo\nthree\n')
+  git('add a.txt')
+
+  t.deepEqual(repo.getLineChanges('a.txt', 'one\ntwo\nthree\n', true), [])
+  t.deepEqual(repo.getLineChanges('a.txt', 'one\ntwo\nthree\n').map((c) => c.kind), ['added'])
+})
+
+// Test: Buffer text goes through the clean filters before it is compared
+test('getLineChanges should apply autocrlf to the buffer', (t) => {
+  const repo = initRepository(TEST_DIR)
+  git('config core.autocrlf true')
+  writeFileSync(join(TEST_DIR, 'a.txt'), 'one\ntwo\nthree\n')
+  git('add -A')
+  git('commit -q -m "Initial commit"')
+
+  t.deepEqual(repo.getLineChanges('a.txt', 'one\r\ntwo\r\nthree\r\n'), [])
+  t.deepEqual(repo.getLineChanges('a.txt', 'one\r\nTWO\r\nthree\r\n').map((c) => [c.kind, c.startLine]), [['modified', 1]])
+})
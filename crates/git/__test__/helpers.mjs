@@ -0,0 +1,56 @@
+import test from 'ava'
+import { join, dirname } from 'path'
+import { fileURLToPath } from 'url'
+import { mkdirSync, writeFileSync, readFileSync, rmSync } from 'fs'
+import { execSync } from 'child_process'
+
+const __dirname = dirname(fileURLToPath(import.meta.url))
+
+// Runs git in `cwd` with a fixed identity and returns its output
+export function gitIn(cwd, command, author = 'Test User', env = process.env) {
+  return execSync(`git -c user.name="${author}" -c user.email=test@example.com ${command}`, { cwd, env, stdio: 'pipe' }).toString()
+}
+
+// Commits `content` as `name` and returns the new HEAD
+export function commitIn(cwd, name, content, message, author = 'Test User <test@example.com>', env = process.env) {
+  writeFileSync(join(cwd, name), content)
+  gitIn(cwd, `add ${name}`, undefined, env)
+  gitIn(cwd, `commit -q --author="${author}" -m "${message}"`, undefined, env)
+  return gitIn(cwd, 'rev-parse HEAD', undefined, env).trim()
+}
+
+// A scratch directory next to the specs, emptied before and removed after each test
+export function useTestDir(name) {
+  const dir = join(__dirname, name)
+
+  test.beforeEach(() => {
+    rmSync(dir, { recursive: true, force: true })
+    mkdirSync(dir, { recursive: true })
+  })
+
+  test.afterEach.always(() => {
+    rmSync(dir, { recursive: true, force: true })
+  })
+
+  return dir
+}
+
+// useTestDir with the helpers bound to it. With a `clock`, in seconds since
+// the epoch, each git command runs a minute after the previous one, so
+// commits get distinct, predictable dates
+export function testRepo(name, { clock } = {}) {
+  const dir = useTestDir(name)
+  const env = () => {
+    if (clock === undefined) {
+      return process.env
+    }
+    clock += 60
+    return { ...process.env, GIT_AUTHOR_DATE: `${clock} +0000`, GIT_COMMITTER_DATE: `${clock} +0000` }
+  }
+  return {
+    dir,
+    git: (command, author) => gitIn(dir, command, author, env()),
+    read: (file) => readFileSync(join(dir, file), 'utf8'),
+    commitFile: (file, content, message, author) => commitIn(dir, file, content, message, author, env()),
+  }
+}
@@ -0,0 +1,120 @@
+import test from 'ava'
+import { join } from 'path'
+import { writeFileSync } from 'fs'
+import { execSync } from 'child_process'
+
+import { initRepository } from '../index.js'
+import { testRepo } from './helpers.mjs'
+
+const { dir: TEST_DIR, git } = testRepo('test-git-history', { clock: 1700000000 })
+
+// Creates: add old.txt, touch other.txt, edit old.txt, rename to new.txt, edit new.txt
+function setupRepo() {
+  const repo = initRepository(TEST_DIR)
+  const body = 'a file long enough\nto be detected\nas a rename\nlater on\n'
+  writeFileSync(join(TEST_DIR, 'old.txt'), body)
+  git('add -A')
+  git('commit -q -m "Add old"')
+  writeFileSync(join(TEST_DIR, 'other.txt'), 'unrelated\n')
+  git('add -A')
+  git('commit -q -m "Add other"')
+  writeFileSync(join(TEST_DIR, 'old.txt'), body + 'more\n')
+  git('commit -q -am "Edit old"')
+  git('mv old.txt new.txt')
+  git('commit -q -m "Rename"')
+  writeFileSync(join(TEST_DIR, 'new.txt'), body + 'more\nand more\n')
+  git('commit -q -am "Edit new"')
+  return repo
+}
+
+// Test: Only commits touching the file are returned, optionally across renames
+test('getFileHistory should list commits that changed the file', (t) => {
+  const repo = setupRepo()
+
+  const history = repo.getFileHistory('new.txt')
+  t.deepEqual(history.map((c) => c.commitMessage.trim()), ['Edit new', 'Rename'])
+  t.deepEqual([history[0].addedLines, history[0].deletedLines], [1, 0])
+
+  const followed = repo.getFileHistory('new.txt', { followRenames: true })
+  t.deepEqual(followed.map((c) => [c.commitMessage.trim(), c.filePath]), [
+    ['Edit new', 'new.txt'],
+    ['Rename', 'new.txt'],
+    ['Edit old', 'old.txt'],
+    ['Add old', 'old.txt'],
+  ])
+  t.deepEqual([followed[1].addedLines, followed[1].deletedLines], [0, 0])
+
+  const page = repo.getFileHistory('new.txt', { followRenames: true, skip: 1, limit: 2 })
+  t.deepEqual(page.map((c) => c.commitMessage.trim()), ['Rename', 'Edit old'])
+
+  t.is(repo.getFileMetadata('other.txt').lastCommitMessage.trim(), 'Add other')
+  t.throws(() => repo.getFileMetadata('missing.txt'))
+})
+
+// Test: The side a merge discarded is left out, like git log
+test('getFileHistory should skip the side a merge kept out', (t) => {
+  const repo = initRepository(TEST_DIR)
+  writeFileSync(join(TEST_DIR, 'x.txt'), 'base\n')
+  git('add -A')
+  git('commit -q -m "Base"')
+  git('branch side')
+  writeFileSync(join(TEST_DIR, 'x.txt'), 'main\n')
+  git('commit -q -am "Edit x on main"')
+  git('checkout -q side')
+  writeFileSync(join(TEST_DIR, 'x.txt'), 'side\n')
+  git('commit -q -am "Edit x on side"')
+  git('checkout -q -')
+  git('merge -q -s ours side -m "Merge side"')
+
+  t.deepEqual(repo.getFileHistory('x.txt').map((c) => c.commitMessage.trim()), ['Edit x on main', 'Base'])
+  t.is(repo.getFileMetadata('x.txt').lastCommitMessage.trim(), 'Edit x on main')
+})
+
+// Test: The async variant streams pages and finishes with a done update
+test('getFileHistoryAsync should stream commits until done', async (t) => {
+  const repo = setupRepo()
+
+  const updates = await new Promise((resolve, reject) => {
+    const received = []
+    const handle = repo.getFileHistoryAsync('new.txt', { followRenames: true }, (err, update) => {
+      if (err) return reject(err)
+      received.push(update)
+      if (update.done) {
+        t.true(handle.isDone())
+        resolve(received)
+      }
+    })
+  })
+
+  const last = updates[updates.length - 1]
+  t.false(last.cancelled)
+  t.deepEqual(last.commits, [])
+  t.deepEqual(updates.flatMap((u) => u.commits.map((c) => c.commitMessage.trim())), ['Edit new', 'Rename', 'Edit old', 'Add old'])
+})
+
+// Test: Cancelling from the first page stops the walk and reports it
+test('getFileHistoryAsync should report cancellation', async (t) => {
+  const repo = initRepository(TEST_DIR)
+  let stream = ''
+  for (let i = 1; i <= 500; i++) {
+    const content = `${i}\n`
+    stream += `commit refs/heads/main\ncommitter Test User <test@example.com> ${1700000000 + i} +0000\n`
+    stream += `data 2\n${i % 10}\nM 100644 inline a.txt\ndata ${content.length}\n${content}\n`
+  }
+  execSync('git fast-import --quiet', { cwd: TEST_DIR, input: stream })
+  git('symbolic-ref HEAD refs/heads/main')
+
+  const received = []
+  const last = await new Promise((resolve, reject) => {
+    const handle = repo.getFileHistoryAsync('a.txt', null, (err, update) => {
+      if (err) return reject(err)
+      received.push(...update.commits)
+      if (update.done) resolve(update)
+      else handle.cancel()
+    })
+  })
+
+  t.true(last.done)
+  t.true(last.cancelled)
+  t.true(received.length < 500)
+})
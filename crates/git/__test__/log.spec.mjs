@@ -0,0 +1,70 @@
+import test from 'ava'
+
+import { initRepository } from '../index.js'
+import { testRepo } from './helpers.mjs'
+
+const { dir: TEST_DIR, git, commitFile } = testRepo('test-git-log', { clock: 1700000000 })
+
+//   * E
+//   *   M
+//   |\
+//   | * D
+//   * | C
+//   |/
+//   * B
+//   * A
+function setupRepo() {
+  const repo = initRepository(TEST_DIR)
+  commitFile('a.txt', 'a\n', 'A', 'Alice <alice@example.com>')
+  git('branch -M main')
+  commitFile('a.txt', 'b\n', 'B', 'Alice <alice@example.com>')
+  git('checkout -q -b feature')
+  git('checkout -q main')
+  commitFile('c.txt', 'c\n', 'C', 'Bob <bob@example.com>')
+  git('checkout -q feature')
+  commitFile('d.txt', 'd\n', 'D fix typo', 'Carol <carol@example.com>')
+  git('checkout -q main')
+  git('merge -q --no-ff feature -m M')
+  commitFile('a.txt', 'e\n', 'E', 'Alice <alice@example.com>')
+  return repo
+}
+
+// Test: Commits come newest first with parents, filters and pagination
+test('log should list commits with filters and pagination', (t) => {
+  const repo = setupRepo()
+
+  const all = repo.log()
+  t.deepEqual(all.map((c) => c.summary), ['E', 'M', 'D fix typo', 'C', 'B', 'A'])
+  t.is(all[1].parents.length, 2)
+  t.is(all[1].parents[1], all[2].hash)
+  t.deepEqual(all[5].parents, [])
+
+  t.deepEqual(repo.log({ skip: 1, limit: 2 }).map((c) => c.summary), ['M', 'D fix typo'])
+  t.deepEqual(repo.log({ author: 'ALICE' }).map((c) => c.summary), ['E', 'B', 'A'])
+  t.deepEqual(repo.log({ message: 'typo' }).map((c) => c.summary), ['D fix typo'])
+  t.deepEqual(repo.log({ paths: ['a.txt'] }).map((c) => c.summary), ['E', 'B', 'A'])
+  t.deepEqual(repo.log({ revisions: ['main~1..main'] }).map((c) => c.summary), ['E'])
+  t.deepEqual(repo.log({ revisions: ['feature', '^main~2'] }).map((c) => c.summary), ['D fix typo'])
+  t.deepEqual(repo.log({ firstParent: true }).map((c) => c.summary), ['E', 'M', 'C', 'B', 'A'])
+})
+
+// Test: Graph rows place merges and branches in lanes like git log --graph
+test('log should compute graph lanes and edges', (t) => {
+  const repo = setupRepo()
+
+  const rows = repo.log({ graph: true }).map((c) => [c.summary, c.graph.lane, c.graph.edges.map((e) => [e.fromLane, e.toLane])])
+  t.deepEqual(rows, [
+    ['E', 0, [[0, 0]]],
+    ['M', 0, [[0, 0], [0, 1]]],
+    ['D fix typo', 1, [[0, 0], [1, 1]]],
+    ['C', 0, [[0, 0], [1, 0]]],
+    ['B', 0, [[0, 0]]],
+    ['A', 0, []],
+  ])
+
+  // A page ends with the lines bending toward the next page's first commit
+  const page = repo.log({ graph: true, skip: 2, limit: 2 })
+  t.deepEqual(page.map((c) => c.graph.edges.map((e) => [e.fromLane, e.toLane])), [[[0, 0], [1, 1]], [[0, 0], [1, 0]]])
+
+  t.throws(() => repo.log({ graph: true, author: 'Alice' }), { message: /graph/ })
+})
@@ -0,0 +1,96 @@
+import test from 'ava'
+import { join } from 'path'
+import { writeFileSync, readFileSync } from 'fs'
+
+import { initRepository } from '../index.js'
+import { testRepo } from './helpers.mjs'
+
+const { dir: TEST_DIR, git, commitFile } = testRepo('test-git-merge')
+
+function parents(revision) {
+  return git(`rev-list --parents -n 1 ${revision}`).trim().split(' ').slice(1)
+}
+
+function setupRepo() {
+  const repo = initRepository(TEST_DIR)
+  git('config user.name "Test User"')
+  git('config user.email test@example.com')
+  commitFile('a.txt', 'one\ntwo\nthree\n', 'Initial commit')
+  git('branch -M main')
+  return repo
+}
+
+// Test: Fast-forward merges move the branch unless noFf is set
+test('merge should fast-forward or create a merge commit', (t) => {
+  const repo = setupRepo()
+  const base = repo.getHeadCommitHash()
+  git('checkout -q -b feature')
+  const feature = commitFile('b.txt', 'b\n', 'Add b')
+  git('checkout -q main')
+
+  t.deepEqual(repo.merge('feature'), { status: 'fastForward', head: feature, conflicts: [] })
+  t.is(repo.getCurrentBranch(), 'main')
+  t.is(readFileSync(join(TEST_DIR, 'b.txt'), 'utf8'), 'b\n')
+  t.is(repo.merge('feature').status, 'upToDate')
+
+  git(`reset -q --hard ${base}`)
+  const merged = repo.merge('feature', { noFf: true })
+  t.is(merged.status, 'merged')
+  t.deepEqual(parents('HEAD'), [base, feature])
+  t.is(git('log -1 --format=%s').trim(), "Merge branch 'feature'")
+
+  git(`reset -q --hard ${base}`)
+  commitFile('c.txt', 'c\n', 'Add c')
+  t.throws(() => repo.merge('feature', { ffOnly: true }), { message: /Cannot fast-forward/ })
+  t.is(repo.merge(feature.slice(0, 7), { message: 'Bring in b' }).status, 'merged')
+  t.is(git('log -1 --format=%s').trim(), 'Bring in b')
+  t.is(git('status --porcelain'), '')
+})
+
+// Test: Conflicts come back with three-way contents and marker regions
+test('merge should report conflicts until they are resolved', (t) => {
+  const repo = setupRepo()
+  git('checkout -q -b feature')
+  const theirs = commitFile('a.txt', 'one\nTWO\nthree\n', 'Shout two')
+  git('checkout -q main')
+  const ours = commitFile('a.txt', 'one\ndos\nthree\n', 'Translate two')
+
+  const result = repo.merge('feature')
+  t.is(result.status, 'conflicts')
+  t.is(result.head, ours)
+  t.deepEqual(result.conflicts, ['a.txt'])
+
+  const conflict = repo.getConflict('a.txt')
+  t.is(conflict.ancestor, 'one\ntwo\nthree\n')
+  t.is(conflict.ours, 'one\ndos\nthree\n')
+  t.is(conflict.theirs, 'one\nTWO\nthree\n')
+  t.false(conflict.isBinary)
+  t.deepEqual(conflict.regions, [{ startLine: 1, baseLine: 3, separatorLine: 5, endLine: 7 }])
+  t.throws(() => repo.getConflict('missing.txt'), { message: /has no conflict/ })
+  t.throws(() => repo.commit('Too early', 'Test User', 'test@example.com'))
+
+  writeFileSync(join(TEST_DIR, 'a.txt'), 'one\nDOS\nthree\n')
+  repo.markResolved('a.txt')
+  t.is(git('diff --name-only --diff-filter=U'), '')
+
+  repo.commit('Merge feature', 'Test User', 'test@example.com')
+  t.deepEqual(parents('HEAD'), [ours, theirs])
+  t.is(git('status --porcelain'), '')
+})
+
+// Test: Regions include the base marker with the diff3 conflict style
+test('getConflict should find diff3 base markers', (t) => {
+  const repo = setupRepo()
+  git('checkout -q -b feature')
+  commitFile('a.txt', 'one\nTWO\nthree\n', 'Shout two')
+  git('checkout -q main')
+  commitFile('a.txt', 'one\ndos\nthree\n', 'Translate two')
+  git('-c merge.conflictStyle=diff3 merge feature || true')
+
+  const conflict = repo.getConflict('a.txt')
+  t.deepEqual(conflict.regions, [{ startLine: 1, baseLine: 3, separatorLine: 5, endLine: 7 }])
+
+  git('rm -q a.txt')
+  repo.markResolved('a.txt')
+  t.is(git('diff --cached --name-status').trim(), 'D\ta.txt')
+})
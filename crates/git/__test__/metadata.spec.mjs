@@ -0,0 +1,83 @@
+import test from 'ava'
+import { join } from 'path'
+import { mkdirSync, writeFileSync } from 'fs'
+
+import { initRepository } from '../index.js'
+import { testRepo } from './helpers.mjs'
+
+const { dir: TEST_DIR, git } = testRepo('test-git-metadata', { clock: 1700000000 })
+
+function setupRepo() {
+  const repo = initRepository(TEST_DIR)
+  mkdirSync(join(TEST_DIR, 'src'))
+  writeFileSync(join(TEST_DIR, 'README.md'), '# readme\n')
+  writeFileSync(join(TEST_DIR, 'src', 'a.js'), 'a\n')
+  writeFileSync(join(TEST_DIR, 'src', 'b.js'), 'b\n')
+  git('add -A')
+  git('commit -q -m "Initial commit"')
+  writeFileSync(join(TEST_DIR, 'src', 'a.js'), 'a\nmore a\n')
+  git('commit -q -am "Edit a"')
+
+  // A side branch edits b.js and is merged back without fast-forwarding
+  git('checkout -q -b side')
+  writeFileSync(join(TEST_DIR, 'src', 'b.js'), 'b\nmore b\n')
+  git('commit -q -am "Edit b on side"')
+  git('checkout -q -')
+  writeFileSync(join(TEST_DIR, 'README.md'), '# readme\nmore\n')
+  git('commit -q -am "Edit readme"')
+  git('merge -q --no-ff side -m "Merge side"')
+  return repo
+}
+
+// Test: Each file gets the last commit that changed it, from one walk
+test('listFilesWithMetadata should assign the last commit per file', (t) => {
+  const repo = setupRepo()
+  writeFileSync(join(TEST_DIR, 'src', 'new.js'), 'new\n')
+
+  const files = repo.listFilesWithMetadata(null)
+  t.deepEqual(files.map((f) => [f.path, f.lastCommitMessage.trim()]), [
+    ['README.md', 'Edit readme'],
+    ['src/a.js', 'Edit a'],
+    ['src/b.js', 'Edit b on side'],
+    ['src/new.js', 'Untracked file'],
+  ])
+  t.deepEqual([files[1].addedLines, files[1].deletedLines], [1, 0])
+
+  for (const file of files.slice(0, 3)) {
+    t.is(repo.getFileMetadata(file.path).lastCommitHash, file.lastCommitHash)
+  }
+
+  t.deepEqual(repo.listFilesWithMetadata('src').map((f) => f.path), ['src/a.js', 'src/b.js', 'src/new.js'])
+})
+
+// Test: Cached results are dropped once HEAD moves
+test('listFilesWithMetadata should refresh after a new commit', (t) => {
+  const repo = setupRepo()
+  t.is(repo.listFilesWithMetadata('src')[0].lastCommitMessage.trim(), 'Edit a')
+
+  writeFileSync(join(TEST_DIR, 'src', 'a.js'), 'a\nchanged\n')
+  git('commit -q -am "Edit a again"')
+  t.is(repo.listFilesWithMetadata('src')[0].lastCommitMessage.trim(), 'Edit a again')
+})
+
+// Test: A merge that kept one side's file blames that side, like git log
+test('listFilesWithMetadata should follow the side a merge kept', (t) => {
+  const repo = initRepository(TEST_DIR)
+  writeFileSync(join(TEST_DIR, 'x.txt'), 'base\n')
+  git('add -A')
+  git('commit -q -m "Base"')
+  git('branch side')
+  writeFileSync(join(TEST_DIR, 'x.txt'), 'main\n')
+  git('commit -q -am "Edit x on main"')
+
+  // The side commit is newer, so a plain walk would reach it first
+  git('checkout -q side')
+  writeFileSync(join(TEST_DIR, 'x.txt'), 'side\n')
+  git('commit -q -am "Edit x on side"')
+  git('checkout -q -')
+  git('merge -q -s ours side -m "Merge side"')
+
+  const [file] = repo.listFilesWithMetadata(null)
+  t.is(file.lastCommitMessage.trim(), 'Edit x on main')
+  t.is(file.lastCommitHash, git('rev-parse HEAD^1').trim())
+})
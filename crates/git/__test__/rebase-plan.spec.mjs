@@ -0,0 +1,218 @@
+import test from 'ava'
+import { join } from 'path'
+import { writeFileSync, appendFileSync, existsSync } from 'fs'
+
+import { initRepository } from '../index.js'
+import { testRepo } from './helpers.mjs'
+
+const { dir: TEST_DIR, git, read, commitFile } = testRepo('test-git-rebase-plan')
+
+function subjects() {
+  return git('log --format=%s').trim().split('\n')
+}
+
+// feature has "Add b", "Add c" and "Fix b" on top of main
+function setupRepo() {
+  const repo = initRepository(TEST_DIR)
+  git('config user.name "Test User"')
+  git('config user.email test@example.com')
+  commitFile('a.txt', 'a\n', 'Initial commit')
+  git('branch -M main')
+  git('checkout -q -b feature')
+  commitFile('b.txt', 'b\n', 'Add b', 'Alice <alice@example.com>')
+  commitFile('c.txt', 'c\n', 'Add c')
+  commitFile('b.txt', 'b fixed\n', 'Fix b')
+  return repo
+}
+
+// Test: The plan lists the branch's commits oldest first as picks
+test('getRebasePlan should list commits to rebase', (t) => {
+  const repo = setupRepo()
+  const plan = repo.getRebasePlan('main')
+
+  t.deepEqual(plan.map((step) => step.action), ['pick', 'pick', 'pick'])
+  t.deepEqual(plan.map((step) => step.message.trim()), ['Add b', 'Add c', 'Fix b'])
+  t.is(plan[2].commit, git('rev-parse HEAD').trim())
+})
+
+// Test: Steps are reordered, reworded, squashed, fixed up and dropped
+test('rebaseInteractive should carry out the plan', (t) => {
+  const repo = setupRepo()
+  const [addB, addC, fixB] = repo.getRebasePlan('main')
+
+  const result = repo.rebaseInteractive('main', [
+    { action: 'reword', commit: addB.commit, message: 'Add file b\n' },
+    { action: 'fixup', commit: fixB.commit },
+    { action: 'drop', commit: addC.commit },
+  ])
+
+  t.is(result.status, 'completed')
+  t.is(result.head, git('rev-parse feature').trim())
+  t.deepEqual(subjects(), ['Add file b', 'Initial commit'])
+  t.is(git('log -1 --format=%an').trim(), 'Alice')
+  t.is(read('b.txt'), 'b fixed\n')
+  t.false(existsSync(join(TEST_DIR, 'c.txt')))
+  t.is(git('symbolic-ref HEAD').trim(), 'refs/heads/feature')
+  t.is(repo.repositoryState().state, 'clean')
+})
+
+// Test: Squashing combines the commit messages
+test('rebaseInteractive should squash messages together', (t) => {
+  const repo = setupRepo()
+  const [addB, addC, fixB] = repo.getRebasePlan('main')
+
+  repo.rebaseInteractive('main', [
+    { action: 'pick', commit: addB.commit },
+    { action: 'squash', commit: fixB.commit },
+    { action: 'pick', commit: addC.commit },
+  ])
+
+  t.deepEqual(subjects(), ['Add c', 'Add b', 'Initial commit'])
+  t.is(git('log -1 --skip=1 --format=%B').trim(), 'Add b\n\nFix b')
+})
+
+// Test: Messages passed back unchanged from the plan keep the usual fixup and squash messages
+test('rebaseInteractive should round-trip the plan messages', (t) => {
+  const repo = setupRepo()
+  const [addB, addC, fixB] = repo.getRebasePlan('main')
+
+  repo.rebaseInteractive('main', [
+    addB,
+    { ...fixB, action: 'fixup' },
+    { ...addC, action: 'reword' },
+  ])
+
+  t.deepEqual(subjects(), ['Add c', 'Add b', 'Initial commit'])
+  t.is(git('log -1 --skip=1 --format=%B').trim(), 'Add b')
+  t.is(read('b.txt'), 'b fixed\n')
+})
+
+// Test: An edit step stops so the commit can be amended
+test('rebaseInteractive should stop at edit steps', (t) => {
+  const repo = setupRepo()
+  const [addB, addC, fixB] = repo.getRebasePlan('main')
+
+  const stopped = repo.rebaseInteractive('main', [
+    { action: 'edit', commit: addB.commit },
+    { action: 'pick', commit: addC.commit },
+    { action: 'pick', commit: fixB.commit },
+  ])
+  t.is(stopped.status, 'edit')
+  t.like(repo.repositoryState(), {
+    state: 'rebase',
+    rebaseStep: 1,
+    rebaseTotal: 3,
+    rebaseBranch: 'refs/heads/feature',
+    rebaseAction: 'edit',
+    rebaseCommit: addB.commit,
+  })
+
+  writeFileSync(join(TEST_DIR, 'b2.txt'), 'b2\n')
+  git('add b2.txt')
+  const result = repo.rebaseContinue()
+
+  t.is(result.status, 'completed')
+  t.deepEqual(subjects(), ['Fix b', 'Add c', 'Add b', 'Initial commit'])
+  t.is(git('show --format= --name-only HEAD~2').trim().split('\n').sort().join(','), 'b.txt,b2.txt')
+})
+
+// Test: A conflicting step stops until it is resolved, skipped or aborted
+test('rebaseInteractive should stop on conflicts', (t) => {
+  const repo = setupRepo()
+  const [addB, , fixB] = repo.getRebasePlan('main')
+
+  const stopped = repo.rebaseInteractive('main', [
+    { action: 'pick', commit: fixB.commit },
+    { action: 'pick', commit: addB.commit },
+  ])
+  // "Fix b" changes a file main does not have yet
+  t.is(stopped.status, 'conflicts')
+  t.deepEqual(stopped.conflicts, ['b.txt'])
+  t.like(repo.repositoryState(), { conflicts: ['b.txt'], rebaseStep: 1, rebaseCommit: fixB.commit })
+  t.throws(() => repo.rebaseContinue(), { message: /Resolve conflicts/ })
+
+  writeFileSync(join(TEST_DIR, 'b.txt'), 'b fixed\n')
+  git('add b.txt')
+  // "Add b" then adds the file again
+  const again = repo.rebaseContinue()
+  t.is(again.status, 'conflicts')
+  t.like(repo.repositoryState(), { rebaseStep: 2, rebaseCommit: addB.commit })
+  t.regex(read('b.txt'), /<<<<<<<.*\nb fixed\n\|{7}[^]*?\n=======\nb\n>>>>>>>/)
+
+  writeFileSync(join(TEST_DIR, 'b.txt'), 'b resolved\n')
+  git('add b.txt')
+  const result = repo.rebaseContinue()
+
+  t.is(result.status, 'completed')
+  t.deepEqual(subjects(), ['Add b', 'Fix b', 'Initial commit'])
+  t.is(read('b.txt'), 'b resolved\n')
+})
+
+test('rebaseSkip should drop the stopped step', (t) => {
+  const repo = setupRepo()
+  const [addB, , fixB] = repo.getRebasePlan('main')
+
+  repo.rebaseInteractive('main', [
+    { action: 'pick', commit: fixB.commit },
+    { action: 'pick', commit: addB.commit },
+  ])
+  const result = repo.rebaseSkip()
+
+  t.is(result.status, 'completed')
+  t.deepEqual(subjects(), ['Add b', 'Initial commit'])
+  t.is(read('b.txt'), 'b\n')
+})
+
+test('rebaseAbort should restore the branch after an interactive rebase', (t) => {
+  const repo = setupRepo()
+  const before = git('rev-parse HEAD').trim()
+  const [addB, , fixB] = repo.getRebasePlan('main')
+
+  repo.rebaseInteractive('main', [
+    { action: 'pick', commit: fixB.commit },
+    { action: 'pick', commit: addB.commit },
+  ])
+  repo.rebaseAbort()
+
+  t.is(git('rev-parse HEAD').trim(), before)
+  t.is(git('symbolic-ref HEAD').trim(), 'refs/heads/feature')
+  t.is(read('b.txt'), 'b fixed\n')
+  t.is(repo.repositoryState().state, 'clean')
+})
+
+test('rebaseInteractive should reject invalid plans', (t) => {
+  const repo = setupRepo()
+  const [addB] = repo.getRebasePlan('main')
+
+  t.throws(() => repo.rebaseInteractive('main', [{ action: 'squash', commit: addB.commit }]), {
+    message: /without a previous commit/,
+  })
+  t.throws(() => repo.rebaseInteractive('main', [{ action: 'reword', commit: addB.commit }]), {
+    message: /needs a message/,
+  })
+  t.throws(() => repo.rebaseInteractive('main', [{ action: 'move', commit: addB.commit }]), {
+    message: /Unknown rebase action/,
+  })
+})
+
+// Test: Rebases this library can't carry out are refused instead of being half understood
+test('rebaseContinue should refuse foreign and unparsable todo lists', (t) => {
+  const repo = setupRepo()
+  const [addB, , fixB] = repo.getRebasePlan('main')
+
+  repo.rebaseInteractive('main', [
+    { action: 'pick', commit: fixB.commit },
+    { action: 'pick', commit: addB.commit },
+  ])
+  appendFileSync(join(TEST_DIR, '.git', 'rebase-merge', 'git-rebase-todo'), 'exec make test\n')
+  writeFileSync(join(TEST_DIR, 'b.txt'), 'b fixed\n')
+  git('add b.txt')
+  t.throws(() => repo.rebaseContinue(), { message: /Cannot parse rebase todo line: exec make test/ })
+  repo.rebaseAbort()
+
+  git('-c sequence.editor="sed -i 1s/^pick/edit/" rebase -q -i main')
+  t.is(repo.repositoryState().state, 'rebase')
+  t.throws(() => repo.rebaseContinue(), { message: /started by `git rebase -i`/ })
+  t.throws(() => repo.rebaseAbort(), { message: /started by `git rebase -i`/ })
+  git('rebase --abort')
+})
@@ -0,0 +1,160 @@
+import test from 'ava'
+import { join } from 'path'
+import { readFileSync } from 'fs'
+import { createServer } from 'http'
+
+import { GitRepo } from '../index.js'
+import { gitIn, commitIn, useTestDir } from './helpers.mjs'
+
+const TEST_DIR = useTestDir('test-git-remote')
+const ORIGIN = join(TEST_DIR, 'origin.git')
+const ALICE = join(TEST_DIR, 'alice')
+const BOB = join(TEST_DIR, 'bob')
+
+test.beforeEach(() => {
+  gitIn(TEST_DIR, 'init -q --bare -b main origin.git')
+
+  gitIn(TEST_DIR, `clone -q file://${ORIGIN} alice`)
+  gitIn(ALICE, 'checkout -q -b main')
+  commitIn(ALICE, 'a.txt', 'one\ntwo\nthree\n', 'Initial commit')
+  gitIn(ALICE, 'push -q -u origin main')
+
+  gitIn(TEST_DIR, `clone -q file://${ORIGIN} bob`)
+  for (const dir of [ALICE, BOB]) {
+    gitIn(dir, 'config user.name "Test User"')
+    gitIn(dir, 'config user.email test@example.com')
+  }
+})
+
+// Test: Pushing reports progress and rejected non-fast-forward updates
+test('push should report progress and rejections', async (t) => {
+  const alice = new GitRepo(ALICE)
+  const bob = new GitRepo(BOB)
+
+  const head = commitIn(ALICE, 'b.txt', 'b\n', 'Add b')
+  const progress = []
+  const pushed = await alice.push('origin', 'main', null, null, (err, p) => progress.push(p))
+  t.deepEqual(pushed, { success: true, rejected: [] })
+  t.is(gitIn(ORIGIN, 'rev-parse main').trim(), head)
+  await new Promise((resolve) => setImmediate(resolve))
+  t.true(progress.length > 0)
+
+  commitIn(BOB, 'c.txt', 'c\n', 'Add c')
+  const rejected = await bob.push('origin', 'main')
+  t.false(rejected.success)
+  t.is(rejected.rejected.length, 1)
+  t.is(rejected.rejected[0].reference, 'refs/heads/main')
+  t.is(gitIn(ORIGIN, 'rev-parse main').trim(), head)
+
+  t.true((await bob.push('origin', 'main', { force: true })).success)
+  t.is(gitIn(ORIGIN, 'rev-parse main').trim(), bob.getHeadCommitHash())
+})
+
+// Test: Pushing a new branch can set it as upstream
+test('push should set the upstream of a new branch', async (t) => {
+  const alice = new GitRepo(ALICE)
+  gitIn(ALICE, 'checkout -q -b feature')
+  commitIn(ALICE, 'f.txt', 'f\n', 'Add f')
+
+  t.true((await alice.push('origin', 'feature', { setUpstream: true })).success)
+  const feature = alice.listBranches(false).find((branch) => branch.name === 'feature')
+  t.is(feature.upstream, 'origin/feature')
+  t.is(feature.ahead, 0)
+})
+
+// Test: Fetching updates remote branches and reports transfer progress
+test('fetch should update remote branches', async (t) => {
+  const bob = new GitRepo(BOB)
+  const head = commitIn(ALICE, 'b.txt', 'b\n', 'Add b')
+  gitIn(ALICE, 'push -q origin main')
+
+  const progress = []
+  let asked = false
+  await bob.fetch(null, null, () => { asked = true; return null }, (err, p) => progress.push(p))
+  t.false(asked)
+  t.is(gitIn(BOB, 'rev-parse origin/main').trim(), head)
+  t.is(bob.getHeadCommitHash() === head, false)
+  await new Promise((resolve) => setImmediate(resolve))
+  t.true(progress.length > 0)
+  const last = progress[progress.length - 1]
+  t.is(last.indexedObjects, last.totalObjects)
+
+  gitIn(ALICE, 'push -q origin main:gone')
+  await bob.fetch('origin')
+  gitIn(ALICE, 'push -q origin :gone')
+  await bob.fetch('origin', { prune: true })
+  t.false(bob.listBranches().some((branch) => branch.name === 'origin/gone'))
+})
+
+// Test: Pulling fast-forwards, merges or rebases onto the upstream
+test('pull should fast-forward, merge and rebase', async (t) => {
+  const bob = new GitRepo(BOB)
+
+  t.is((await bob.pull()).status, 'upToDate')
+
+  const b = commitIn(ALICE, 'b.txt', 'b\n', 'Add b')
+  gitIn(ALICE, 'push -q origin main')
+  t.deepEqual(await bob.pull(), { status: 'fastForward', head: b, conflicts: [] })
+  t.is(readFileSync(join(BOB, 'b.txt'), 'utf8'), 'b\n')
+
+  commitIn(ALICE, 'c.txt', 'c\n', 'Add c')
+  gitIn(ALICE, 'push -q origin main')
+  commitIn(BOB, 'd.txt', 'd\n', 'Add d')
+  await t.throwsAsync(() => bob.pull(null, { strategy: 'ff-only' }), { message: /Cannot fast-forward/ })
+
+  const merged = await bob.pull()
+  t.is(merged.status, 'merged')
+  t.is(gitIn(BOB, 'rev-list --parents -n 1 HEAD').trim().split(' ').length, 3)
+  t.is(gitIn(BOB, 'log -1 --format=%s').trim(), "Merge remote-tracking branch 'origin/main'")
+
+  gitIn(BOB, 'reset -q --hard HEAD~1')
+  const rebased = await bob.pull(null, { strategy: 'rebase' })
+  t.is(rebased.status, 'rebased')
+  t.is(gitIn(BOB, 'log --format=%s').trim().split('\n').join(','), 'Add d,Add c,Add b,Initial commit')
+  t.is(gitIn(BOB, 'status --porcelain'), '')
+})
+
+// Test: Pull stops with conflict markers when both sides changed the same lines
+test('pull should report conflicts', async (t) => {
+  const bob = new GitRepo(BOB)
+  commitIn(ALICE, 'a.txt', 'ONE\ntwo\nthree\n', 'Shout one')
+  gitIn(ALICE, 'push -q origin main')
+  commitIn(BOB, 'a.txt', 'uno\ntwo\nthree\n', 'Translate one')
+
+  const result = await bob.pull()
+  t.is(result.status, 'conflicts')
+  t.deepEqual(result.conflicts, ['a.txt'])
+  t.regex(readFileSync(join(BOB, 'a.txt'), 'utf8'), /<<<<<<<[\s\S]*=======[\s\S]*>>>>>>>/)
+})
+
+// Test: Credential providers may answer with a promise, and only real credentials count as attempts
+test('fetch should ask an async credentials provider until it gives up', async (t) => {
+  const server = createServer((req, res) => {
+    res.writeHead(401, { 'WWW-Authenticate': 'Basic realm="test"' })
+    res.end()
+  })
+  await new Promise((resolve) => server.listen(0, '127.0.0.1', resolve))
+  const url = `http://127.0.0.1:${server.address().port}/repo.git`
+  gitIn(BOB, `remote add locked ${url}`)
+
+  const bob = new GitRepo(BOB)
+  const requests = []
+  try {
+    await t.throwsAsync(
+      () => bob.fetch('locked', null, async (err, request) => {
+        requests.push(request)
+        await new Promise((resolve) => setTimeout(resolve, 5))
+        return { username: 'bob', password: 'wrong' }
+      }),
+      { message: /Authentication failed|too many redirects or authentication replays/ },
+    )
+    const thrown = await t.throwsAsync(() => bob.fetch('locked', null, () => { throw new Error('no keychain') }))
+    t.regex(thrown.message, /Credentials callback failed: .*no keychain/)
+  } finally {
+    server.close()
+  }
+
+  t.deepEqual(requests.map((request) => request.attempt), [1, 2, 3])
+  t.is(requests[0].url, url)
+  t.deepEqual(requests[0].allowedTypes, ['userPass'])
+})
@@ -0,0 +1,139 @@
+import test from 'ava'
+import { join } from 'path'
+import { writeFileSync } from 'fs'
+
+import { initRepository } from '../index.js'
+import { testRepo } from './helpers.mjs'
+
+const { dir: TEST_DIR, git, read, commitFile } = testRepo('test-git-sequencer')
+
+function subjects() {
+  return git('log --format=%s').trim().split('\n')
+}
+
+// main has "Translate one"; feature has "Add b" and "Shout one"
+function setupRepo() {
+  const repo = initRepository(TEST_DIR)
+  git('config user.name "Test User"')
+  git('config user.email test@example.com')
+  commitFile('a.txt', 'one\ntwo\nthree\n', 'Initial commit')
+  git('branch -M main')
+  git('checkout -q -b feature')
+  commitFile('b.txt', 'b\n', 'Add b', 'Alice <alice@example.com>')
+  commitFile('a.txt', 'ONE\ntwo\nthree\n', 'Shout one', 'Bob <bob@example.com>')
+  git('checkout -q main')
+  commitFile('a.txt', 'uno\ntwo\nthree\n', 'Translate one')
+  return repo
+}
+
+// Test: Cherry-picking keeps the original author and message
+test('cherryPick should apply a commit or stop on conflicts', (t) => {
+  const repo = setupRepo()
+  t.is(repo.repositoryState().state, 'clean')
+
+  const picked = repo.cherryPick('feature~1')
+  t.is(picked.status, 'completed')
+  t.is(picked.head, repo.getHeadCommitHash())
+  t.is(git('log -1 --format="%an|%s"').trim(), 'Alice|Add b')
+  t.is(repo.cherryPick('feature~1').status, 'empty')
+
+  writeFileSync(join(TEST_DIR, 'b.txt'), 'local\n')
+  const conflicted = repo.cherryPick('feature')
+  t.deepEqual(conflicted.conflicts, ['a.txt'])
+  t.like(repo.repositoryState(), { state: 'cherryPick', conflicts: ['a.txt'] })
+
+  // Only the files the cherry-pick changed are reset
+  repo.abortOperation()
+  t.is(repo.repositoryState().state, 'clean')
+  t.is(read('a.txt'), 'uno\ntwo\nthree\n')
+  t.is(git('status --porcelain'), ' M b.txt\n')
+  git('checkout -- b.txt')
+
+  // The message and author default to the picked commit's
+  repo.cherryPick('feature')
+  writeFileSync(join(TEST_DIR, 'a.txt'), 'UNO\ntwo\nthree\n')
+  repo.markResolved('a.txt')
+  repo.commit()
+  t.is(repo.repositoryState().state, 'clean')
+  t.deepEqual(subjects(), ['Shout one', 'Add b', 'Translate one', 'Initial commit'])
+  t.is(git('log -1 --format="%an|%cn|%B"').trim(), 'Bob|Test User|Shout one')
+
+  git('checkout -q -b other')
+  commitFile('b.txt', 'b2\n', 'Change b')
+  git('checkout -q main')
+  writeFileSync(join(TEST_DIR, 'b.txt'), 'local\n')
+  t.throws(() => repo.cherryPick('other'), { message: /Cherry-pick would overwrite local changes: b\.txt/ })
+  t.is(read('b.txt'), 'local\n')
+
+  // Staged changes to unrelated files block it too, and are named
+  git('checkout -- b.txt')
+  writeFileSync(join(TEST_DIR, 'c.txt'), 'staged\n')
+  git('add c.txt')
+  t.throws(() => repo.cherryPick('other'), { message: /Cherry-pick cannot run with staged changes: c\.txt/ })
+})
+
+// Test: Reverting commits the inverse change
+test('revert should undo a commit', (t) => {
+  const repo = setupRepo()
+  const result = repo.revert('HEAD')
+  t.is(result.status, 'completed')
+  t.is(read('a.txt'), 'one\ntwo\nthree\n')
+  t.is(subjects()[0], 'Revert "Translate one"')
+  t.regex(git('log -1 --format=%b'), /This reverts commit [0-9a-f]{40}\./)
+
+  commitFile('a.txt', 'eins\ntwo\nthree\n', 'Translate one again')
+  t.is(repo.revert('HEAD~2').status, 'conflicts')
+  t.is(repo.repositoryState().state, 'revert')
+  repo.abortOperation()
+  t.is(read('a.txt'), 'eins\ntwo\nthree\n')
+  t.is(git('status --porcelain'), '')
+
+  repo.revert('HEAD~2')
+  writeFileSync(join(TEST_DIR, 'a.txt'), 'one\ntwo\nthree\n')
+  repo.markResolved('a.txt')
+  repo.commit()
+  t.is(subjects()[0], 'Revert "Translate one"')
+  t.is(repo.repositoryState().state, 'clean')
+})
+
+// Test: Rebasing stops on conflicts and can be continued, skipped or aborted
+test('rebase should replay commits and handle conflicts', (t) => {
+  const repo = setupRepo()
+  git('checkout -q feature')
+  const original = repo.getHeadCommitHash()
+
+  const stopped = repo.rebase('main')
+  t.is(stopped.status, 'conflicts')
+  t.deepEqual(stopped.conflicts, ['a.txt'])
+  t.deepEqual(repo.repositoryState(), {
+    state: 'rebase',
+    conflicts: ['a.txt'],
+    rebaseStep: 2,
+    rebaseTotal: 2,
+    rebaseBranch: 'refs/heads/feature',
+    rebaseAction: 'pick',
+    rebaseCommit: git('rev-parse feature').trim(),
+  })
+  t.throws(() => repo.rebaseContinue(), { message: /Resolve conflicts before continuing: a\.txt/ })
+
+  repo.rebaseAbort()
+  t.is(repo.repositoryState().state, 'clean')
+  t.is(repo.getHeadCommitHash(), original)
+  t.is(repo.getCurrentBranch(), 'feature')
+
+  repo.rebase('main')
+  writeFileSync(join(TEST_DIR, 'a.txt'), 'UNO\ntwo\nthree\n')
+  repo.markResolved('a.txt')
+  t.is(repo.rebaseContinue().status, 'completed')
+  t.is(repo.getCurrentBranch(), 'feature')
+  t.deepEqual(subjects(), ['Shout one', 'Add b', 'Translate one', 'Initial commit'])
+  t.is(read('a.txt'), 'UNO\ntwo\nthree\n')
+  t.is(git('log -1 --format=%an HEAD~1').trim(), 'Alice')
+
+  git(`reset -q --hard ${original}`)
+  repo.rebase('main')
+  t.is(repo.rebaseSkip().status, 'completed')
+  t.deepEqual(subjects(), ['Add b', 'Translate one', 'Initial commit'])
+  t.is(git('status --porcelain'), '')
+  t.throws(() => repo.rebaseContinue(), { message: /No rebase in progress/ })
+})
@@ -0,0 +1,102 @@
+import test from 'ava'
+import { join } from 'path'
+import { existsSync, writeFileSync, readFileSync } from 'fs'
+
+import { initRepository, findRepository } from '../index.js'
+import { testRepo } from './helpers.mjs'
+
+const { dir: TEST_DIR, git } = testRepo('test-git-staging')
+
+const ORIGINAL = Array.from({ length: 20 }, (_, i) => `line ${i + 1}`)
+
+function lines(changes) {
+  return ORIGINAL.map((line, i) => changes[i + 1] ?? line).join('\n') + '\n'
+}
+
+function setupRepo() {
+  const repo = initRepository(TEST_DIR)
+  writeFileSync(join(TEST_DIR, 'file.txt'), lines({}))
+  repo.addAll()
+  git('commit -q -m "Initial commit"')
+  writeFileSync(join(TEST_DIR, 'file.txt'), lines({ 2: 'changed 2', 15: 'changed 15' }))
+  return repo
+}
+
+// Test: Stage and unstage whole paths, including a brand new file
+test('stagePaths and unstagePaths should move files in and out of the index', (t) => {
+  const repo = setupRepo()
+  writeFileSync(join(TEST_DIR, 'new.txt'), 'new\n')
+
+  repo.stagePaths(['file.txt', 'new.txt'])
+  t.is(git('diff --cached --name-only'), 'file.txt\nnew.txt\n')
+
+  repo.unstagePaths(['new.txt'])
+  t.is(git('diff --cached --name-only'), 'file.txt\n')
+})
+
+// Test: Paths are taken literally, not as glob patterns
+test('stagePaths and unstagePaths should not expand glob characters', (t) => {
+  const repo = setupRepo()
+  writeFileSync(join(TEST_DIR, 'a.txt'), 'a\n')
+  writeFileSync(join(TEST_DIR, '[a].txt'), 'brackets\n')
+
+  repo.stagePaths(['[a].txt'])
+  t.is(git('diff --cached --name-only'), '[a].txt\n')
+
+  repo.stagePaths(['a.txt'])
+  repo.unstagePaths(['[a].txt'])
+  t.is(git('diff --cached --name-only'), 'a.txt\n')
+})
+
+// Test: Stage a single hunk selected by line range
+test('stageHunk should stage only the selected lines', (t) => {
+  const repo = setupRepo()
+
+  repo.stageHunk('file.txt', { startLine: 15, endLine: 15 })
+  t.is(git('show :file.txt'), lines({ 15: 'changed 15' }))
+  t.is(readFileSync(join(TEST_DIR, 'file.txt'), 'utf8'), lines({ 2: 'changed 2', 15: 'changed 15' }))
+
+  t.throws(() => repo.stageHunk('file.txt', { startLine: 8 }), { message: /No matching changes/ })
+})
+
+// Test: Hunk headers select whole hunks, and unstaging reverses just that hunk
+test('stageHunk and unstageHunk should accept hunk headers', (t) => {
+  const repo = setupRepo()
+  repo.stagePaths(['file.txt'])
+
+  repo.unstageHunk('file.txt', { header: '@@ -1,5 +1,5 @@' })
+  t.is(git('show :file.txt'), lines({ 15: 'changed 15' }))
+
+  repo.stageHunk('file.txt', { header: '@@ -1,5 +1,5 @@' })
+  t.is(git('show :file.txt'), lines({ 2: 'changed 2', 15: 'changed 15' }))
+})
+
+// Test: Discard drops a hunk from the working tree but keeps the rest
+test('discardHunk should revert selected working tree changes', (t) => {
+  const repo = setupRepo()
+
+  repo.discardHunk('file.txt', { startLine: 2 })
+  t.is(readFileSync(join(TEST_DIR, 'file.txt'), 'utf8'), lines({ 15: 'changed 15' }))
+  t.is(git('status --porcelain'), ' M file.txt\n')
+
+  writeFileSync(join(TEST_DIR, 'scratch.txt'), 'temporary\n')
+  repo.discardHunk('scratch.txt', { startLine: 1 })
+  t.false(existsSync(join(TEST_DIR, 'scratch.txt')))
+})
+
+// Test: Hunks are computed on filtered content, so CRLF checkouts don't stage or lose line endings
+test('stageHunk and discardHunk should apply autocrlf filters', (t) => {
+  setupRepo()
+  git('config core.autocrlf true')
+  // libgit2 caches core settings per handle, so reopen after changing them
+  const repo = findRepository(TEST_DIR)
+  const crlf = (text) => text.replace(/\n/g, '\r\n')
+  writeFileSync(join(TEST_DIR, 'file.txt'), crlf(lines({ 2: 'changed 2', 15: 'changed 15' })))
+
+  repo.stageHunk('file.txt', { startLine: 15 })
+  t.is(git('show :file.txt'), lines({ 15: 'changed 15' }))
+
+  repo.discardHunk('file.txt', { startLine: 2 })
+  t.is(readFileSync(join(TEST_DIR, 'file.txt'), 'utf8'), crlf(lines({ 15: 'changed 15' })))
+  t.is(git('status --porcelain'), 'M  file.txt\n')
+})
@@ -0,0 +1,91 @@
+import test from 'ava'
+import { join } from 'path'
+import { writeFileSync, existsSync } from 'fs'
+
+import { initRepository } from '../index.js'
+import { testRepo } from './helpers.mjs'
+
+const { dir: TEST_DIR, git, read } = testRepo('test-git-stash')
+
+function setupRepo() {
+  const repo = initRepository(TEST_DIR)
+  git('config user.name "Test User"')
+  git('config user.email test@example.com')
+  writeFileSync(join(TEST_DIR, 'a.txt'), 'one\ntwo\nthree\n')
+  writeFileSync(join(TEST_DIR, 'b.txt'), 'b\n')
+  git('add -A')
+  git('commit -q -m "Initial commit"')
+  git('branch -M main')
+  return repo
+}
+
+// Test: Saving stashes changes and lists them newest first
+test('stashSave should stash changes and stashList should list them', (t) => {
+  const repo = setupRepo()
+  t.is(repo.stashSave('nothing'), null)
+
+  writeFileSync(join(TEST_DIR, 'a.txt'), 'ONE\ntwo\nthree\n')
+  const first = repo.stashSave('first')
+  t.is(read('a.txt'), 'one\ntwo\nthree\n')
+
+  writeFileSync(join(TEST_DIR, 'b.txt'), 'B\n')
+  writeFileSync(join(TEST_DIR, 'new.txt'), 'new\n')
+  repo.stashSave('second', { includeUntracked: true })
+  t.false(existsSync(join(TEST_DIR, 'new.txt')))
+
+  const stashes = repo.stashList()
+  t.deepEqual(stashes.map((s) => [s.index, s.message]), [[0, 'On main: second'], [1, 'On main: first']])
+  t.is(stashes[1].hash, first)
+  t.true(stashes[0].time > 0)
+
+  const shown = repo.stashShow(0)
+  t.deepEqual(shown.map((file) => [file.newPath, file.status]), [['b.txt', 'modified'], ['new.txt', 'added']])
+  t.like(repo.stashShow(1)[0], { newPath: 'a.txt', additions: 1, deletions: 1 })
+  t.throws(() => repo.stashShow(5), { message: /No stash found/ })
+})
+
+// Test: Keeping the index leaves staged changes in place
+test('stashSave should keep the index when asked', (t) => {
+  const repo = setupRepo()
+  writeFileSync(join(TEST_DIR, 'a.txt'), 'ONE\ntwo\nthree\n')
+  git('add a.txt')
+  writeFileSync(join(TEST_DIR, 'b.txt'), 'B\n')
+
+  repo.stashSave(null, { keepIndex: true })
+  t.is(read('a.txt'), 'ONE\ntwo\nthree\n')
+  t.is(read('b.txt'), 'b\n')
+
+  git('reset -q --hard')
+  t.deepEqual(repo.stashApply(0, true), { applied: true, conflicts: [] })
+  t.is(git('diff --cached --name-only').trim(), 'a.txt')
+  t.is(read('b.txt'), 'B\n')
+  t.is(repo.stashList().length, 1)
+})
+
+// Test: Popping drops the stash, unless applying it conflicted
+test('stashPop should drop the stash unless there are conflicts', (t) => {
+  const repo = setupRepo()
+  writeFileSync(join(TEST_DIR, 'a.txt'), 'ONE\ntwo\nthree\n')
+  repo.stashSave('shout')
+
+  t.deepEqual(repo.stashPop(), { applied: true, conflicts: [] })
+  t.is(read('a.txt'), 'ONE\ntwo\nthree\n')
+  t.is(repo.stashList().length, 0)
+
+  repo.stashSave('shout')
+  writeFileSync(join(TEST_DIR, 'a.txt'), 'uno\ntwo\nthree\n')
+  t.deepEqual(repo.stashPop(), { applied: false, conflicts: ['a.txt'] })
+  t.is(read('a.txt'), 'uno\ntwo\nthree\n')
+
+  git('commit -q -am "Translate one"')
+  const result = repo.stashPop()
+  t.deepEqual(result, { applied: true, conflicts: ['a.txt'] })
+  t.regex(read('a.txt'), /<<<<<<<[\s\S]*=======[\s\S]*>>>>>>>/)
+  t.is(repo.stashList().length, 1)
+
+  git('checkout -q -f HEAD -- a.txt')
+  git('reset -q --hard')
+  repo.stashDrop()
+  t.is(repo.stashList().length, 0)
+  t.throws(() => repo.stashDrop(), { message: /Failed to drop stash@\{0\}/ })
+})
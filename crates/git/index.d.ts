@@ -8,6 +8,386 @@ export declare function initRepository(path: string): GitRepo
 export declare function findRepository(startPath: string): GitRepo
 export declare function isGitRepository(path: string): boolean
 export declare function getBranchName(path: string): string
+/**
+ * Picks the changes of one file to stage, unstage or discard: either the hunk
+ * whose header matches, or every changed line within `startLine..=endLine`
+ * (1-based, counted on the new side of the diff).
+ */
+export interface HunkSelector {
+  header?: string
+  startLine?: number
+  endLine?: number
+}
+export interface BlameOptions {
+  /** Blame the file as of this revision instead of the working tree */
+  revision?: string
+  ignoreWhitespace?: boolean
+  /** Only follow the first parent of merge commits */
+  firstParent?: boolean
+  /** First line to blame, 1-based */
+  startLine?: number
+  /** Last line to blame, 1-based and inclusive */
+  endLine?: number
+}
+/** Consecutive lines last changed by the same commit. */
+export interface BlameRange {
+  /** First line of the range, 1-based */
+  startLine: number
+  /** Last line of the range, 1-based and inclusive */
+  endLine: number
+  /** All zeros for lines that are not committed yet */
+  commitHash: string
+  isCommitted: boolean
+  authorName: string
+  authorEmail: string
+  commitTime: number
+  /** First line of the commit message */
+  summary: string
+  /** Path of the file in that commit, which differs after a rename */
+  originalPath: string
+  /** Where the range started in that commit, 1-based */
+  originalStartLine: number
+  /** Whether the commit is the oldest one blame looked at */
+  isBoundary: boolean
+}
+export interface BranchInfo {
+  /** Short name, like `main` or `origin/main` */
+  name: string
+  /** Full reference name, like `refs/heads/main` */
+  fullName: string
+  isRemote: boolean
+  /** Whether HEAD points at this branch */
+  isHead: boolean
+  /** Short name of the upstream branch, if one is configured */
+  upstream?: string
+  /** Whether the configured upstream no longer exists, e.g. after it was deleted on the remote */
+  isUpstreamGone: boolean
+  /** Commits on this branch that are not on its upstream */
+  ahead?: number
+  /** Commits on the upstream that are not on this branch */
+  behind?: number
+  lastCommitHash: string
+  lastCommitSummary: string
+  lastCommitAuthor: string
+  lastCommitTime: number
+}
+export interface CheckoutOptions {
+  /**
+   * `safe` (default) refuses to overwrite local changes, `force` discards
+   * them, and `merge` carries them over to the new tree like `git checkout -m`
+   */
+  strategy?: string
+}
+export interface CheckoutProgress {
+  /** File being written, if any */
+  path?: string
+  completed: number
+  total: number
+}
+export interface CheckoutResult {
+  /** Whether HEAD now points at the target */
+  success: boolean
+  /**
+   * With `safe`, local changes that blocked the checkout; nothing was changed.
+   * With `merge`, files left with conflict markers
+   */
+  conflicts: Array<string>
+}
+export interface CommitOptions {
+  /** Replace HEAD instead of adding a commit on top of it, like `git commit --amend` */
+  amend?: boolean
+  /** Commit even when nothing changed, like `git commit --allow-empty` */
+  allowEmpty?: boolean
+  /** Defaults to `user.name` from git config */
+  committerName?: string
+  /** Defaults to `user.email` from git config */
+  committerEmail?: string
+}
+export interface DiffOptions {
+  /** Unchanged lines shown around each change; defaults to 3 */
+  contextLines?: number
+  /** Only diff these paths or pathspecs */
+  paths?: Array<string>
+  ignoreWhitespace?: boolean
+  /** Pair up deleted and added files that are renames or copies; defaults to true */
+  detectRenames?: boolean
+  /** Show untracked files as additions in working-tree diffs; defaults to true */
+  includeUntracked?: boolean
+}
+export interface DiffLine {
+  /** `+` for added, `-` for removed and a space for context lines */
+  origin: string
+  oldLineNumber?: number
+  newLineNumber?: number
+  /** Line text without its line terminator */
+  content: string
+}
+export interface DiffHunk {
+  /** For example `@@ -10,7 +10,8 @@ fn main()` */
+  header: string
+  oldStart: number
+  oldLines: number
+  newStart: number
+  newLines: number
+  lines: Array<DiffLine>
+}
+export interface DiffFile {
+  /** Missing for added files */
+  oldPath?: string
+  /** Missing for deleted files */
+  newPath?: string
+  /** One of `added`, `deleted`, `modified`, `renamed`, `copied`, `typechange`, `untracked` or `conflicted` */
+  status: string
+  isBinary: boolean
+  isRenamed: boolean
+  additions: number
+  deletions: number
+  /** Empty for binary files */
+  hunks: Array<DiffHunk>
+}
+/** A run of changed lines in the buffer, for drawing gutter markers. */
+export interface LineChange {
+  /** `added`, `modified` or `deleted` */
+  kind: string
+  /** Zero-based first changed line in the buffer */
+  startLine: number
+  /**
+   * Zero-based line after the last changed one. Equal to `start_line` for
+   * deletions, which sit between `start_line - 1` and `start_line`
+   */
+  endLine: number
+}
+export interface FileHistoryOptions {
+  /** Keep following the file through renames and copies; defaults to false */
+  followRenames?: boolean
+  /** Matching commits to skip, for pagination */
+  skip?: number
+  /** Stop after this many matching commits */
+  limit?: number
+}
+/** A page of file history from `getFileHistoryAsync`. */
+export interface FileHistoryUpdate {
+  commits: Array<CommitInfo>
+  /** Set on the last update, which carries no commits */
+  done: boolean
+  cancelled: boolean
+}
+export interface RebaseStep {
+  /** `pick`, `reword`, `edit`, `squash`, `fixup` or `drop` */
+  action: string
+  commit: string
+  /**
+   * New message for `reword`, and for `squash` or `fixup` instead of the
+   * combined one. Planned steps carry the current message, which is ignored
+   * when left unchanged
+   */
+  message?: string
+}
+export interface LogOptions {
+  /**
+   * Revisions to start from, like `main`, `v1.0..v2.0`, `main...feature` or
+   * `^old` to exclude; defaults to HEAD
+   */
+  revisions?: Array<string>
+  /** Start from every branch, remote branch and tag as well */
+  all?: boolean
+  /** Case-insensitive substring of the author name or email */
+  author?: string
+  /** Case-insensitive substring of the commit message */
+  message?: string
+  /** Only commits that changed one of these paths or pathspecs */
+  paths?: Array<string>
+  /** Only follow the first parent of merge commits */
+  firstParent?: boolean
+  /** Matching commits to skip, for pagination */
+  skip?: number
+  /** Stop after this many matching commits */
+  limit?: number
+  /** Compute a graph row for each commit; cannot be combined with filters */
+  graph?: boolean
+}
+/** A line in the graph from a lane in one row to a lane in the next row. */
+export interface GraphEdge {
+  fromLane: number
+  toLane: number
+}
+/** Where a commit sits in the graph and the lines leaving its row downwards. */
+export interface GraphRow {
+  /** Zero-based column of the commit's node */
+  lane: number
+  edges: Array<GraphEdge>
+}
+export interface LogEntry {
+  hash: string
+  /** Parent hashes, first parent first */
+  parents: Array<string>
+  authorName: string
+  authorEmail: string
+  authorTime: number
+  committerName: string
+  committerEmail: string
+  commitTime: number
+  /** First line of the message */
+  summary: string
+  message: string
+  /** Set when `graph` was requested */
+  graph?: GraphRow
+}
+export interface MergeOptions {
+  /** Always create a merge commit, like `git merge --no-ff` */
+  noFf?: boolean
+  /** Fail unless the merge is a fast-forward, like `git merge --ff-only` */
+  ffOnly?: boolean
+  /** Message of the merge commit; defaults to `Merge branch '<name>'` */
+  message?: string
+}
+export interface MergeResult {
+  /** `upToDate`, `fastForward`, `merged` or `conflicts` */
+  status: string
+  /** HEAD after the merge */
+  head: string
+  /** Files left with conflict markers when the status is `conflicts` */
+  conflicts: Array<string>
+}
+export interface ConflictRegion {
+  /** Zero-based line of the `<<<<<<<` marker in the working file */
+  startLine: number
+  /** Line of the `|||||||` marker, missing only in files merged without the diff3 style */
+  baseLine?: number
+  /** Line of the `=======` marker */
+  separatorLine: number
+  /** Line of the `>>>>>>>` marker */
+  endLine: number
+}
+export interface ConflictInfo {
+  path: string
+  /** Content in the common ancestor; missing when both sides added the file */
+  ancestor?: string
+  /** Content on the current branch; missing when it deleted the file */
+  ours?: string
+  /** Content on the branch being merged; missing when it deleted the file */
+  theirs?: string
+  /** Whether any side is binary, in which case no content is returned */
+  isBinary: boolean
+  /** Conflict markers in the working file */
+  regions: Array<ConflictRegion>
+}
+export interface CredentialRequest {
+  url: string
+  /** User name embedded in the URL, like `git` in `git@github.com:org/repo.git` */
+  usernameFromUrl?: string
+  /** Kinds of credentials the server accepts: `sshKey`, `userPass`, `username` or `default` */
+  allowedTypes: Array<string>
+  /** Starts at 1 and goes up each time the previous credentials were rejected */
+  attempt: number
+}
+export interface Credentials {
+  /** Defaults to the user name from the URL */
+  username?: string
+  /** Password or HTTPS access token */
+  password?: string
+  /** Private key file for SSH */
+  sshKeyPath?: string
+  /** Public key file for SSH; derived from the private key when not given */
+  sshPublicKeyPath?: string
+  /** Passphrase of the private key */
+  passphrase?: string
+  /** Ask the running SSH agent instead of reading a key file */
+  useSshAgent?: boolean
+  /** Ask the credential helper configured in git, like `git credential fill` */
+  useCredentialHelper?: boolean
+}
+export interface TransferProgress {
+  /** Objects received while fetching, or sent while pushing */
+  completedObjects: number
+  totalObjects: number
+  /** Received objects that have been indexed; equals `completedObjects` when pushing */
+  indexedObjects: number
+  bytes: number
+}
+export interface FetchOptions {
+  /** Remove remote branches that no longer exist on the remote */
+  prune?: boolean
+}
+export interface PullOptions {
+  /** Remote branch to pull, like `main`; defaults to the upstream of the current branch */
+  branch?: string
+  /** `merge` (default), `ff-only` or `rebase` */
+  strategy?: string
+}
+export interface PullResult {
+  /** `upToDate`, `fastForward`, `merged`, `rebased` or `conflicts` */
+  status: string
+  /** HEAD after the pull */
+  head: string
+  /** Files left with conflict markers when the status is `conflicts` */
+  conflicts: Array<string>
+}
+export interface PushOptions {
+  /** Overwrite the remote branch even when it is not an ancestor, like `git push --force` */
+  force?: boolean
+  /** Track the pushed branch afterwards, like `git push -u` */
+  setUpstream?: boolean
+}
+export interface PushRejection {
+  /** Remote reference that was not updated, like `refs/heads/main` */
+  reference: string
+  reason: string
+}
+export interface PushResult {
+  success: boolean
+  rejected: Array<PushRejection>
+}
+export interface SequencerResult {
+  /**
+   * `completed`, `empty` when the changes were already on HEAD, `conflicts`,
+   * or `edit` when an interactive rebase stopped to amend a commit
+   */
+  status: string
+  /** HEAD after the operation */
+  head: string
+  /** Files left with conflict markers when the status is `conflicts` */
+  conflicts: Array<string>
+}
+export interface RepositoryStateInfo {
+  /** `clean`, `merge`, `rebase`, `cherryPick`, `revert`, `bisect` or `applyMailbox` */
+  state: string
+  /** Files with unresolved conflicts */
+  conflicts: Array<string>
+  /** One-based number of the commit being rebased */
+  rebaseStep?: number
+  /** Number of commits in the rebase */
+  rebaseTotal?: number
+  /** Branch being rebased, like `refs/heads/feature` */
+  rebaseBranch?: string
+  /** Action of the current rebase step, like `pick` or `edit` */
+  rebaseAction?: string
+  /** Commit the current rebase step applies */
+  rebaseCommit?: string
+}
+export interface StashSaveOptions {
+  /** Also stash untracked files, like `git stash -u` */
+  includeUntracked?: boolean
+  /** Leave staged changes in place, like `git stash --keep-index` */
+  keepIndex?: boolean
+}
+export interface StashEntry {
+  /** Position in the stash list; 0 is the most recent */
+  index: number
+  /** For example `On main: wip` or `WIP on main: 1a2b3c4 Add login form` */
+  message: string
+  hash: string
+  time: number
+}
+export interface StashApplyResult {
+  /**
+   * Whether the stash was applied. When it was not, `conflicts` lists the
+   * local changes that were in the way and nothing was changed
+   */
+  applied: boolean
+  /** When applied, files left with conflict markers */
+  conflicts: Array<string>
+}
 export declare class GitRepo {
   constructor(path: string)
   getHeadCommitHash(): string
@@ -16,17 +396,212 @@ export declare class GitRepo {
   createBranch(name: string, targetCommit?: string | undefined | null): void
   checkoutBranch(name: string): void
   getFileStatus(): Array<FileStatus>
-  commit(message: string, authorName: string, authorEmail: string): string
+  /**
+   * Commit the index and return the new commit. HEAD may be unborn, making
+   * this the first commit of the branch.
+   *
+   * The author defaults to `user.name` and `user.email` from git config,
+   * and the committer to the same, or the author when config has none.
+   * With `amend`, a missing message or author is kept from HEAD. Concluding
+   * a conflicted merge, cherry-pick or revert records it and ends it; the
+   * message then defaults to the one it prepared, and a cherry-pick keeps
+   * the author of the picked commit.
+   */
+  commit(message?: string | undefined | null, authorName?: string | undefined | null, authorEmail?: string | undefined | null, options?: CommitOptions | undefined | null): string
+  /**
+   * Commit like `commit`, signed by `sign`. It is called with the commit as
+   * it will be written and returns its armored signature, or a promise of
+   * it, so it can wait for `gpg` or `ssh-keygen`.
+   */
+  commitSigned(message: string | undefined | null, authorName: string | undefined | null, authorEmail: string | undefined | null, options: CommitOptions | undefined | null, sign: (err: Error | null, arg: string) => string | Promise<string>): Promise<string>
   addAll(): void
-  push(remoteName: string, branchName: string): void
-  getFileMetadata(filePath: string): FileMetadata
-  listFilesWithMetadata(directoryPath?: string | undefined | null): Array<FileMetadata>
-  getFileHistory(filePath: string): Array<CommitInfo>
+  stagePaths(paths: Array<string>): void
+  unstagePaths(paths: Array<string>): void
+  /** Stage part of the working-tree changes of `path`, like `git add -p`. */
+  stageHunk(path: string, selector: HunkSelector): void
+  /** Move part of the staged changes of `path` back out of the index, like `git reset -p`. */
+  unstageHunk(path: string, selector: HunkSelector): void
+  /** Throw away part of the unstaged changes of `path`, like `git checkout -p`. */
+  discardHunk(path: string, selector: HunkSelector): void
   listTags(): Array<TagInfo>
   createTag(tagName: string, message?: string | undefined | null, targetCommit?: string | undefined | null): string
   deleteTag(tagName: string): void
   checkoutTag(tagName: string): void
   checkoutCommit(commitHash: string): void
+  /** Unstaged changes: the index compared to the working tree. */
+  diffIndexToWorkdir(options?: DiffOptions | undefined | null): Array<DiffFile>
+  /** Staged changes: HEAD compared to the index. */
+  diffHeadToIndex(options?: DiffOptions | undefined | null): Array<DiffFile>
+  /** Staged and unstaged changes together: HEAD compared to the working tree. */
+  diffHeadToWorkdir(options?: DiffOptions | undefined | null): Array<DiffFile>
+  /** Changes between two revisions, e.g. `HEAD~3` and `main`. */
+  diffCommits(oldRevision: string, newRevision: string, options?: DiffOptions | undefined | null): Array<DiffFile>
+  /**
+   * Changed line ranges of unsaved buffer `text` compared to the HEAD
+   * version of `path`, or to the staged version with `againstIndex`.
+   *
+   * The text is run through the clean filters first, as it would be when
+   * staged. A file missing from the base shows as entirely added.
+   */
+  getLineChanges(path: string, text: string, againstIndex?: boolean | undefined | null): Array<LineChange>
+  /**
+   * Who last changed each line of `path`.
+   *
+   * Without a `revision` the working-tree file is blamed, so line numbers match
+   * what is on disk and uncommitted lines come back with `isCommitted` false.
+   */
+  blameFile(path: string, options?: BlameOptions | undefined | null): Array<BlameRange>
+  /** Local branches, then remote branches unless `includeRemote` is false, each sorted by name. */
+  listBranches(includeRemote?: boolean | undefined | null): Array<BranchInfo>
+  /** Rename a local branch. HEAD follows when the current branch is renamed. */
+  renameBranch(oldName: string, newName: string, force?: boolean | undefined | null): void
+  /**
+   * Delete a local branch. Unless `force` is set, a branch whose commits are
+   * not contained in its upstream, or in HEAD when it has none, is refused.
+   */
+  deleteBranch(name: string, force?: boolean | undefined | null): void
+  /** Track `upstream` (like `origin/main`) from local branch `name`, or stop tracking when it is null. */
+  setUpstream(name: string, upstream?: string | undefined | null): void
+  /**
+   * Switch to a local branch, or detach HEAD at a tag or any other revision.
+   *
+   * The checkout runs off the JS thread, so `progress` receives updates while
+   * files are being written.
+   */
+  checkout(target: string, options?: CheckoutOptions | undefined | null, progress?: ((err: Error | null, arg: CheckoutProgress) => any) | undefined | null): Promise<CheckoutResult>
+  /**
+   * Merge a branch, tag or commit into HEAD.
+   *
+   * Conflicts are left in the working tree and index with the status
+   * `conflicts`; resolve them with `markResolved` and `commit` to conclude.
+   */
+  merge(target: string, options?: MergeOptions | undefined | null): MergeResult
+  /**
+   * The three versions of a conflicted file and the conflict markers in its
+   * working copy, for a three-way merge view.
+   */
+  getConflict(path: string): ConflictInfo
+  /**
+   * Stage the working copy of a conflicted file as its resolution, or the
+   * deletion when the file was removed.
+   */
+  markResolved(path: string): void
+  /**
+   * Download objects and update the remote branches of `remote`, which
+   * defaults to the remote of the current branch's upstream, else `origin`.
+   *
+   * `credentials` is called when the server asks for authentication and may
+   * return a promise; without it the SSH agent and the configured credential
+   * helper are tried.
+   */
+  fetch(remote?: string | undefined | null, options?: FetchOptions | undefined | null, credentials?: ((err: Error | null, arg: CredentialRequest) => Credentials | null | undefined | Promise<Credentials | null | undefined>) | undefined | null, progress?: ((err: Error | null, arg: TransferProgress) => any) | undefined | null): Promise<void>
+  /**
+   * Fetch and integrate the upstream of the current branch.
+   *
+   * Conflicts from a merge or rebase are left in the working tree to be
+   * resolved, with the status `conflicts`.
+   */
+  pull(remote?: string | undefined | null, options?: PullOptions | undefined | null, credentials?: ((err: Error | null, arg: CredentialRequest) => Credentials | null | undefined | Promise<Credentials | null | undefined>) | undefined | null, progress?: ((err: Error | null, arg: TransferProgress) => any) | undefined | null): Promise<PullResult>
+  /**
+   * Push local branch `branchName` to the branch of the same name on `remoteName`.
+   *
+   * Rejected updates, like a non-fast-forward push, come back in `rejected`
+   * instead of throwing.
+   */
+  push(remoteName: string, branchName: string, options?: PushOptions | undefined | null, credentials?: ((err: Error | null, arg: CredentialRequest) => Credentials | null | undefined | Promise<Credentials | null | undefined>) | undefined | null, progress?: ((err: Error | null, arg: TransferProgress) => any) | undefined | null): Promise<PushResult>
+  /**
+   * Apply the changes of `commit` on top of HEAD and commit them with its
+   * author and message. For a merge commit, `mainline` is the one-based
+   * parent to diff against.
+   *
+   * Conflicts are left to be resolved and concluded with `commit`.
+   */
+  cherryPick(commit: string, mainline?: number | undefined | null): SequencerResult
+  /**
+   * Commit the inverse of the changes of `commit` on top of HEAD. For a
+   * merge commit, `mainline` is the one-based parent to keep.
+   *
+   * Conflicts are left to be resolved and concluded with `commit`.
+   */
+  revert(commit: string, mainline?: number | undefined | null): SequencerResult
+  /**
+   * Replay the commits of the current branch that are not on `upstream` on top of it.
+   *
+   * When a commit conflicts, the rebase stops with the status `conflicts`.
+   * Resolve them with `markResolved`, then call `rebaseContinue`, or
+   * `rebaseSkip` to drop the commit, or `rebaseAbort` to go back.
+   */
+  rebase(upstream: string): SequencerResult
+  /**
+   * Commit the resolved changes of the stopped commit, or amend the commit of
+   * an `edit` step with staged changes, and carry on rebasing.
+   */
+  rebaseContinue(): SequencerResult
+  /** Drop the stopped commit and carry on rebasing. */
+  rebaseSkip(): SequencerResult
+  /** Stop rebasing and restore the branch as it was before the rebase. */
+  rebaseAbort(): void
+  /**
+   * Abort the merge, cherry-pick, revert or rebase in progress, restoring
+   * the files it changed to how they were before it started. Like
+   * `git reset --merge`, local changes to other files are kept.
+   */
+  abortOperation(): void
+  /** The operation in progress, if any, so it can be continued or aborted. */
+  repositoryState(): RepositoryStateInfo
+  /** Stash local changes and return the stash commit, or null when there was nothing to stash. */
+  stashSave(message?: string | undefined | null, options?: StashSaveOptions | undefined | null): string | null
+  /** Stashes, most recent first. */
+  stashList(): Array<StashEntry>
+  /**
+   * Apply stash `index` (0 by default) and keep it. Staged changes are
+   * restored to the index as well with `reinstateIndex`.
+   */
+  stashApply(index?: number | undefined | null, reinstateIndex?: boolean | undefined | null): StashApplyResult
+  /**
+   * Apply stash `index` (0 by default) and drop it, unless applying it left
+   * conflicts to resolve.
+   */
+  stashPop(index?: number | undefined | null, reinstateIndex?: boolean | undefined | null): StashApplyResult
+  stashDrop(index?: number | undefined | null): void
+  /**
+   * Changes in stash `index` (0 by default) relative to the commit it was
+   * made on, including stashed untracked files.
+   */
+  stashShow(index?: number | undefined | null, options?: DiffOptions | undefined | null): Array<DiffFile>
+  /** Commits reachable from HEAD that changed `filePath`, newest first. */
+  getFileHistory(filePath: string, options?: FileHistoryOptions | undefined | null): Array<CommitInfo>
+  /**
+   * Like `getFileHistory`, but walks on a background thread and streams commits
+   * to `callback` in pages, so long histories neither block nor have to finish.
+   */
+  getFileHistoryAsync(filePath: string, options: FileHistoryOptions | undefined | null, callback: (err: Error | null, arg: FileHistoryUpdate) => any): FileHistoryHandle
+  /** Last commit that changed `filePath`. */
+  getFileMetadata(filePath: string): FileMetadata
+  /**
+   * Every file under `directoryPath` with the last commit that changed it,
+   * sorted by path. Untracked files are included without commit details.
+   *
+   * History is walked once for the whole directory and the result is cached
+   * until HEAD moves.
+   */
+  listFilesWithMetadata(directoryPath?: string | undefined | null): Array<FileMetadata>
+  /**
+   * The commits of the current branch that are not on `upstream`, oldest
+   * first, each as a `pick` step to be rearranged for `rebaseInteractive`.
+   * Merge commits are left out, as `git rebase -i` does.
+   */
+  getRebasePlan(upstream: string): Array<RebaseStep>
+  /**
+   * Rebuild the current branch on top of `onto` by carrying out `steps` in order.
+   *
+   * The rebase pauses with the status `conflicts` when a step conflicts and
+   * `edit` after an `edit` step is committed, to amend it by staging changes.
+   * Carry on with `rebaseContinue`, or use `rebaseSkip` or `rebaseAbort`.
+   */
+  rebaseInteractive(onto: string, steps: Array<RebaseStep>): SequencerResult
+  /** Commits in topological order, newest first, like `git log --topo-order`. */
+  log(options?: LogOptions | undefined | null): Array<LogEntry>
 }
 export declare class FileStatus {
   path: string
@@ -54,6 +629,8 @@ export declare class CommitInfo {
   commitTime: number
   addedLines: number
   deletedLines: number
+  /** Path of the file in this commit, set by file history */
+  filePath?: string
 }
 export declare class TagInfo {
   name: string
@@ -63,3 +640,9 @@ export declare class TagInfo {
   taggerEmail: string
   tagTime: number
 }
+/** A running history walk; pages keep arriving on the callback until `done`. */
+export declare class FileHistoryHandle {
+  /** Stop the walk; a final update with `cancelled` set follows. */
+  cancel(): void
+  isDone(): boolean
+}
@@ -310,13 +310,14 @@ if (!nativeBinding) {
   throw new Error(`Failed to load native binding`)
 }
 
-const { GitRepo, FileStatus, FileMetadata, CommitInfo, TagInfo, cloneRepository, initRepository, findRepository, isGitRepository, getBranchName } = nativeBinding
+const { GitRepo, FileStatus, FileMetadata, CommitInfo, TagInfo, FileHistoryHandle, cloneRepository, initRepository, findRepository, isGitRepository, getBranchName } = nativeBinding
 
 module.exports.GitRepo = GitRepo
 module.exports.FileStatus = FileStatus
 module.exports.FileMetadata = FileMetadata
 module.exports.CommitInfo = CommitInfo
 module.exports.TagInfo = TagInfo
+module.exports.FileHistoryHandle = FileHistoryHandle
 module.exports.cloneRepository = cloneRepository
 module.exports.initRepository = initRepository
 module.exports.findRepository = findRepository
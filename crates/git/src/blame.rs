@@ -0,0 +1,142 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use git2::{Blame, BlameOptions as GitBlameOptions, Oid};
+use napi::{Error, Result, Status};
+
+use crate::GitRepo;
+
+#[napi(object)]
+#[derive(Debug, Clone, Default)]
+pub struct BlameOptions {
+  /// Blame the file as of this revision instead of the working tree
+  pub revision: Option<String>,
+  pub ignore_whitespace: Option<bool>,
+  /// Only follow the first parent of merge commits
+  pub first_parent: Option<bool>,
+  /// First line to blame, 1-based
+  pub start_line: Option<u32>,
+  /// Last line to blame, 1-based and inclusive
+  pub end_line: Option<u32>,
+}
+
+/// Consecutive lines last changed by the same commit.
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct BlameRange {
+  /// First line of the range, 1-based
+  pub start_line: u32,
+  /// Last line of the range, 1-based and inclusive
+  pub end_line: u32,
+  /// All zeros for lines that are not committed yet
+  pub commit_hash: String,
+  pub is_committed: bool,
+  pub author_name: String,
+  pub author_email: String,
+  pub commit_time: i64,
+  /// First line of the commit message
+  pub summary: String,
+  /// Path of the file in that commit, which differs after a rename
+  pub original_path: String,
+  /// Where the range started in that commit, 1-based
+  pub original_start_line: u32,
+  /// Whether the commit is the oldest one blame looked at
+  pub is_boundary: bool,
+}
+
+#[napi]
+impl GitRepo {
+  /// Who last changed each line of `path`.
+  ///
+  /// Without a `revision` the working-tree file is blamed, so line numbers match
+  /// what is on disk and uncommitted lines come back with `isCommitted` false.
+  #[napi]
+  pub fn blame_file(&self, path: String, options: Option<BlameOptions>) -> Result<Vec<BlameRange>> {
+    let options = options.unwrap_or_default();
+    let mut blame_options = GitBlameOptions::new();
+    blame_options
+      .ignore_whitespace(options.ignore_whitespace.unwrap_or(false))
+      .first_parent(options.first_parent.unwrap_or(false));
+
+    let mut working_copy = None;
+    if let Some(revision) = &options.revision {
+      let commit = self.repo.revparse_single(revision)
+        .and_then(|object| object.peel_to_commit())
+        .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to resolve {}: {}", revision, e)))?;
+      blame_options.newest_commit(commit.id());
+      if let Some(start_line) = options.start_line {
+        blame_options.min_line(start_line as usize);
+      }
+      if let Some(end_line) = options.end_line {
+        blame_options.max_line(end_line as usize);
+      }
+    } else {
+      working_copy = self.workdir_file(&path)?;
+    }
+
+    let blame = self.repo.blame_file(Path::new(&path), Some(&mut blame_options))
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to blame {}: {}", path, e)))?;
+    let first = options.start_line.unwrap_or(1).max(1);
+    let last = options.end_line.unwrap_or(u32::MAX);
+
+    match &working_copy {
+      Some(contents) => {
+        let blame = blame.blame_buffer(contents)
+          .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to blame {}: {}", path, e)))?;
+        Ok(self.blame_ranges(&blame, &path, first, last))
+      }
+      None => Ok(self.blame_ranges(&blame, &path, first, last)),
+    }
+  }
+}
+
+impl GitRepo {
+  /// Convert blame hunks to ranges, clipped to lines `first..=last`.
+  fn blame_ranges(&self, blame: &Blame, path: &str, first: u32, last: u32) -> Vec<BlameRange> {
+    let mut commits = HashMap::new();
+    let mut ranges = Vec::with_capacity(blame.len());
+    for hunk in blame.iter() {
+      let hunk_start = hunk.final_start_line() as u32;
+      let hunk_end = hunk_start + hunk.lines_in_hunk() as u32 - 1;
+      if hunk.lines_in_hunk() == 0 || hunk_end < first || hunk_start > last {
+        continue;
+      }
+
+      let commit_id = hunk.final_commit_id();
+      let (author_name, author_email, commit_time, summary) = commits
+        .entry(commit_id)
+        .or_insert_with(|| self.commit_details(commit_id))
+        .clone();
+      let start_line = hunk_start.max(first);
+
+      ranges.push(BlameRange {
+        start_line,
+        end_line: hunk_end.min(last),
+        commit_hash: commit_id.to_string(),
+        is_committed: !commit_id.is_zero(),
+        author_name,
+        author_email,
+        commit_time,
+        summary,
+        original_path: hunk.path().map(|p| p.to_string_lossy().to_string()).unwrap_or_else(|| path.to_string()),
+        original_start_line: hunk.orig_start_line() as u32 + (start_line - hunk_start),
+        is_boundary: hunk.is_boundary(),
+      });
+    }
+    ranges
+  }
+
+  /// Author name, email, time and summary of a commit; empty for uncommitted lines.
+  fn commit_details(&self, id: Oid) -> (String, String, i64, String) {
+    let Ok(commit) = self.repo.find_commit(id) else {
+      return (String::new(), String::new(), 0, String::new());
+    };
+    let author = commit.author();
+    (
+      author.name().unwrap_or("").to_string(),
+      author.email().unwrap_or("").to_string(),
+      commit.time().seconds(),
+      commit.summary().unwrap_or("").to_string(),
+    )
+  }
+}
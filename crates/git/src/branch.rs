@@ -0,0 +1,161 @@
+use git2::{Branch, BranchType, ErrorCode};
+use napi::{Error, Result, Status};
+
+use crate::GitRepo;
+
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct BranchInfo {
+  /// Short name, like `main` or `origin/main`
+  pub name: String,
+  /// Full reference name, like `refs/heads/main`
+  pub full_name: String,
+  pub is_remote: bool,
+  /// Whether HEAD points at this branch
+  pub is_head: bool,
+  /// Short name of the upstream branch, if one is configured
+  pub upstream: Option<String>,
+  /// Whether the configured upstream no longer exists, e.g. after it was deleted on the remote
+  pub is_upstream_gone: bool,
+  /// Commits on this branch that are not on its upstream
+  pub ahead: Option<u32>,
+  /// Commits on the upstream that are not on this branch
+  pub behind: Option<u32>,
+  pub last_commit_hash: String,
+  pub last_commit_summary: String,
+  pub last_commit_author: String,
+  pub last_commit_time: i64,
+}
+
+#[napi]
+impl GitRepo {
+  /// Local branches, then remote branches unless `includeRemote` is false, each sorted by name.
+  #[napi]
+  pub fn list_branches(&self, include_remote: Option<bool>) -> Result<Vec<BranchInfo>> {
+    let filter = if include_remote.unwrap_or(true) { None } else { Some(BranchType::Local) };
+    let branches = self.repo.branches(filter)
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to list branches: {}", e)))?;
+
+    let mut result = Vec::new();
+    for item in branches {
+      let (branch, branch_type) = item
+        .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to read branch: {}", e)))?;
+      // `origin/HEAD` is a pointer to another remote branch, not a branch of its own
+      if branch.get().symbolic_target().is_some() {
+        continue;
+      }
+      result.push(self.branch_info(&branch, branch_type)?);
+    }
+
+    result.sort_by(|a, b| a.is_remote.cmp(&b.is_remote).then_with(|| a.name.cmp(&b.name)));
+    Ok(result)
+  }
+
+  /// Rename a local branch. HEAD follows when the current branch is renamed.
+  #[napi]
+  pub fn rename_branch(&self, old_name: String, new_name: String, force: Option<bool>) -> Result<()> {
+    let mut branch = self.find_local_branch(&old_name)?;
+    branch.rename(&new_name, force.unwrap_or(false))
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to rename branch {}: {}", old_name, e)))?;
+    Ok(())
+  }
+
+  /// Delete a local branch. Unless `force` is set, a branch whose commits are
+  /// not contained in its upstream, or in HEAD when it has none, is refused.
+  #[napi]
+  pub fn delete_branch(&self, name: String, force: Option<bool>) -> Result<()> {
+    let mut branch = self.find_local_branch(&name)?;
+    if branch.is_head() {
+      return Err(Error::new(Status::GenericFailure, format!("Cannot delete the checked out branch {}", name)));
+    }
+
+    if !force.unwrap_or(false) {
+      let tip = branch.get().peel_to_commit()
+        .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to get commit: {}", e)))?
+        .id();
+      let base = match branch.upstream() {
+        Ok(upstream) => upstream.get().target(),
+        Err(_) => self.repo.head().ok().and_then(|head| head.target()),
+      };
+
+      let merged = match base {
+        Some(base) => base == tip || self.repo.graph_descendant_of(base, tip)
+          .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to compare commits: {}", e)))?,
+        None => false,
+      };
+      if !merged {
+        return Err(Error::new(Status::GenericFailure, format!("Branch {} is not fully merged", name)));
+      }
+    }
+
+    branch.delete()
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to delete branch {}: {}", name, e)))
+  }
+
+  /// Track `upstream` (like `origin/main`) from local branch `name`, or stop tracking when it is null.
+  #[napi]
+  pub fn set_upstream(&self, name: String, upstream: Option<String>) -> Result<()> {
+    let mut branch = self.find_local_branch(&name)?;
+    branch.set_upstream(upstream.as_deref())
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to set upstream of {}: {}", name, e)))
+  }
+}
+
+impl GitRepo {
+  fn find_local_branch(&self, name: &str) -> Result<Branch<'_>> {
+    self.repo.find_branch(name, BranchType::Local)
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to find branch {}: {}", name, e)))
+  }
+
+  fn branch_info(&self, branch: &Branch, branch_type: BranchType) -> Result<BranchInfo> {
+    let reference = branch.get();
+    let name = branch.name()
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to get branch name: {}", e)))?
+      .unwrap_or("")
+      .to_string();
+    let commit = reference.peel_to_commit()
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to get commit of {}: {}", name, e)))?;
+
+    let mut info = BranchInfo {
+      name,
+      full_name: reference.name().unwrap_or("").to_string(),
+      is_remote: branch_type == BranchType::Remote,
+      is_head: branch.is_head(),
+      upstream: None,
+      is_upstream_gone: false,
+      ahead: None,
+      behind: None,
+      last_commit_hash: commit.id().to_string(),
+      last_commit_summary: commit.summary().unwrap_or("").to_string(),
+      last_commit_author: commit.author().name().unwrap_or("").to_string(),
+      last_commit_time: commit.time().seconds(),
+    };
+    if branch_type == BranchType::Remote {
+      return Ok(info);
+    }
+
+    match branch.upstream() {
+      Ok(upstream) => {
+        info.upstream = upstream.name().ok().flatten().map(str::to_string);
+        if let Some(upstream_id) = upstream.get().target() {
+          let (ahead, behind) = self.repo.graph_ahead_behind(commit.id(), upstream_id)
+            .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to compare with upstream: {}", e)))?;
+          info.ahead = Some(ahead as u32);
+          info.behind = Some(behind as u32);
+        }
+      }
+      // The branch is configured to track a ref that no longer exists
+      Err(e) if e.code() == ErrorCode::NotFound => {
+        let full_name = reference.name().unwrap_or("");
+        if let Ok(upstream_name) = self.repo.branch_upstream_name(full_name) {
+          info.is_upstream_gone = true;
+          info.upstream = upstream_name.as_str()
+            .map(|name| name.strip_prefix("refs/remotes/").unwrap_or(name).to_string());
+        }
+      }
+      Err(e) => return Err(Error::new(Status::GenericFailure, format!("Failed to get upstream: {}", e))),
+    }
+
+    Ok(info)
+  }
+}
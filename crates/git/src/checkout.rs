@@ -0,0 +1,335 @@
+use std::cell::RefCell;
+
+use git2::build::CheckoutBuilder;
+use git2::{BranchType, CheckoutNotificationType, Commit, ErrorCode, Index, IndexEntry, IndexTime, Oid, StatusOptions, Tree};
+use napi::threadsafe_function::{ThreadsafeFunction, ThreadsafeFunctionCallMode};
+use napi::{Env, Error, JsObject, Result, Status};
+
+use crate::worker::{open, spawn_promise};
+use crate::GitRepo;
+
+#[napi(object)]
+#[derive(Debug, Clone, Default)]
+pub struct CheckoutOptions {
+  /// `safe` (default) refuses to overwrite local changes, `force` discards
+  /// them, and `merge` carries them over to the new tree like `git checkout -m`
+  pub strategy: Option<String>,
+}
+
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct CheckoutProgress {
+  /// File being written, if any
+  pub path: Option<String>,
+  pub completed: u32,
+  pub total: u32,
+}
+
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct CheckoutResult {
+  /// Whether HEAD now points at the target
+  pub success: bool,
+  /// With `safe`, local changes that blocked the checkout; nothing was changed.
+  /// With `merge`, files left with conflict markers
+  pub conflicts: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum CheckoutStrategy {
+  Safe,
+  Force,
+  Merge,
+}
+
+#[napi]
+impl GitRepo {
+  /// Switch to a local branch, or detach HEAD at a tag or any other revision.
+  ///
+  /// The checkout runs off the JS thread, so `progress` receives updates while
+  /// files are being written.
+  #[napi(ts_return_type = "Promise<CheckoutResult>")]
+  pub fn checkout(
+    &self,
+    env: Env,
+    target: String,
+    options: Option<CheckoutOptions>,
+    progress: Option<ThreadsafeFunction<CheckoutProgress>>,
+  ) -> Result<JsObject> {
+    let git_dir = self.repo.path().to_path_buf();
+    let options = options.unwrap_or_default();
+    spawn_promise(&env, move || open(&git_dir)?.checkout_target(&target, &options, progress.as_ref()))
+  }
+}
+
+impl GitRepo {
+  fn checkout_target(
+    &self,
+    target: &str,
+    options: &CheckoutOptions,
+    progress: Option<&ThreadsafeFunction<CheckoutProgress>>,
+  ) -> Result<CheckoutResult> {
+    let strategy = match options.strategy.as_deref() {
+      None | Some("safe") => CheckoutStrategy::Safe,
+      Some("force") => CheckoutStrategy::Force,
+      Some("merge") => CheckoutStrategy::Merge,
+      Some(other) => {
+        return Err(Error::new(Status::InvalidArg, format!("Unknown checkout strategy: {}", other)));
+      }
+    };
+
+    let (commit_id, branch_ref) = match self.repo.find_branch(target, BranchType::Local) {
+      Ok(branch) => {
+        let commit = branch.get().peel_to_commit()
+          .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to peel to commit: {}", e)))?;
+        (commit.id(), branch.get().name().map(str::to_string))
+      }
+      Err(_) => {
+        let commit = self.repo.revparse_single(target)
+          .and_then(|object| object.peel_to_commit())
+          .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to resolve {}: {}", target, e)))?;
+        (commit.id(), None)
+      }
+    };
+
+    let report = |path: Option<&std::path::Path>, completed: usize, total: usize| {
+      if let Some(progress) = progress {
+        let update = CheckoutProgress {
+          path: path.map(|p| p.to_string_lossy().to_string()),
+          completed: completed as u32,
+          total: total as u32,
+        };
+        progress.call(Ok(update), ThreadsafeFunctionCallMode::NonBlocking);
+      }
+    };
+
+    if strategy == CheckoutStrategy::Merge {
+      return self.checkout_merge(commit_id, branch_ref.as_deref(), report);
+    }
+    let commit = self.repo.find_commit(commit_id)
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to find commit: {}", e)))?;
+    self.checkout_commit_with(&commit, branch_ref.as_deref(), strategy, report)
+  }
+
+  /// Check out `commit` and move HEAD to `branch_ref`, or detach it when there is none.
+  ///
+  /// Safe checkouts that would overwrite local changes leave everything as it
+  /// was and return the blocking paths instead.
+  pub(crate) fn checkout_commit_with(
+    &self,
+    commit: &Commit,
+    branch_ref: Option<&str>,
+    strategy: CheckoutStrategy,
+    progress: impl FnMut(Option<&std::path::Path>, usize, usize),
+  ) -> Result<CheckoutResult> {
+    let conflicts = RefCell::new(Vec::new());
+    let checkout = {
+      let mut builder = CheckoutBuilder::new();
+      if strategy == CheckoutStrategy::Force {
+        builder.force();
+      } else {
+        builder.safe();
+      }
+      builder
+        .notify_on(CheckoutNotificationType::CONFLICT)
+        .notify(|_, path, _, _, _| {
+          if let Some(path) = path {
+            conflicts.borrow_mut().push(path.to_string_lossy().to_string());
+          }
+          true
+        })
+        .progress(progress);
+      self.repo.checkout_tree(commit.as_object(), Some(&mut builder))
+    };
+
+    if let Err(e) = checkout {
+      let conflicts = conflicts.into_inner();
+      if e.code() == ErrorCode::Conflict || !conflicts.is_empty() {
+        return Ok(CheckoutResult { success: false, conflicts });
+      }
+      return Err(Error::new(Status::GenericFailure, format!("Failed to checkout tree: {}", e)));
+    }
+
+    match branch_ref {
+      Some(branch_ref) => self.repo.set_head(branch_ref),
+      None => self.repo.set_head_detached(commit.id()),
+    }
+    .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to set HEAD: {}", e)))?;
+
+    Ok(CheckoutResult { success: true, conflicts: Vec::new() })
+  }
+
+  /// Checkout that keeps local changes, like `git checkout -m`: the working
+  /// tree is merged with the target using HEAD as the base, leaving conflict
+  /// markers where both changed the same lines. The index is set to the target,
+  /// so local changes stay unstaged, and conflicts are recorded in it.
+  fn checkout_merge(
+    &self,
+    commit_id: Oid,
+    branch_ref: Option<&str>,
+    progress: impl FnMut(Option<&std::path::Path>, usize, usize),
+  ) -> Result<CheckoutResult> {
+    let commit = self.repo.find_commit(commit_id)
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to find commit: {}", e)))?;
+    let head_tree = self.repo.head()
+      .and_then(|head| head.peel_to_tree())
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to get HEAD tree: {}", e)))?;
+    let worktree = self.worktree_tree()?;
+    if worktree.id() == head_tree.id() {
+      return self.checkout_commit_with(&commit, branch_ref, CheckoutStrategy::Safe, progress);
+    }
+
+    let target_tree = commit.tree()
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to get target tree: {}", e)))?;
+
+    // The merged result is forced onto the working tree, so untracked files in its way must block it
+    let blocked: Vec<String> = self.untracked_paths()?
+      .into_iter()
+      .filter(|path| target_tree.get_path(std::path::Path::new(path)).is_ok())
+      .collect();
+    if !blocked.is_empty() {
+      return Ok(CheckoutResult { success: false, conflicts: blocked });
+    }
+
+    let mut merged = self.repo.merge_trees(&head_tree, &target_tree, &worktree, None)
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to merge local changes: {}", e)))?;
+
+    let mut checkout = CheckoutBuilder::new();
+    checkout
+      .force()
+      .update_index(false)
+      .allow_conflicts(true)
+      .conflict_style_diff3(true)
+      .our_label("target")
+      .their_label("local")
+      .progress(progress);
+    self.repo.checkout_index(Some(&mut merged), Some(&mut checkout))
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to checkout merged tree: {}", e)))?;
+
+    let mut index = self.repo.index()
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to get index: {}", e)))?;
+    index.read_tree(&target_tree)
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to read target tree: {}", e)))?;
+    let mut conflicts = Vec::new();
+    for conflict in merged.conflicts().map_err(|e| Error::new(Status::GenericFailure, format!("Failed to read conflicts: {}", e)))? {
+      let conflict = conflict
+        .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to read conflict: {}", e)))?;
+      let entries = [conflict.ancestor, conflict.our, conflict.their];
+      let Some(path) = entries.iter().flatten().next().map(|entry| entry.path.clone()) else {
+        continue;
+      };
+      let _ = index.remove(std::path::Path::new(&*String::from_utf8_lossy(&path)), 0);
+      for entry in entries.iter().flatten() {
+        index.add(entry)
+          .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to record conflict: {}", e)))?;
+      }
+      conflicts.push(String::from_utf8_lossy(&path).to_string());
+    }
+    index.write()
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to write index: {}", e)))?;
+
+    match branch_ref {
+      Some(branch_ref) => self.repo.set_head(branch_ref),
+      None => self.repo.set_head_detached(commit.id()),
+    }
+    .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to set HEAD: {}", e)))?;
+
+    Ok(CheckoutResult { success: true, conflicts })
+  }
+
+  /// Tree of the tracked files as they are in the working tree, staged or not.
+  fn worktree_tree(&self) -> Result<Tree<'_>> {
+    self.refresh_index()?;
+    let mut repo_index = self.repo.index()
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to get index: {}", e)))?;
+    if repo_index.has_conflicts() {
+      return Err(Error::new(Status::GenericFailure, "Resolve conflicts before checking out".to_string()));
+    }
+    let index_tree = repo_index.write_tree()
+      .and_then(|id| self.repo.find_tree(id))
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to write index tree: {}", e)))?;
+
+    let mut index = Index::new()
+      .and_then(|mut index| index.read_tree(&index_tree).map(|_| index))
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to read index tree: {}", e)))?;
+
+    let mut options = StatusOptions::new();
+    options.include_untracked(false);
+    let statuses = self.repo.statuses(Some(&mut options))
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to get status: {}", e)))?;
+    let workdir = self.repo.workdir()
+      .ok_or_else(|| Error::new(Status::GenericFailure, "Repository has no working directory".to_string()))?;
+    for entry in statuses.iter() {
+      let Some(path) = entry.path() else {
+        continue;
+      };
+      let status = entry.status();
+      if status.is_wt_deleted() {
+        let _ = index.remove_path(std::path::Path::new(path));
+      } else if status.is_wt_modified() || status.is_wt_typechange() {
+        // Hashing through the repository applies the clean filters, as staging would
+        let id = self.repo.blob_path(&workdir.join(path))
+          .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to read {}: {}", path, e)))?;
+        index.add(&IndexEntry {
+          ctime: IndexTime::new(0, 0),
+          mtime: IndexTime::new(0, 0),
+          dev: 0,
+          ino: 0,
+          mode: self.workdir_mode(path),
+          uid: 0,
+          gid: 0,
+          file_size: 0,
+          id,
+          flags: 0,
+          flags_extended: 0,
+          path: path.as_bytes().to_vec(),
+        })
+        .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to add {}: {}", path, e)))?;
+      }
+    }
+
+    index.write_tree_to(&self.repo)
+      .and_then(|id| self.repo.find_tree(id))
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to write working tree: {}", e)))
+  }
+
+  fn untracked_paths(&self) -> Result<Vec<String>> {
+    let mut options = StatusOptions::new();
+    options.include_untracked(true).recurse_untracked_dirs(true);
+    let statuses = self.repo.statuses(Some(&mut options))
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to get status: {}", e)))?;
+    Ok(statuses.iter()
+      .filter(|entry| entry.status().is_wt_new())
+      .filter_map(|entry| entry.path().map(str::to_string))
+      .collect())
+  }
+
+  /// Safe checkout for the simple checkout methods, failing with the paths that block it.
+  pub(crate) fn checkout_or_fail(&self, commit: &Commit, branch_ref: Option<&str>) -> Result<()> {
+    let result = self.checkout_commit_with(commit, branch_ref, CheckoutStrategy::Safe, |_, _, _| {})?;
+    if !result.success {
+      return Err(Error::new(
+        Status::GenericFailure,
+        format!("Checkout would overwrite local changes: {}", result.conflicts.join(", ")),
+      ));
+    }
+    Ok(())
+  }
+
+  pub(crate) fn conflicted_paths(&self) -> Result<Vec<String>> {
+    let index = self.repo.index()
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to get index: {}", e)))?;
+    let conflicts = index.conflicts()
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to read conflicts: {}", e)))?;
+
+    let mut paths = Vec::new();
+    for conflict in conflicts {
+      let conflict = conflict
+        .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to read conflict: {}", e)))?;
+      if let Some(entry) = conflict.our.or(conflict.their).or(conflict.ancestor) {
+        paths.push(String::from_utf8_lossy(&entry.path).to_string());
+      }
+    }
+    Ok(paths)
+  }
+}
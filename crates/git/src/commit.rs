@@ -0,0 +1,250 @@
+use git2::{Commit, ErrorCode, Oid, RepositoryState, Signature};
+use napi::threadsafe_function::ThreadsafeFunction;
+use napi::{Env, Error, JsFunction, JsObject, Result, Status};
+
+use crate::worker::{async_callback, call_async, open, spawn_promise};
+use crate::GitRepo;
+
+#[napi(object)]
+#[derive(Debug, Clone, Default)]
+pub struct CommitOptions {
+  /// Replace HEAD instead of adding a commit on top of it, like `git commit --amend`
+  pub amend: Option<bool>,
+  /// Commit even when nothing changed, like `git commit --allow-empty`
+  pub allow_empty: Option<bool>,
+  /// Defaults to `user.name` from git config
+  pub committer_name: Option<String>,
+  /// Defaults to `user.email` from git config
+  pub committer_email: Option<String>,
+}
+
+/// Called with the commit to be written and returns a promise of its armored GPG or SSH signature
+type SignCallback = ThreadsafeFunction<String>;
+
+#[napi]
+impl GitRepo {
+  /// Commit the index and return the new commit. HEAD may be unborn, making
+  /// this the first commit of the branch.
+  ///
+  /// The author defaults to `user.name` and `user.email` from git config,
+  /// and the committer to the same, or the author when config has none.
+  /// With `amend`, a missing message or author is kept from HEAD. Concluding
+  /// a conflicted merge, cherry-pick or revert records it and ends it; the
+  /// message then defaults to the one it prepared, and a cherry-pick keeps
+  /// the author of the picked commit.
+  #[napi]
+  pub fn commit(
+    &self,
+    message: Option<String>,
+    author_name: Option<String>,
+    author_email: Option<String>,
+    options: Option<CommitOptions>,
+  ) -> Result<String> {
+    self.commit_with(message, author_name, author_email, options.unwrap_or_default(), None)
+  }
+
+  /// Commit like `commit`, signed by `sign`. It is called with the commit as
+  /// it will be written and returns its armored signature, or a promise of
+  /// it, so it can wait for `gpg` or `ssh-keygen`.
+  #[napi(ts_return_type = "Promise<string>")]
+  pub fn commit_signed(
+    &self,
+    env: Env,
+    message: Option<String>,
+    author_name: Option<String>,
+    author_email: Option<String>,
+    options: Option<CommitOptions>,
+    sign: JsFunction,
+  ) -> Result<JsObject> {
+    let git_dir = self.repo.path().to_path_buf();
+    let options = options.unwrap_or_default();
+    let sign: SignCallback = async_callback(&env, sign)?;
+    spawn_promise(&env, move || {
+      open(&git_dir)?.commit_with(
+        message,
+        author_name,
+        author_email,
+        options,
+        Some(&|content| call_async(&sign, content)),
+      )
+    })
+  }
+}
+
+impl GitRepo {
+  fn commit_with(
+    &self,
+    message: Option<String>,
+    author_name: Option<String>,
+    author_email: Option<String>,
+    options: CommitOptions,
+    sign: Option<&dyn Fn(String) -> Result<String>>,
+  ) -> Result<String> {
+    let amend = options.amend.unwrap_or(false);
+
+    let head = match self.repo.head() {
+      Ok(head) => Some(head.peel_to_commit()
+        .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to get head commit: {}", e)))?),
+      Err(e) if e.code() == ErrorCode::UnbornBranch => None,
+      Err(e) => return Err(Error::new(Status::GenericFailure, format!("Failed to get HEAD: {}", e))),
+    };
+    let amended = match (amend, &head) {
+      (false, _) => None,
+      (true, None) => return Err(Error::new(Status::GenericFailure, "No commit to amend".to_string())),
+      (true, Some(_)) if self.repo.state() == RepositoryState::Merge => {
+        return Err(Error::new(Status::GenericFailure, "Cannot amend during a merge".to_string()));
+      }
+      (true, Some(head)) => Some(head),
+    };
+
+    let message = match (message, amended) {
+      (Some(message), _) => message,
+      (None, Some(head)) => head.message().unwrap_or("").to_string(),
+      (None, None) => match self.prepared_message()? {
+        Some(message) => message,
+        None => return Err(Error::new(Status::InvalidArg, "A commit message is required".to_string())),
+      },
+    };
+
+    let (config_name, config_email) = self.config_identity();
+    let author = match (amended, self.picked_commit()) {
+      (Some(head), _) if author_name.is_none() && author_email.is_none() => head.author().to_owned(),
+      (None, Some(picked)) if author_name.is_none() && author_email.is_none() => picked.author().to_owned(),
+      _ => identity(author_name.or(config_name.clone()), author_email.or(config_email.clone()), "author")?,
+    };
+    let committer = match (options.committer_name, options.committer_email) {
+      // Committed now, even when the author and its time come from an amended commit
+      (None, None) if config_name.is_none() || config_email.is_none() => identity(
+        author.name().map(str::to_string),
+        author.email().map(str::to_string),
+        "committer",
+      )?,
+      (name, email) => identity(name.or(config_name), email.or(config_email), "committer")?,
+    };
+
+    self.refresh_index()?;
+    let tree_id = self.repo.index()
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to get index: {}", e)))?
+      .write_tree()
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to write tree: {}", e)))?;
+    let tree = self.repo.find_tree(tree_id)
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to find tree: {}", e)))?;
+
+    // Concluding a conflicted merge records the merged commits as parents too
+    let merge_heads = self.merge_heads()?;
+    let parents: Vec<Commit> = match (amended, &head) {
+      (Some(head), _) => head.parents().collect(),
+      (None, Some(head)) => std::iter::once(head.clone()).chain(merge_heads.iter().cloned()).collect(),
+      (None, None) => Vec::new(),
+    };
+
+    if amended.is_none() && merge_heads.is_empty() && !options.allow_empty.unwrap_or(false) {
+      let unchanged = match &head {
+        Some(head) => head.tree_id() == tree_id,
+        None => tree.is_empty(),
+      };
+      if unchanged {
+        return Err(Error::new(Status::GenericFailure, "Nothing to commit".to_string()));
+      }
+    }
+
+    let parent_refs: Vec<&Commit> = parents.iter().collect();
+    let commit_id = match sign {
+      Some(sign) => {
+        let content = self.repo.commit_create_buffer(&author, &committer, &message, &tree, &parent_refs)
+          .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to create commit: {}", e)))?;
+        let content = content.as_str()
+          .ok_or_else(|| Error::new(Status::GenericFailure, "Commit is not valid UTF-8".to_string()))?
+          .to_string();
+        let signature = sign(content.clone())
+          .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to sign commit: {}", e)))?;
+        self.repo.commit_signed(&content, &signature, None)
+          .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to create signed commit: {}", e)))?
+      }
+      None => self.repo.commit(None, &author, &committer, &message, &tree, &parent_refs)
+        .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to commit: {}", e)))?,
+    };
+
+    let kind = if amended.is_some() {
+      " (amend)"
+    } else if head.is_none() {
+      " (initial)"
+    } else if !merge_heads.is_empty() {
+      " (merge)"
+    } else {
+      ""
+    };
+    let summary = message.lines().next().unwrap_or("");
+    self.update_head(commit_id, head.as_ref().map(Commit::id), &format!("commit{}: {}", kind, summary))?;
+
+    // Likewise for a conflicted cherry-pick or revert
+    let concludes = !merge_heads.is_empty()
+      || matches!(self.repo.state(), RepositoryState::CherryPick | RepositoryState::Revert);
+    if concludes {
+      self.repo.cleanup_state()
+        .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to clean up state: {}", e)))?;
+    }
+
+    Ok(commit_id.to_string())
+  }
+
+  fn config_identity(&self) -> (Option<String>, Option<String>) {
+    match self.repo.config() {
+      Ok(config) => (config.get_string("user.name").ok(), config.get_string("user.email").ok()),
+      Err(_) => (None, None),
+    }
+  }
+
+  /// The message a conflicted merge, cherry-pick or revert left in MERGE_MSG,
+  /// without the commented conflict list.
+  fn prepared_message(&self) -> Result<Option<String>> {
+    let concluding = matches!(
+      self.repo.state(),
+      RepositoryState::Merge | RepositoryState::CherryPick | RepositoryState::Revert
+    );
+    let message = match std::fs::read_to_string(self.repo.path().join("MERGE_MSG")) {
+      Ok(message) if concluding => message,
+      _ => return Ok(None),
+    };
+    git2::message_prettify(message, Some(b'#'))
+      .map(Some)
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to read MERGE_MSG: {}", e)))
+  }
+
+  /// The commit being cherry-picked while a conflicted cherry-pick is in progress.
+  fn picked_commit(&self) -> Option<Commit<'_>> {
+    if self.repo.state() != RepositoryState::CherryPick {
+      return None;
+    }
+    self.repo.revparse_single("CHERRY_PICK_HEAD")
+      .and_then(|object| object.peel_to_commit())
+      .ok()
+  }
+
+  /// Point the branch HEAD is on, or HEAD itself when detached, at `commit_id`,
+  /// provided it still points at `expected`, the commit the new one was built
+  /// on, or the branch is still unborn. Fails rather than dropping commits
+  /// another process made in the meantime.
+  fn update_head(&self, commit_id: Oid, expected: Option<Oid>, log_message: &str) -> Result<()> {
+    let head = self.repo.find_reference("HEAD")
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to get HEAD: {}", e)))?;
+    let name = head.symbolic_target().unwrap_or("HEAD").to_string();
+    match expected {
+      Some(expected) => self.repo.reference_matching(&name, commit_id, true, expected, log_message),
+      None => self.repo.reference(&name, commit_id, false, log_message),
+    }
+    .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to update {}: {}", name, e)))?;
+    Ok(())
+  }
+}
+
+fn identity(name: Option<String>, email: Option<String>, role: &str) -> Result<Signature<'static>> {
+  match (name, email) {
+    (Some(name), Some(email)) => Signature::now(&name, &email)
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to create signature: {}", e))),
+    _ => Err(Error::new(
+      Status::GenericFailure,
+      format!("No {} identity: pass a name and email or set user.name and user.email", role),
+    )),
+  }
+}
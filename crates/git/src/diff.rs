@@ -0,0 +1,337 @@
+use std::io::Write;
+use std::path::Path;
+
+use git2::{Delta, Diff, DiffFindOptions, DiffOptions as GitDiffOptions, Oid, Patch};
+use napi::{Error, Result, Status};
+
+use crate::GitRepo;
+
+#[napi(object)]
+#[derive(Debug, Clone, Default)]
+pub struct DiffOptions {
+  /// Unchanged lines shown around each change; defaults to 3
+  pub context_lines: Option<u32>,
+  /// Only diff these paths or pathspecs
+  pub paths: Option<Vec<String>>,
+  pub ignore_whitespace: Option<bool>,
+  /// Pair up deleted and added files that are renames or copies; defaults to true
+  pub detect_renames: Option<bool>,
+  /// Show untracked files as additions in working-tree diffs; defaults to true
+  pub include_untracked: Option<bool>,
+}
+
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct DiffLine {
+  /// `+` for added, `-` for removed and a space for context lines
+  pub origin: String,
+  pub old_line_number: Option<u32>,
+  pub new_line_number: Option<u32>,
+  /// Line text without its line terminator
+  pub content: String,
+}
+
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct DiffHunk {
+  /// For example `@@ -10,7 +10,8 @@ fn main()`
+  pub header: String,
+  pub old_start: u32,
+  pub old_lines: u32,
+  pub new_start: u32,
+  pub new_lines: u32,
+  pub lines: Vec<DiffLine>,
+}
+
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct DiffFile {
+  /// Missing for added files
+  pub old_path: Option<String>,
+  /// Missing for deleted files
+  pub new_path: Option<String>,
+  /// One of `added`, `deleted`, `modified`, `renamed`, `copied`, `typechange`, `untracked` or `conflicted`
+  pub status: String,
+  pub is_binary: bool,
+  pub is_renamed: bool,
+  pub additions: u32,
+  pub deletions: u32,
+  /// Empty for binary files
+  pub hunks: Vec<DiffHunk>,
+}
+
+/// A run of changed lines in the buffer, for drawing gutter markers.
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct LineChange {
+  /// `added`, `modified` or `deleted`
+  pub kind: String,
+  /// Zero-based first changed line in the buffer
+  pub start_line: u32,
+  /// Zero-based line after the last changed one. Equal to `start_line` for
+  /// deletions, which sit between `start_line - 1` and `start_line`
+  pub end_line: u32,
+}
+
+#[napi]
+impl GitRepo {
+  /// Unstaged changes: the index compared to the working tree.
+  #[napi]
+  pub fn diff_index_to_workdir(&self, options: Option<DiffOptions>) -> Result<Vec<DiffFile>> {
+    let options = options.unwrap_or_default();
+    let index = self.repo.index()
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to get index: {}", e)))?;
+
+    let mut diff = self.repo.diff_index_to_workdir(Some(&index), Some(&mut git_diff_options(&options)))
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to diff working tree: {}", e)))?;
+    diff_files(&mut diff, &options)
+  }
+
+  /// Staged changes: HEAD compared to the index.
+  #[napi]
+  pub fn diff_head_to_index(&self, options: Option<DiffOptions>) -> Result<Vec<DiffFile>> {
+    let options = options.unwrap_or_default();
+    let head_tree = self.head_tree()?;
+    let index = self.repo.index()
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to get index: {}", e)))?;
+
+    let mut diff = self.repo.diff_tree_to_index(head_tree.as_ref(), Some(&index), Some(&mut git_diff_options(&options)))
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to diff index: {}", e)))?;
+    diff_files(&mut diff, &options)
+  }
+
+  /// Staged and unstaged changes together: HEAD compared to the working tree.
+  #[napi]
+  pub fn diff_head_to_workdir(&self, options: Option<DiffOptions>) -> Result<Vec<DiffFile>> {
+    let options = options.unwrap_or_default();
+    let head_tree = self.head_tree()?;
+
+    let mut diff = self.repo.diff_tree_to_workdir_with_index(head_tree.as_ref(), Some(&mut git_diff_options(&options)))
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to diff working tree: {}", e)))?;
+    diff_files(&mut diff, &options)
+  }
+
+  /// Changes between two revisions, e.g. `HEAD~3` and `main`.
+  #[napi]
+  pub fn diff_commits(&self, old_revision: String, new_revision: String, options: Option<DiffOptions>) -> Result<Vec<DiffFile>> {
+    let options = options.unwrap_or_default();
+    let old_tree = self.revision_tree(&old_revision)?;
+    let new_tree = self.revision_tree(&new_revision)?;
+
+    let mut diff = self.repo.diff_tree_to_tree(Some(&old_tree), Some(&new_tree), Some(&mut git_diff_options(&options)))
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to diff trees: {}", e)))?;
+    diff_files(&mut diff, &options)
+  }
+
+  /// Changed line ranges of unsaved buffer `text` compared to the HEAD
+  /// version of `path`, or to the staged version with `againstIndex`.
+  ///
+  /// The text is run through the clean filters first, as it would be when
+  /// staged. A file missing from the base shows as entirely added.
+  #[napi]
+  pub fn get_line_changes(&self, path: String, text: String, against_index: Option<bool>) -> Result<Vec<LineChange>> {
+    let base = if against_index.unwrap_or(false) {
+      self.index_blob_id(&path)?
+    } else {
+      self.head_blob_id(&path)?
+    };
+
+    let Some(base) = base else {
+      let lines = text.split_inclusive('\n').count() as u32;
+      return Ok(if lines == 0 {
+        Vec::new()
+      } else {
+        vec![LineChange { kind: "added".to_string(), start_line: 0, end_line: lines }]
+      });
+    };
+
+    let blob = self.repo.find_blob(base)
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to find blob: {}", e)))?;
+    let text = self.clean_buffer(&path, text.as_bytes())?;
+    let mut options = GitDiffOptions::new();
+    options.context_lines(0);
+    let patch = Patch::from_blob_and_buffer(&blob, Some(Path::new(&path)), &text, Some(Path::new(&path)), Some(&mut options))
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to diff buffer: {}", e)))?;
+
+    let mut changes = Vec::with_capacity(patch.num_hunks());
+    for hunk_idx in 0..patch.num_hunks() {
+      let (hunk, _) = patch.hunk(hunk_idx)
+        .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to read hunk: {}", e)))?;
+
+      // Without context, a pure deletion's new_start is the line before the gap
+      let change = if hunk.new_lines() == 0 {
+        LineChange { kind: "deleted".to_string(), start_line: hunk.new_start(), end_line: hunk.new_start() }
+      } else {
+        let start_line = hunk.new_start() - 1;
+        let kind = if hunk.old_lines() == 0 { "added" } else { "modified" };
+        LineChange { kind: kind.to_string(), start_line, end_line: start_line + hunk.new_lines() }
+      };
+      changes.push(change);
+    }
+    Ok(changes)
+  }
+}
+
+impl GitRepo {
+  /// HEAD's tree, or `None` before the first commit.
+  pub(crate) fn head_tree(&self) -> Result<Option<git2::Tree<'_>>> {
+    match self.repo.head() {
+      Ok(head) => head.peel_to_tree()
+        .map(Some)
+        .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to get HEAD tree: {}", e))),
+      Err(e) if e.code() == git2::ErrorCode::UnbornBranch || e.code() == git2::ErrorCode::NotFound => Ok(None),
+      Err(e) => Err(Error::new(Status::GenericFailure, format!("Failed to get HEAD: {}", e))),
+    }
+  }
+
+  /// `text` as it would be staged at `path`, after the clean filters
+  /// (autocrlf, `filter.*.clean`) ran, so it compares with committed blobs.
+  fn clean_buffer(&self, path: &str, text: &[u8]) -> Result<Vec<u8>> {
+    let mut writer = self.repo.blob_writer(Some(Path::new(path)))
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to create blob: {}", e)))?;
+    writer.write_all(text)
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to write blob: {}", e)))?;
+    let blob_id = writer.commit()
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to filter {}: {}", path, e)))?;
+    let blob = self.repo.find_blob(blob_id)
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to find blob: {}", e)))?;
+    Ok(blob.content().to_vec())
+  }
+
+  fn head_blob_id(&self, path: &str) -> Result<Option<Oid>> {
+    let Some(tree) = self.head_tree()? else {
+      return Ok(None);
+    };
+    Ok(tree.get_path(Path::new(path)).ok().map(|entry| entry.id()))
+  }
+
+  fn index_blob_id(&self, path: &str) -> Result<Option<Oid>> {
+    let index = self.repo.index()
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to get index: {}", e)))?;
+    Ok(index.get_path(Path::new(path), 0).map(|entry| entry.id))
+  }
+
+  pub(crate) fn revision_tree(&self, revision: &str) -> Result<git2::Tree<'_>> {
+    self.repo.revparse_single(revision)
+      .and_then(|object| object.peel_to_tree())
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to resolve {}: {}", revision, e)))
+  }
+}
+
+pub(crate) fn git_diff_options(options: &DiffOptions) -> GitDiffOptions {
+  let mut diff_options = GitDiffOptions::new();
+  diff_options.context_lines(options.context_lines.unwrap_or(3));
+  diff_options.ignore_whitespace(options.ignore_whitespace.unwrap_or(false));
+
+  if options.include_untracked.unwrap_or(true) {
+    diff_options
+      .include_untracked(true)
+      .recurse_untracked_dirs(true)
+      .show_untracked_content(true);
+  }
+
+  for path in options.paths.iter().flatten() {
+    diff_options.pathspec(path);
+  }
+
+  diff_options
+}
+
+/// Convert a git2 diff into files, hunks and lines, pairing renames first when asked.
+pub(crate) fn diff_files(diff: &mut Diff, options: &DiffOptions) -> Result<Vec<DiffFile>> {
+  if options.detect_renames.unwrap_or(true) {
+    let mut find_options = DiffFindOptions::new();
+    find_options.renames(true).copies(true).for_untracked(true);
+    diff.find_similar(Some(&mut find_options))
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to detect renames: {}", e)))?;
+  }
+
+  let mut files = Vec::with_capacity(diff.deltas().len());
+  for idx in 0..diff.deltas().len() {
+    let patch = Patch::from_diff(diff, idx)
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to load diff: {}", e)))?;
+    let delta = diff.get_delta(idx).expect("delta index is in range");
+
+    let mut file = DiffFile {
+      old_path: (delta.status() != Delta::Added && delta.status() != Delta::Untracked)
+        .then(|| delta.old_file().path().map(|p| p.to_string_lossy().to_string()))
+        .flatten(),
+      new_path: (delta.status() != Delta::Deleted)
+        .then(|| delta.new_file().path().map(|p| p.to_string_lossy().to_string()))
+        .flatten(),
+      status: delta_status(delta.status()).to_string(),
+      is_binary: delta.flags().is_binary(),
+      is_renamed: delta.status() == Delta::Renamed,
+      additions: 0,
+      deletions: 0,
+      hunks: Vec::new(),
+    };
+
+    if let Some(patch) = patch {
+      file.is_binary = patch.delta().flags().is_binary();
+      file.hunks = patch_hunks(&patch)?;
+      let (_, additions, deletions) = patch.line_stats()
+        .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to count lines: {}", e)))?;
+      file.additions = additions as u32;
+      file.deletions = deletions as u32;
+    }
+
+    files.push(file);
+  }
+
+  Ok(files)
+}
+
+pub(crate) fn patch_hunks(patch: &Patch) -> Result<Vec<DiffHunk>> {
+  let mut hunks = Vec::with_capacity(patch.num_hunks());
+  for hunk_idx in 0..patch.num_hunks() {
+    let (hunk, line_count) = patch.hunk(hunk_idx)
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to read hunk: {}", e)))?;
+
+    let mut lines = Vec::with_capacity(line_count);
+    for line_idx in 0..line_count {
+      let line = patch.line_in_hunk(hunk_idx, line_idx)
+        .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to read diff line: {}", e)))?;
+
+      // The "no newline at end of file" markers are not lines of the file
+      if !matches!(line.origin(), '+' | '-' | ' ') {
+        continue;
+      }
+
+      let content = String::from_utf8_lossy(line.content());
+      let content = content.strip_suffix('\n').unwrap_or(&content);
+      lines.push(DiffLine {
+        origin: line.origin().to_string(),
+        old_line_number: line.old_lineno(),
+        new_line_number: line.new_lineno(),
+        content: content.strip_suffix('\r').unwrap_or(content).to_string(),
+      });
+    }
+
+    hunks.push(DiffHunk {
+      header: String::from_utf8_lossy(hunk.header()).trim_end().to_string(),
+      old_start: hunk.old_start(),
+      old_lines: hunk.old_lines(),
+      new_start: hunk.new_start(),
+      new_lines: hunk.new_lines(),
+      lines,
+    });
+  }
+  Ok(hunks)
+}
+
+fn delta_status(status: Delta) -> &'static str {
+  match status {
+    Delta::Added => "added",
+    Delta::Deleted => "deleted",
+    Delta::Modified => "modified",
+    Delta::Renamed => "renamed",
+    Delta::Copied => "copied",
+    Delta::Typechange => "typechange",
+    Delta::Untracked => "untracked",
+    Delta::Conflicted => "conflicted",
+    Delta::Ignored => "ignored",
+    Delta::Unreadable => "unreadable",
+    Delta::Unmodified => "unmodified",
+  }
+}
@@ -0,0 +1,458 @@
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+
+use git2::{Commit, Delta, DiffFindOptions, DiffOptions as GitDiffOptions, Oid, Patch, Repository, Sort, Status as GitStatus, StatusOptions, TreeWalkMode, TreeWalkResult};
+use napi::threadsafe_function::{ThreadsafeFunction, ThreadsafeFunctionCallMode};
+use napi::{Error, Result, Status};
+
+use crate::{CommitInfo, FileMetadata, GitRepo};
+
+/// Commits are streamed to `getFileHistoryAsync` callbacks in pages of this size.
+const HISTORY_PAGE_SIZE: usize = 50;
+
+#[napi(object)]
+#[derive(Debug, Clone, Default)]
+pub struct FileHistoryOptions {
+  /// Keep following the file through renames and copies; defaults to false
+  pub follow_renames: Option<bool>,
+  /// Matching commits to skip, for pagination
+  pub skip: Option<u32>,
+  /// Stop after this many matching commits
+  pub limit: Option<u32>,
+}
+
+/// A page of file history from `getFileHistoryAsync`.
+#[napi(object, object_from_js = false)]
+pub struct FileHistoryUpdate {
+  pub commits: Vec<CommitInfo>,
+  /// Set on the last update, which carries no commits
+  pub done: bool,
+  pub cancelled: bool,
+}
+
+/// Last-commit metadata of committed files per directory, valid for one HEAD.
+#[derive(Debug, Default)]
+pub(crate) struct MetadataCache {
+  head: Option<Oid>,
+  directories: HashMap<String, Vec<FileMetadata>>,
+}
+
+/// A running history walk; pages keep arriving on the callback until `done`.
+#[napi]
+pub struct FileHistoryHandle {
+  cancelled: Arc<AtomicBool>,
+  done: Arc<AtomicBool>,
+}
+
+#[napi]
+impl FileHistoryHandle {
+  /// Stop the walk; a final update with `cancelled` set follows.
+  #[napi]
+  pub fn cancel(&self) {
+    self.cancelled.store(true, Ordering::SeqCst);
+  }
+
+  #[napi]
+  pub fn is_done(&self) -> bool {
+    self.done.load(Ordering::SeqCst)
+  }
+}
+
+#[napi]
+impl GitRepo {
+  /// Commits reachable from HEAD that changed `filePath`, newest first.
+  #[napi]
+  pub fn get_file_history(&self, file_path: String, options: Option<FileHistoryOptions>) -> Result<Vec<CommitInfo>> {
+    let mut commits = Vec::new();
+    walk_file_history(&self.repo, &file_path, &options.unwrap_or_default(), &AtomicBool::new(false), |commit| {
+      commits.push(commit)
+    })?;
+    Ok(commits)
+  }
+
+  /// Like `getFileHistory`, but walks on a background thread and streams commits
+  /// to `callback` in pages, so long histories neither block nor have to finish.
+  #[napi]
+  pub fn get_file_history_async(
+    &self,
+    file_path: String,
+    options: Option<FileHistoryOptions>,
+    callback: ThreadsafeFunction<FileHistoryUpdate>,
+  ) -> Result<FileHistoryHandle> {
+    let options = options.unwrap_or_default();
+    let git_dir = self.repo.path().to_path_buf();
+    let handle = FileHistoryHandle {
+      cancelled: Arc::new(AtomicBool::new(false)),
+      done: Arc::new(AtomicBool::new(false)),
+    };
+
+    let cancelled = Arc::clone(&handle.cancelled);
+    let done = Arc::clone(&handle.done);
+    thread::spawn(move || {
+      // Repositories cannot cross threads, so the walk opens its own
+      let result = Repository::open(&git_dir)
+        .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to open repository: {}", e)))
+        .and_then(|repo| {
+          let mut page = Vec::with_capacity(HISTORY_PAGE_SIZE);
+          walk_file_history(&repo, &file_path, &options, &cancelled, |commit| {
+            page.push(commit);
+            if page.len() == HISTORY_PAGE_SIZE {
+              send_page(&callback, std::mem::take(&mut page));
+            }
+          })?;
+          if !page.is_empty() && !cancelled.load(Ordering::SeqCst) {
+            send_page(&callback, page);
+          }
+          Ok(())
+        });
+
+      done.store(true, Ordering::SeqCst);
+      let update = result.map(|_| FileHistoryUpdate {
+        commits: Vec::new(),
+        done: true,
+        cancelled: cancelled.load(Ordering::SeqCst),
+      });
+      callback.call(update, ThreadsafeFunctionCallMode::Blocking);
+    });
+
+    Ok(handle)
+  }
+
+  /// Last commit that changed `filePath`.
+  #[napi]
+  pub fn get_file_metadata(&self, file_path: String) -> Result<FileMetadata> {
+    let options = FileHistoryOptions { limit: Some(1), ..Default::default() };
+    let mut last = None;
+    walk_file_history(&self.repo, &file_path, &options, &AtomicBool::new(false), |commit| last = Some(commit))?;
+
+    let commit = last
+      .ok_or_else(|| Error::new(Status::GenericFailure, format!("Could not find commits for file: {}", file_path)))?;
+    Ok(FileMetadata {
+      path: file_path,
+      last_commit_hash: commit.commit_hash,
+      last_commit_message: commit.commit_message,
+      last_author_name: commit.author_name,
+      last_author_email: commit.author_email,
+      last_commit_time: commit.commit_time,
+      added_lines: commit.added_lines,
+      deleted_lines: commit.deleted_lines,
+    })
+  }
+
+  /// Every file under `directoryPath` with the last commit that changed it,
+  /// sorted by path. Untracked files are included without commit details.
+  ///
+  /// History is walked once for the whole directory and the result is cached
+  /// until HEAD moves.
+  #[napi]
+  pub fn list_files_with_metadata(&self, directory_path: Option<String>) -> Result<Vec<FileMetadata>> {
+    let base_path = directory_path.unwrap_or_else(|| ".".to_string());
+    let mut result = self.committed_metadata(&base_path)?;
+
+    let mut status_options = StatusOptions::new();
+    status_options.include_untracked(true).recurse_untracked_dirs(true).include_ignored(false);
+    let statuses = self.repo.statuses(Some(&mut status_options))
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to get status: {}", e)))?;
+
+    let committed: HashSet<String> = result.iter().map(|metadata| metadata.path.clone()).collect();
+    for entry in statuses.iter() {
+      let Some(path) = entry.path() else {
+        continue;
+      };
+      let is_new = entry.status().intersects(GitStatus::WT_NEW | GitStatus::INDEX_NEW);
+      if is_new && in_directory(path, &base_path) && !committed.contains(path) {
+        result.push(FileMetadata {
+          path: path.to_string(),
+          last_commit_hash: "".to_string(),
+          last_commit_message: "Untracked file".to_string(),
+          last_author_name: "".to_string(),
+          last_author_email: "".to_string(),
+          last_commit_time: 0,
+          added_lines: 0,
+          deleted_lines: 0,
+        });
+      }
+    }
+
+    result.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(result)
+  }
+}
+
+impl GitRepo {
+  /// Metadata of the files under `base_path` in HEAD, from the cache when HEAD has not moved.
+  fn committed_metadata(&self, base_path: &str) -> Result<Vec<FileMetadata>> {
+    let Ok(head) = self.repo.head().and_then(|head| head.peel_to_commit()) else {
+      return Ok(Vec::new());
+    };
+
+    let mut cache = self.metadata_cache.borrow_mut();
+    if cache.head != Some(head.id()) {
+      cache.head = Some(head.id());
+      cache.directories.clear();
+    }
+    if let Some(metadata) = cache.directories.get(base_path) {
+      return Ok(metadata.clone());
+    }
+
+    let metadata = last_commits(&self.repo, &head, base_path)?;
+    cache.directories.insert(base_path.to_string(), metadata.clone());
+    Ok(metadata)
+  }
+}
+
+/// Find the last commit to change each file under `base_path` in one history walk.
+///
+/// Commits are visited newest first, and every file still waiting on a commit
+/// is either settled by it or handed to one parent. Like `git log`, a file
+/// follows a parent it is unchanged from, so a merge that kept one side's
+/// version never blames the other side; a merge only counts for a file when
+/// it differs from all of the merge's parents.
+fn last_commits(repo: &Repository, head: &Commit, base_path: &str) -> Result<Vec<FileMetadata>> {
+  let head_tree = head.tree()
+    .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to get commit tree: {}", e)))?;
+  let mut pending = HashSet::new();
+  head_tree.walk(TreeWalkMode::PreOrder, |dir, entry| {
+    if entry.kind() == Some(git2::ObjectType::Blob) {
+      let path = format!("{}{}", dir, entry.name().unwrap_or(""));
+      if in_directory(&path, base_path) {
+        pending.insert(path);
+      }
+    }
+    TreeWalkResult::Ok
+  }).map_err(|e| Error::new(Status::GenericFailure, format!("Failed to walk tree: {}", e)))?;
+
+  let mut revwalk = repo.revwalk()
+    .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to create revwalk: {}", e)))?;
+  revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::TIME)
+    .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to sort revwalk: {}", e)))?;
+  revwalk.push(head.id())
+    .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to push HEAD to revwalk: {}", e)))?;
+
+  let mut result = Vec::with_capacity(pending.len());
+  // Files still looking for their last change, by the commit they wait on
+  let mut waiting: HashMap<Oid, Vec<String>> = HashMap::new();
+  if !pending.is_empty() {
+    waiting.insert(head.id(), pending.into_iter().collect());
+  }
+  for oid_result in revwalk {
+    if waiting.is_empty() {
+      break;
+    }
+
+    let oid = oid_result
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to get commit oid: {}", e)))?;
+    let Some(paths) = waiting.remove(&oid) else {
+      continue;
+    };
+    let commit = repo.find_commit(oid)
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to find commit: {}", e)))?;
+    let tree = commit.tree()
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to get commit tree: {}", e)))?;
+
+    let mut diff_options = GitDiffOptions::new();
+    if base_path != "." {
+      diff_options.pathspec(base_path);
+    }
+
+    // Diff against every parent; the first parent's diff also supplies line counts
+    let mut diffs = Vec::with_capacity(commit.parent_count().max(1));
+    if commit.parent_count() == 0 {
+      diffs.push(repo.diff_tree_to_tree(None, Some(&tree), Some(&mut diff_options)));
+    }
+    for parent in commit.parents() {
+      let parent_tree = parent.tree()
+        .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to get parent tree: {}", e)))?;
+      diffs.push(repo.diff_tree_to_tree(Some(&parent_tree), Some(&tree), Some(&mut diff_options)));
+    }
+    let diffs = diffs.into_iter().collect::<std::result::Result<Vec<_>, _>>()
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to diff trees: {}", e)))?;
+    let changed: Vec<HashMap<String, usize>> = diffs.iter()
+      .map(|diff| {
+        diff.deltas().enumerate()
+          .filter_map(|(idx, delta)| Some((delta.new_file().path()?.to_string_lossy().to_string(), idx)))
+          .collect()
+      })
+      .collect();
+
+    let author = commit.author();
+    for path in paths {
+      // Follow the first parent this commit left the file alone in
+      if let Some(parent) = commit.parent_ids().zip(&changed).find(|(_, paths)| !paths.contains_key(&path)) {
+        waiting.entry(parent.0).or_default().push(path);
+        continue;
+      }
+
+      let (added_lines, deleted_lines) = match changed[0].get(&path).map(|&idx| Patch::from_diff(&diffs[0], idx)) {
+        Some(Ok(Some(patch))) => {
+          patch.line_stats().map_or((0, 0), |(_, added, deleted)| (added as i32, deleted as i32))
+        }
+        _ => (0, 0),
+      };
+      result.push(FileMetadata {
+        path,
+        last_commit_hash: commit.id().to_string(),
+        last_commit_message: commit.message().unwrap_or("").to_string(),
+        last_author_name: author.name().unwrap_or("").to_string(),
+        last_author_email: author.email().unwrap_or("").to_string(),
+        last_commit_time: commit.time().seconds(),
+        added_lines,
+        deleted_lines,
+      });
+    }
+  }
+
+  Ok(result)
+}
+
+fn in_directory(path: &str, base_path: &str) -> bool {
+  base_path == "." || Path::new(path).starts_with(base_path)
+}
+
+fn send_page(callback: &ThreadsafeFunction<FileHistoryUpdate>, commits: Vec<CommitInfo>) {
+  callback.call(
+    Ok(FileHistoryUpdate { commits, done: false, cancelled: false }),
+    ThreadsafeFunctionCallMode::NonBlocking,
+  );
+}
+
+/// Walk back from HEAD and hand every commit that changed `path` to `emit`.
+///
+/// A commit only counts when the file's blob differs from every parent, which
+/// is a tree lookup rather than a diff; the file is only diffed for commits
+/// that changed it. Like `git log`, the walk only goes on through a parent the
+/// file is unchanged from, so the side a merge discarded is left out.
+pub(crate) fn walk_file_history(
+  repo: &Repository,
+  path: &str,
+  options: &FileHistoryOptions,
+  cancelled: &AtomicBool,
+  mut emit: impl FnMut(CommitInfo),
+) -> Result<()> {
+  let mut revwalk = repo.revwalk()
+    .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to create revwalk: {}", e)))?;
+  revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::TIME)
+    .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to sort revwalk: {}", e)))?;
+  revwalk.push_head()
+    .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to push HEAD to revwalk: {}", e)))?;
+  let head = repo.head().ok().and_then(|head| head.target());
+
+  let follow_renames = options.follow_renames.unwrap_or(false);
+  let mut skip = options.skip.unwrap_or(0);
+  let mut remaining = options.limit.map_or(usize::MAX, |limit| limit as usize);
+  // Commits the simplified history still goes through, with the file's path there
+  let mut wanted: HashMap<Oid, String> = head.into_iter().map(|head| (head, path.to_string())).collect();
+
+  for oid_result in revwalk {
+    if remaining == 0 || wanted.is_empty() || cancelled.load(Ordering::SeqCst) {
+      break;
+    }
+
+    let oid = oid_result
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to get commit oid: {}", e)))?;
+    let Some(current_path) = wanted.remove(&oid) else {
+      continue;
+    };
+    let commit = repo.find_commit(oid)
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to find commit: {}", e)))?;
+
+    let blob = blob_at(&commit, &current_path);
+    let parent_blobs: Vec<Option<Oid>> = commit.parents().map(|parent| blob_at(&parent, &current_path)).collect();
+    if let Some((parent, _)) = commit.parent_ids().zip(&parent_blobs).find(|(_, parent_blob)| **parent_blob == blob) {
+      wanted.insert(parent, current_path);
+      continue;
+    }
+    if parent_blobs.is_empty() && blob.is_none() {
+      continue;
+    }
+
+    // The file appeared here; with rename following, find where it came from
+    let renamed_from = if follow_renames && blob.is_some() && parent_blobs.first().is_some_and(|b| b.is_none()) {
+      rename_source(repo, &commit, &current_path)?
+    } else {
+      None
+    };
+
+    if skip > 0 {
+      skip -= 1;
+    } else {
+      let (added_lines, deleted_lines) = line_stats(repo, &commit, &current_path, renamed_from.as_deref())?;
+      let author = commit.author();
+      emit(CommitInfo {
+        commit_hash: commit.id().to_string(),
+        commit_message: commit.message().unwrap_or("").to_string(),
+        author_name: author.name().unwrap_or("").to_string(),
+        author_email: author.email().unwrap_or("").to_string(),
+        commit_time: commit.time().seconds(),
+        added_lines,
+        deleted_lines,
+        file_path: Some(current_path.clone()),
+      });
+      remaining -= 1;
+    }
+
+    let parent_path = renamed_from.unwrap_or(current_path);
+    for parent in commit.parent_ids() {
+      wanted.insert(parent, parent_path.clone());
+    }
+  }
+
+  Ok(())
+}
+
+fn blob_at(commit: &Commit, path: &str) -> Option<Oid> {
+  commit.tree().ok()?.get_path(Path::new(path)).ok().map(|entry| entry.id())
+}
+
+/// Path that `path` was renamed or copied from in `commit`, if any.
+fn rename_source(repo: &Repository, commit: &Commit, path: &str) -> Result<Option<String>> {
+  let parent_tree = commit.parent(0).and_then(|parent| parent.tree())
+    .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to get parent tree: {}", e)))?;
+  let tree = commit.tree()
+    .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to get commit tree: {}", e)))?;
+
+  let mut diff = repo.diff_tree_to_tree(Some(&parent_tree), Some(&tree), None)
+    .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to diff trees: {}", e)))?;
+  let mut find_options = DiffFindOptions::new();
+  find_options.renames(true).copies(true);
+  diff.find_similar(Some(&mut find_options))
+    .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to detect renames: {}", e)))?;
+
+  Ok(diff.deltas()
+    .find(|delta| {
+      matches!(delta.status(), Delta::Renamed | Delta::Copied) && delta.new_file().path() == Some(Path::new(path))
+    })
+    .and_then(|delta| delta.old_file().path().map(|p| p.to_string_lossy().to_string())))
+}
+
+/// Lines added and deleted in `path` by `commit`, compared to its first parent.
+fn line_stats(repo: &Repository, commit: &Commit, path: &str, old_path: Option<&str>) -> Result<(i32, i32)> {
+  let tree = commit.tree()
+    .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to get commit tree: {}", e)))?;
+  let parent_tree = match commit.parent(0) {
+    Ok(parent) => Some(parent.tree()
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to get parent tree: {}", e)))?),
+    Err(_) => None,
+  };
+
+  let mut diff_options = GitDiffOptions::new();
+  diff_options.disable_pathspec_match(true).pathspec(path);
+  if let Some(old_path) = old_path {
+    diff_options.pathspec(old_path);
+  }
+  let mut diff = repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), Some(&mut diff_options))
+    .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to diff trees: {}", e)))?;
+  if old_path.is_some() {
+    let mut find_options = DiffFindOptions::new();
+    find_options.renames(true).copies(true);
+    diff.find_similar(Some(&mut find_options))
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to detect renames: {}", e)))?;
+  }
+
+  let stats = diff.stats()
+    .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to count lines: {}", e)))?;
+  Ok((stats.insertions() as i32, stats.deletions() as i32))
+}
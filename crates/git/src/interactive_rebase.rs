@@ -0,0 +1,500 @@
+use std::fs;
+use std::path::PathBuf;
+
+use git2::build::CheckoutBuilder;
+use git2::{Commit, ObjectType, Oid, RepositoryState, Sort, StatusOptions};
+use napi::{Error, Result, Status};
+
+use crate::checkout::CheckoutStrategy;
+use crate::sequencer::{RepositoryStateInfo, SequencerResult};
+use crate::GitRepo;
+
+/// Directory git keeps rebase state in; the `interactive` file in it marks an interactive rebase
+const STATE_DIR: &str = "rebase-merge";
+/// File in the state directory marking a rebase started by `rebaseInteractive`,
+/// whose todo list holds only the actions it knows, unlike one from `git rebase -i`
+const PLAN_MARKER: &str = "rebase-plan";
+
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct RebaseStep {
+  /// `pick`, `reword`, `edit`, `squash`, `fixup` or `drop`
+  pub action: String,
+  pub commit: String,
+  /// New message for `reword`, and for `squash` or `fixup` instead of the
+  /// combined one. Planned steps carry the current message, which is ignored
+  /// when left unchanged
+  pub message: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Action {
+  Pick,
+  Reword,
+  Edit,
+  Squash,
+  Fixup,
+  Drop,
+}
+
+impl Action {
+  fn parse(action: &str) -> Option<Action> {
+    match action {
+      "pick" | "p" => Some(Action::Pick),
+      "reword" | "r" => Some(Action::Reword),
+      "edit" | "e" => Some(Action::Edit),
+      "squash" | "s" => Some(Action::Squash),
+      "fixup" | "f" => Some(Action::Fixup),
+      "drop" | "d" => Some(Action::Drop),
+      _ => None,
+    }
+  }
+
+  fn name(self) -> &'static str {
+    match self {
+      Action::Pick => "pick",
+      Action::Reword => "reword",
+      Action::Edit => "edit",
+      Action::Squash => "squash",
+      Action::Fixup => "fixup",
+      Action::Drop => "drop",
+    }
+  }
+}
+
+/// A line of the todo list, numbered by its position in the whole plan
+struct TodoLine {
+  action: Action,
+  commit: Oid,
+}
+
+#[napi]
+impl GitRepo {
+  /// The commits of the current branch that are not on `upstream`, oldest
+  /// first, each as a `pick` step to be rearranged for `rebaseInteractive`.
+  /// Merge commits are left out, as `git rebase -i` does.
+  #[napi]
+  pub fn get_rebase_plan(&self, upstream: String) -> Result<Vec<RebaseStep>> {
+    let upstream_id = self.resolve_commit_id(&upstream)?;
+    let mut revwalk = self.repo.revwalk()
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to create revwalk: {}", e)))?;
+    revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::REVERSE)
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to set sorting: {}", e)))?;
+    revwalk.push_head()
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to push HEAD to revwalk: {}", e)))?;
+    revwalk.hide(upstream_id)
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to hide {}: {}", upstream, e)))?;
+
+    let mut steps = Vec::new();
+    for id in revwalk {
+      let id = id.map_err(|e| Error::new(Status::GenericFailure, format!("Failed to walk history: {}", e)))?;
+      let commit = self.repo.find_commit(id)
+        .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to find commit: {}", e)))?;
+      if commit.parent_count() > 1 {
+        continue;
+      }
+      steps.push(RebaseStep {
+        action: "pick".to_string(),
+        commit: id.to_string(),
+        message: commit.message().map(str::to_string),
+      });
+    }
+    Ok(steps)
+  }
+
+  /// Rebuild the current branch on top of `onto` by carrying out `steps` in order.
+  ///
+  /// The rebase pauses with the status `conflicts` when a step conflicts and
+  /// `edit` after an `edit` step is committed, to amend it by staging changes.
+  /// Carry on with `rebaseContinue`, or use `rebaseSkip` or `rebaseAbort`.
+  #[napi]
+  pub fn rebase_interactive(&self, onto: String, steps: Vec<RebaseStep>) -> Result<SequencerResult> {
+    self.refresh_index()?;
+    if self.repo.state() != RepositoryState::Clean {
+      return Err(Error::new(Status::GenericFailure, "Another operation is in progress".to_string()));
+    }
+    let changes = self.local_changes()?;
+    if !changes.is_empty() {
+      return Err(Error::new(
+        Status::GenericFailure,
+        format!("Cannot rebase with local changes: {}", changes.join(", ")),
+      ));
+    }
+
+    let mut todo = Vec::new();
+    let mut messages = Vec::new();
+    for step in &steps {
+      let action = Action::parse(&step.action)
+        .ok_or_else(|| Error::new(Status::InvalidArg, format!("Unknown rebase action: {}", step.action)))?;
+      if action == Action::Reword && step.message.is_none() {
+        return Err(Error::new(Status::InvalidArg, format!("reword of {} needs a message", step.commit)));
+      }
+      let kept = todo.iter().any(|line: &TodoLine| line.action != Action::Drop);
+      if matches!(action, Action::Squash | Action::Fixup) && !kept {
+        return Err(Error::new(Status::InvalidArg, format!("Cannot {} without a previous commit", action.name())));
+      }
+      let commit = self.repo.find_commit(self.resolve_commit_id(&step.commit)?)
+        .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to find commit: {}", e)))?;
+      // A message left as the plan gave it doesn't replace the combined one of a squash or fixup
+      let message = step.message.clone().filter(|message| Some(message.as_str()) != commit.message());
+      todo.push(TodoLine { action, commit: commit.id() });
+      messages.push(message);
+    }
+
+    let onto_commit = self.repo.find_commit(self.resolve_commit_id(&onto)?)
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to find commit: {}", e)))?;
+    let head = self.repo.head()
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to get HEAD: {}", e)))?;
+    let head_name = if head.is_branch() { head.name().unwrap_or("").to_string() } else { "detached HEAD".to_string() };
+    let orig_head = head.peel_to_commit()
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to get head commit: {}", e)))?
+      .id();
+
+    let dir = self.rebase_state_dir();
+    fs::create_dir_all(&dir)
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to create rebase state: {}", e)))?;
+    self.write_state("interactive", "")?;
+    self.write_state(PLAN_MARKER, "")?;
+    self.write_state("head-name", &head_name)?;
+    self.write_state("orig-head", &orig_head.to_string())?;
+    self.write_state("onto", &onto_commit.id().to_string())?;
+    self.write_state("msgnum", "0")?;
+    self.write_state("end", &todo.len().to_string())?;
+    self.write_state("done", "")?;
+    for (number, message) in messages.iter().enumerate() {
+      if let Some(message) = message {
+        self.write_state(&format!("message-{}", number + 1), message)?;
+      }
+    }
+    self.write_todo(&todo)?;
+
+    let checkout = self.checkout_commit_with(&onto_commit, None, CheckoutStrategy::Safe, |_, _, _| {})?;
+    if !checkout.success {
+      let _ = fs::remove_dir_all(&dir);
+      return Err(Error::new(
+        Status::GenericFailure,
+        format!("Rebase would overwrite local changes: {}", checkout.conflicts.join(", ")),
+      ));
+    }
+    self.run_plan()
+  }
+}
+
+impl GitRepo {
+  pub(crate) fn interactive_rebase_in_progress(&self) -> bool {
+    self.rebase_state_dir().join(PLAN_MARKER).exists()
+  }
+
+  /// Refuse to touch an interactive rebase started by `git rebase -i`, whose
+  /// todo list may hold commands like `exec` that only git can carry out.
+  pub(crate) fn check_foreign_rebase(&self) -> Result<()> {
+    let dir = self.rebase_state_dir();
+    if dir.join("interactive").exists() && !dir.join(PLAN_MARKER).exists() {
+      return Err(Error::new(
+        Status::GenericFailure,
+        "The rebase was started by `git rebase -i`; continue or abort it with git".to_string(),
+      ));
+    }
+    Ok(())
+  }
+
+  /// Fill in the step being carried out or paused at, counted from one,
+  /// with its action and commit.
+  pub(crate) fn interactive_rebase_state(&self, info: &mut RepositoryStateInfo) -> Result<()> {
+    info.rebase_step = self.read_state("msgnum")?.trim().parse().ok();
+    info.rebase_total = self.read_state("end")?.trim().parse().ok();
+    let head_name = self.read_state("head-name")?.trim().to_string();
+    info.rebase_branch = head_name.starts_with("refs/").then_some(head_name);
+    if let Some(line) = parse_todo(&self.read_state("done")?)?.pop() {
+      info.rebase_action = Some(line.action.name().to_string());
+      info.rebase_commit = Some(line.commit.to_string());
+    }
+    Ok(())
+  }
+
+  /// Commit the resolved step, or amend the paused `edit` step with staged
+  /// changes, then carry on with the plan.
+  pub(crate) fn continue_plan(&self) -> Result<SequencerResult> {
+    self.refresh_index()?;
+    let conflicts = self.conflicted_paths()?;
+    if !conflicts.is_empty() {
+      return Err(Error::new(
+        Status::GenericFailure,
+        format!("Resolve conflicts before continuing: {}", conflicts.join(", ")),
+      ));
+    }
+
+    let amend = self.rebase_state_dir().join("amend");
+    if amend.exists() {
+      let head = self.head_commit()?;
+      let tree = self.index_tree()?;
+      if tree.id() != head.tree_id() {
+        head.amend(Some("HEAD"), None, Some(&self.committer()?), None, None, Some(&tree))
+          .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to amend commit: {}", e)))?;
+      }
+      fs::remove_file(&amend)
+        .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to update rebase state: {}", e)))?;
+    } else {
+      let number = self.read_state("msgnum")?.trim().parse().unwrap_or(0);
+      if let Some(line) = parse_todo(&self.read_state("done")?)?.pop() {
+        let commit = self.repo.find_commit(line.commit)
+          .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to find commit: {}", e)))?;
+        self.commit_step(line.action, number, &commit)?;
+        if line.action == Action::Edit {
+          return self.pause_for_edit();
+        }
+      }
+    }
+    self.run_plan()
+  }
+
+  /// Throw away the changes of the paused step and carry on with the plan.
+  pub(crate) fn skip_plan_step(&self) -> Result<SequencerResult> {
+    self.reset_to(&self.head_commit()?)?;
+    let amend = self.rebase_state_dir().join("amend");
+    if amend.exists() {
+      fs::remove_file(&amend)
+        .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to update rebase state: {}", e)))?;
+    }
+    self.run_plan()
+  }
+
+  /// Put the branch and working tree back as they were before the rebase.
+  pub(crate) fn abort_plan(&self) -> Result<()> {
+    let orig_head = Oid::from_str(self.read_state("orig-head")?.trim())
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Invalid rebase state: {}", e)))?;
+    let head_name = self.read_state("head-name")?.trim().to_string();
+    let commit = self.repo.find_commit(orig_head)
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to find commit: {}", e)))?;
+
+    self.reset_to(&commit)?;
+    if head_name.starts_with("refs/") {
+      self.repo.set_head(&head_name)
+    } else {
+      self.repo.set_head_detached(orig_head)
+    }
+    .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to set HEAD: {}", e)))?;
+    fs::remove_dir_all(self.rebase_state_dir())
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to remove rebase state: {}", e)))
+  }
+
+  /// Carry out the remaining todo lines until the plan is done or pauses.
+  fn run_plan(&self) -> Result<SequencerResult> {
+    loop {
+      let mut lines = parse_todo(&self.read_state("git-rebase-todo")?)?.into_iter();
+      let Some(line) = lines.next() else {
+        return self.finish_plan();
+      };
+      let remaining: Vec<TodoLine> = lines.collect();
+
+      let number = self.read_state("msgnum")?.trim().parse::<u32>().unwrap_or(0) + 1;
+      let mut done = self.read_state("done")?;
+      done.push_str(&todo_line(&line, &self.commit_summary(line.commit)));
+      self.write_state("done", &done)?;
+      self.write_state("msgnum", &number.to_string())?;
+      self.write_todo(&remaining)?;
+
+      if line.action == Action::Drop {
+        continue;
+      }
+      let commit = self.repo.find_commit(line.commit)
+        .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to find commit: {}", e)))?;
+      let conflicts = self.apply_commit(&commit)?;
+      if !conflicts.is_empty() {
+        return self.plan_result("conflicts", conflicts);
+      }
+      self.commit_step(line.action, number, &commit)?;
+      if line.action == Action::Edit {
+        return self.pause_for_edit();
+      }
+    }
+  }
+
+  /// Put the changes of `commit` on top of HEAD in the index and working
+  /// tree, returning the paths left with conflicts.
+  fn apply_commit(&self, commit: &Commit) -> Result<Vec<String>> {
+    let head = self.head_commit()?;
+    let mut merged = self.repo.cherrypick_commit(commit, &head, 0, None)
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to apply {}: {}", commit.id(), e)))?;
+
+    if !merged.has_conflicts() {
+      let tree = merged.write_tree_to(&self.repo)
+        .and_then(|tree_id| self.repo.find_tree(tree_id))
+        .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to write tree: {}", e)))?;
+      let mut checkout = CheckoutBuilder::new();
+      checkout.safe();
+      self.repo.checkout_tree(tree.as_object(), Some(&mut checkout))
+        .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to checkout tree: {}", e)))?;
+      return Ok(Vec::new());
+    }
+
+    // Write the conflict markers, then make the merged index with its
+    // conflicts the repository's index so they can be resolved
+    let mut checkout = CheckoutBuilder::new();
+    checkout.safe().allow_conflicts(true).conflict_style_diff3(true);
+    self.repo.checkout_index(Some(&mut merged), Some(&mut checkout))
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to checkout conflicts: {}", e)))?;
+    let mut index = self.repo.index()
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to get index: {}", e)))?;
+    index.clear()
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to update index: {}", e)))?;
+    for entry in merged.iter() {
+      index.add(&entry)
+        .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to update index: {}", e)))?;
+    }
+    index.write()
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to write index: {}", e)))?;
+    self.conflicted_paths()
+  }
+
+  /// Commit the applied changes of step `number` as its action asks.
+  fn commit_step(&self, action: Action, number: u32, commit: &Commit) -> Result<()> {
+    let head = self.head_commit()?;
+    let tree = self.index_tree()?;
+    let message = self.read_state(&format!("message-{}", number)).ok();
+    let committer = self.committer()?;
+
+    match action {
+      Action::Squash | Action::Fixup => {
+        let message = message.unwrap_or_else(|| match action {
+          Action::Squash => format!("{}\n\n{}", head.message().unwrap_or("").trim_end(), commit.message().unwrap_or("")),
+          _ => head.message().unwrap_or("").to_string(),
+        });
+        head.amend(Some("HEAD"), None, Some(&committer), None, Some(&message), Some(&tree))
+          .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to amend commit: {}", e)))?;
+      }
+      // The changes are already on HEAD
+      _ if tree.id() == head.tree_id() => {}
+      _ => {
+        let message = match action {
+          Action::Reword => message.unwrap_or_else(|| commit.message().unwrap_or("").to_string()),
+          _ => commit.message().unwrap_or("").to_string(),
+        };
+        self.repo.commit(Some("HEAD"), &commit.author(), &committer, &message, &tree, &[&head])
+          .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to commit: {}", e)))?;
+      }
+    }
+    Ok(())
+  }
+
+  fn pause_for_edit(&self) -> Result<SequencerResult> {
+    self.write_state("amend", &self.head_commit()?.id().to_string())?;
+    self.plan_result("edit", Vec::new())
+  }
+
+  /// Point the rebased branch at the new commits and leave the rebase.
+  fn finish_plan(&self) -> Result<SequencerResult> {
+    let head = self.head_commit()?;
+    let head_name = self.read_state("head-name")?.trim().to_string();
+    if head_name.starts_with("refs/") {
+      self.repo.reference(&head_name, head.id(), true, "rebase (finish)")
+        .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to update {}: {}", head_name, e)))?;
+      self.repo.set_head(&head_name)
+        .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to set HEAD: {}", e)))?;
+    }
+    fs::remove_dir_all(self.rebase_state_dir())
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to remove rebase state: {}", e)))?;
+    self.plan_result("completed", Vec::new())
+  }
+
+  fn plan_result(&self, status: &str, conflicts: Vec<String>) -> Result<SequencerResult> {
+    Ok(SequencerResult { status: status.to_string(), head: self.head_commit()?.id().to_string(), conflicts })
+  }
+
+  /// Make the index and working tree match `commit`. Unlike a hard reset
+  /// this leaves the rebase state alone.
+  fn reset_to(&self, commit: &Commit) -> Result<()> {
+    let tree = commit.tree()
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to get tree: {}", e)))?;
+    // Check out first, while files of the stopped step are still in the index and get removed
+    let mut checkout = CheckoutBuilder::new();
+    checkout.force();
+    self.repo.checkout_tree(tree.as_object(), Some(&mut checkout))
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to discard changes: {}", e)))?;
+    let mut index = self.repo.index()
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to get index: {}", e)))?;
+    index.read_tree(&tree)
+      .and_then(|_| index.write())
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to reset index: {}", e)))
+  }
+
+  fn local_changes(&self) -> Result<Vec<String>> {
+    let mut options = StatusOptions::new();
+    options.include_untracked(false).include_ignored(false);
+    let statuses = self.repo.statuses(Some(&mut options))
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to get status: {}", e)))?;
+    Ok(statuses.iter().filter_map(|entry| entry.path().map(str::to_string)).collect())
+  }
+
+  fn resolve_commit_id(&self, revision: &str) -> Result<Oid> {
+    self.repo.revparse_single(revision)
+      .and_then(|object| object.peel(ObjectType::Commit))
+      .map(|commit| commit.id())
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to resolve {}: {}", revision, e)))
+  }
+
+  fn head_commit(&self) -> Result<Commit<'_>> {
+    self.repo.head()
+      .and_then(|head| head.peel_to_commit())
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to get head commit: {}", e)))
+  }
+
+  fn index_tree(&self) -> Result<git2::Tree<'_>> {
+    self.repo.index()
+      .and_then(|mut index| index.write_tree())
+      .and_then(|tree_id| self.repo.find_tree(tree_id))
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to write tree: {}", e)))
+  }
+
+  fn committer(&self) -> Result<git2::Signature<'static>> {
+    self.repo.signature()
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to get signature: {}", e)))
+  }
+
+  fn commit_summary(&self, id: Oid) -> String {
+    self.repo.find_commit(id).ok().and_then(|commit| commit.summary().map(str::to_string)).unwrap_or_default()
+  }
+
+  fn rebase_state_dir(&self) -> PathBuf {
+    self.repo.path().join(STATE_DIR)
+  }
+
+  fn read_state(&self, name: &str) -> Result<String> {
+    fs::read_to_string(self.rebase_state_dir().join(name))
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to read rebase state {}: {}", name, e)))
+  }
+
+  fn write_state(&self, name: &str, content: &str) -> Result<()> {
+    fs::write(self.rebase_state_dir().join(name), content)
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to write rebase state {}: {}", name, e)))
+  }
+
+  /// The todo list in the format of `git rebase -i`, so git itself can pick the rebase up too.
+  fn write_todo(&self, lines: &[TodoLine]) -> Result<()> {
+    let todo: String = lines.iter().map(|line| todo_line(line, &self.commit_summary(line.commit))).collect();
+    self.write_state("git-rebase-todo", &todo)
+  }
+}
+
+fn todo_line(line: &TodoLine, summary: &str) -> String {
+  format!("{} {} {}\n", line.action.name(), line.commit, summary)
+}
+
+/// The lines of a todo list, skipping blank lines and comments. Lines that
+/// aren't one of the actions this rebase writes are an error rather than
+/// being skipped, since skipping them would drop commits from the plan.
+fn parse_todo(todo: &str) -> Result<Vec<TodoLine>> {
+  todo.lines()
+    .map(str::trim)
+    .filter(|line| !line.is_empty() && !line.starts_with('#'))
+    .map(|line| parse_todo_line(line)
+      .ok_or_else(|| Error::new(Status::GenericFailure, format!("Cannot parse rebase todo line: {}", line))))
+    .collect()
+}
+
+fn parse_todo_line(line: &str) -> Option<TodoLine> {
+  let mut parts = line.split_whitespace();
+  let action = Action::parse(parts.next()?)?;
+  let commit = Oid::from_str(parts.next()?).ok()?;
+  Some(TodoLine { action, commit })
+}
@@ -4,14 +4,32 @@
 extern crate napi_derive;
 extern crate git2;
 
-use git2::{Repository, Oid, Signature, BranchType, StatusOptions, Status as GitStatus};
+use git2::{Repository, Oid, Signature, BranchType, StatusOptions, Status as GitStatus, IndexEntry, IndexTime, Patch, FileMode};
+use git2::build::{CheckoutBuilder, TreeUpdateBuilder};
 use napi::{Error, Result, Status};
+use std::cell::RefCell;
 use std::path::Path;
-use std::collections::HashMap;
+
+pub mod blame;
+pub mod branch;
+pub mod checkout;
+pub mod commit;
+pub mod diff;
+pub mod history;
+pub mod interactive_rebase;
+pub mod log;
+pub mod merge;
+pub mod remote;
+pub mod sequencer;
+pub mod stash;
+mod worker;
+
+use history::MetadataCache;
 
 #[napi]
 pub struct GitRepo {
   repo: Repository,
+  metadata_cache: RefCell<MetadataCache>,
 }
 
 #[napi]
@@ -26,7 +44,7 @@ pub struct FileStatus {
 }
 
 #[napi]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct FileMetadata {
   pub path: String,
   pub last_commit_hash: String,
@@ -48,6 +66,8 @@ pub struct CommitInfo {
   pub commit_time: i64,
   pub added_lines: i32,
   pub deleted_lines: i32,
+  /// Path of the file in this commit, set by file history
+  pub file_path: Option<String>,
 }
 
 #[napi]
@@ -61,12 +81,23 @@ pub struct TagInfo {
   pub tag_time: i64,
 }
 
+/// Picks the changes of one file to stage, unstage or discard: either the hunk
+/// whose header matches, or every changed line within `startLine..=endLine`
+/// (1-based, counted on the new side of the diff).
+#[napi(object)]
+#[derive(Debug, Clone, Default)]
+pub struct HunkSelector {
+  pub header: Option<String>,
+  pub start_line: Option<u32>,
+  pub end_line: Option<u32>,
+}
+
 #[napi]
 impl GitRepo {
   #[napi(constructor)]
   pub fn new(path: String) -> Result<Self> {
     match Repository::open(path) {
-      Ok(repo) => Ok(GitRepo { repo }),
+      Ok(repo) => Ok(GitRepo { repo, metadata_cache: Default::default() }),
       Err(e) => Err(Error::new(Status::GenericFailure, format!("Failed to open repository: {}", e))),
     }
   }
@@ -139,16 +170,10 @@ impl GitRepo {
     let branch = self.repo.find_branch(&name, BranchType::Local)
       .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to find branch {}: {}", name, e)))?;
     
-    let obj = branch.get().peel(git2::ObjectType::Commit)
+    let commit = branch.get().peel_to_commit()
       .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to peel to commit: {}", e)))?;
-    
-    self.repo.checkout_tree(&obj, None)
-      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to checkout tree: {}", e)))?;
-    
-    self.repo.set_head(&format!("refs/heads/{}", name))
-      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to set HEAD: {}", e)))?;
-    
-    Ok(())
+
+    self.checkout_or_fail(&commit, Some(&format!("refs/heads/{}", name)))
   }
 
   #[napi]
@@ -177,37 +202,6 @@ impl GitRepo {
     Ok(result)
   }
 
-  #[napi]
-  pub fn commit(&self, message: String, author_name: String, author_email: String) -> Result<String> {
-    let signature = Signature::now(&author_name, &author_email)
-      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to create signature: {}", e)))?;
-    
-    let head = self.repo.head()
-      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to get HEAD: {}", e)))?;
-    
-    let head_commit = head.peel_to_commit()
-      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to get head commit: {}", e)))?;
-    
-    let tree_id = self.repo.index()
-      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to get index: {}", e)))?
-      .write_tree()
-      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to write tree: {}", e)))?;
-    
-    let tree = self.repo.find_tree(tree_id)
-      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to find tree: {}", e)))?;
-    
-    let commit_id = self.repo.commit(
-      Some("HEAD"),
-      &signature,
-      &signature,
-      &message,
-      &tree,
-      &[&head_commit]
-    ).map_err(|e| Error::new(Status::GenericFailure, format!("Failed to commit: {}", e)))?;
-    
-    Ok(commit_id.to_string())
-  }
-
   #[napi]
   pub fn add_all(&self) -> Result<()> {
     let mut index = self.repo.index()
@@ -223,233 +217,84 @@ impl GitRepo {
   }
 
   #[napi]
-  pub fn push(&self, remote_name: String, branch_name: String) -> Result<()> {
-    let mut remote = self.repo.find_remote(&remote_name)
-      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to find remote {}: {}", remote_name, e)))?;
-    
-    // For a real implementation, we would need to handle authentication
-    // This is a simplified version that assumes SSH keys are set up
-    remote.push(&[&format!("refs/heads/{0}:refs/heads/{0}", branch_name)], None)
-      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to push: {}", e)))?;
-    
+  pub fn stage_paths(&self, paths: Vec<String>) -> Result<()> {
+    let mut index = self.repo.index()
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to get index: {}", e)))?;
+    let paths: Vec<String> = paths.iter().map(|path| literal_pathspec(path)).collect();
+
+    // add_all picks up new and modified files, update_all records deletions
+    index.add_all(paths.iter(), git2::IndexAddOption::DEFAULT, None)
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to add files: {}", e)))?;
+    index.update_all(paths.iter(), None)
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to update files: {}", e)))?;
+
+    index.write()
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to write index: {}", e)))?;
+
     Ok(())
   }
 
   #[napi]
-  pub fn get_file_metadata(&self, file_path: String) -> Result<FileMetadata> {
-    // Create a revwalk to iterate through the repository's commits
-    let mut revwalk = self.repo.revwalk()
-      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to create revwalk: {}", e)))?;
-
-    // Configure revwalk to start from HEAD and go backwards
-    revwalk.push_head()
-      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to push HEAD to revwalk: {}", e)))?;
-
-    // Iterate through commits to find the last one that modified the file
-    for oid_result in revwalk {
-      let oid = oid_result
-        .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to get commit oid: {}", e)))?;
-      
-      let commit = self.repo.find_commit(oid)
-        .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to find commit: {}", e)))?;
-      
-      if let Ok(diff) = self.get_commit_diff(&commit) {
-        // Check if this commit touches our file
-        let mut file_changed = false;
-        let mut added_lines = 0;
-        let mut deleted_lines = 0;
-        
-        diff.foreach(
-          &mut |_delta, _progress| true,
-          None,
-          None,
-          Some(&mut |delta, _hunk, line| {
-            let old_path = delta.old_file().path().map(|p| p.to_string_lossy().to_string()).unwrap_or_default();
-            let new_path = delta.new_file().path().map(|p| p.to_string_lossy().to_string()).unwrap_or_default();
-            
-            if old_path == file_path || new_path == file_path {
-              file_changed = true;
-              match line.origin() {
-                '+' => added_lines += 1,
-                '-' => deleted_lines += 1,
-                _ => {}
-              }
-            }
-            true
-          }),
-        ).unwrap_or(());
-        
-        if file_changed {
-          // This commit changed the file, return metadata
-          let author = commit.author();
-          let time = commit.time();
-          let timestamp = time.seconds();
-          
-          return Ok(FileMetadata {
-            path: file_path.clone(),
-            last_commit_hash: commit.id().to_string(),
-            last_commit_message: commit.message().unwrap_or("").to_string(),
-            last_author_name: author.name().unwrap_or("").to_string(),
-            last_author_email: author.email().unwrap_or("").to_string(),
-            last_commit_time: timestamp,
-            added_lines,
-            deleted_lines,
-          });
-        }
+  pub fn unstage_paths(&self, paths: Vec<String>) -> Result<()> {
+    let paths: Vec<String> = paths.iter().map(|path| literal_pathspec(path)).collect();
+    match self.repo.head().and_then(|head| head.peel_to_commit()) {
+      Ok(head_commit) => {
+        self.repo.reset_default(Some(head_commit.as_object()), paths.iter())
+          .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to unstage files: {}", e)))?;
+      },
+      Err(_) => {
+        // Nothing is committed yet, so unstaging means dropping the entries
+        let mut index = self.repo.index()
+          .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to get index: {}", e)))?;
+        index.remove_all(paths.iter(), None)
+          .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to unstage files: {}", e)))?;
+        index.write()
+          .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to write index: {}", e)))?;
       }
     }
-    
-    // If we got here, no commit was found that modified the file
-    Err(Error::new(Status::GenericFailure, format!("Could not find commits for file: {}", file_path)))
+
+    Ok(())
   }
 
+  /// Stage part of the working-tree changes of `path`, like `git add -p`.
   #[napi]
-  pub fn list_files_with_metadata(&self, directory_path: Option<String>) -> Result<Vec<FileMetadata>> {
-    let base_path = directory_path.unwrap_or_else(|| ".".to_string());
-    let mut result = Vec::new();
-    
-    // Get all files from the repository, включая неотслеживаемые
-    let mut status_options = StatusOptions::new();
-    status_options.include_untracked(true); // Включаем неотслеживаемые файлы
-    status_options.recurse_untracked_dirs(true);
-    status_options.include_ignored(false);
-    
-    let statuses = self.repo.statuses(Some(&mut status_options))
-      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to get status: {}", e)))?;
-    
-    // Также получаем список всех файлов в индексе
-    let mut all_files = HashMap::new();
-    
-    // Добавляем файлы из статуса
-    for entry in statuses.iter() {
-      if let Some(path_str) = entry.path() {
-        // Check if file is in requested directory
-        let full_path = Path::new(path_str);
-        let in_directory = full_path.starts_with(&base_path) || base_path == ".";
-        
-        if in_directory {
-          all_files.insert(path_str.to_string(), true);
-        }
-      }
-    }
-    
-    // Добавляем файлы из индекса (которые уже коммичены)
-    if let Ok(head) = self.repo.head() {
-      if let Ok(head_commit) = head.peel_to_commit() {
-        if let Ok(tree) = head_commit.tree() {
-          tree.walk(git2::TreeWalkMode::PreOrder, |dir, entry| {
-            if let Some(name) = entry.name() {
-              let path = if dir.is_empty() { name.to_string() } else { format!("{}{}", dir, name) };
-              let full_path = Path::new(&path);
-              let in_directory = full_path.starts_with(&base_path) || base_path == ".";
-              
-              if in_directory && entry.kind() == Some(git2::ObjectType::Blob) {
-                all_files.insert(path, true);
-              }
-            }
-            0
-          }).ok();
-        }
-      }
+  pub fn stage_hunk(&self, path: String, selector: HunkSelector) -> Result<()> {
+    let staged = self.index_blob(&path)?;
+    let workdir = self.workdir_file(&path)?;
+    let updated = select_changes(&path, staged.as_deref().unwrap_or_default(), workdir.as_deref().unwrap_or_default(), &selector, true)?;
+
+    if workdir.is_none() && updated.is_empty() {
+      return self.remove_index_entry(&path);
     }
-    
-    // Получаем метаданные для каждого файла
-    for path in all_files.keys() {
-      // Если это .git директория, пропускаем
-      if path.starts_with(".git/") {
-        continue;
-      }
-      
-      // Получаем метаданные на основе истории
-      if let Ok(metadata) = self.get_file_metadata(path.clone()) {
-        result.push(metadata);
-      } else {
-        // Для новых файлов, которые еще не коммичены
-        if let Some(file_stat) = statuses.iter().find(|entry| entry.path() == Some(path)) {
-          if file_stat.status().contains(GitStatus::WT_NEW) || file_stat.status().contains(GitStatus::INDEX_NEW) {
-            result.push(FileMetadata {
-              path: path.clone(),
-              last_commit_hash: "".to_string(),
-              last_commit_message: "Untracked file".to_string(),
-              last_author_name: "".to_string(),
-              last_author_email: "".to_string(),
-              last_commit_time: 0,
-              added_lines: 0,
-              deleted_lines: 0,
-            });
-          }
-        }
-      }
+    self.write_index_entry(&path, &updated, self.workdir_mode(&path))
+  }
+
+  /// Move part of the staged changes of `path` back out of the index, like `git reset -p`.
+  #[napi]
+  pub fn unstage_hunk(&self, path: String, selector: HunkSelector) -> Result<()> {
+    let committed = self.head_blob(&path)?;
+    let staged = self.index_blob(&path)?;
+    let updated = select_changes(&path, committed.as_ref().map(|(content, _)| content.as_slice()).unwrap_or_default(), staged.as_deref().unwrap_or_default(), &selector, false)?;
+
+    match committed {
+      None if updated.is_empty() => self.remove_index_entry(&path),
+      Some((_, mode)) => self.write_index_entry(&path, &updated, mode),
+      None => self.write_index_entry(&path, &updated, self.index_mode(&path)),
     }
-    
-    Ok(result)
   }
 
+  /// Throw away part of the unstaged changes of `path`, like `git checkout -p`.
   #[napi]
-  pub fn get_file_history(&self, file_path: String) -> Result<Vec<CommitInfo>> {
-    let mut result = Vec::new();
-    
-    // Create a revwalk to iterate through the repository's commits
-    let mut revwalk = self.repo.revwalk()
-      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to create revwalk: {}", e)))?;
-
-    // Configure revwalk to start from HEAD and go backwards
-    revwalk.push_head()
-      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to push HEAD to revwalk: {}", e)))?;
-
-    // Iterate through commits
-    for oid_result in revwalk {
-      let oid = oid_result
-        .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to get commit oid: {}", e)))?;
-      
-      let commit = self.repo.find_commit(oid)
-        .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to find commit: {}", e)))?;
-      
-      if let Ok(diff) = self.get_commit_diff(&commit) {
-        // Check if this commit touches our file
-        let mut file_changed = false;
-        let mut added_lines = 0;
-        let mut deleted_lines = 0;
-        
-        diff.foreach(
-          &mut |_delta, _progress| true,
-          None,
-          None,
-          Some(&mut |delta, _hunk, line| {
-            let old_path = delta.old_file().path().map(|p| p.to_string_lossy().to_string()).unwrap_or_default();
-            let new_path = delta.new_file().path().map(|p| p.to_string_lossy().to_string()).unwrap_or_default();
-            
-            if old_path == file_path || new_path == file_path {
-              file_changed = true;
-              match line.origin() {
-                '+' => added_lines += 1,
-                '-' => deleted_lines += 1,
-                _ => {}
-              }
-            }
-            true
-          }),
-        ).unwrap_or(());
-        
-        if file_changed {
-          // This commit changed the file, add to history
-          let author = commit.author();
-          let time = commit.time();
-          
-          result.push(CommitInfo {
-            commit_hash: commit.id().to_string(),
-            commit_message: commit.message().unwrap_or("").to_string(),
-            author_name: author.name().unwrap_or("").to_string(),
-            author_email: author.email().unwrap_or("").to_string(),
-            commit_time: time.seconds(),
-            added_lines,
-            deleted_lines,
-          });
-        }
-      }
+  pub fn discard_hunk(&self, path: String, selector: HunkSelector) -> Result<()> {
+    let staged = self.index_blob(&path)?;
+    let workdir = self.workdir_file(&path)?;
+    let updated = select_changes(&path, staged.as_deref().unwrap_or_default(), workdir.as_deref().unwrap_or_default(), &selector, false)?;
+
+    if staged.is_none() && updated.is_empty() {
+      return std::fs::remove_file(self.workdir_path(&path)?)
+        .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to remove {}: {}", path, e)));
     }
-    
-    Ok(result)
+    self.write_workdir_file(&path, &updated)
   }
 
   #[napi]
@@ -565,17 +410,7 @@ impl GitRepo {
     let commit = obj.peel_to_commit()
       .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to peel to commit: {}", e)))?;
     
-    let commit_id = commit.id(); // Запоминаем id до того как commit будет перемещён
-    
-    // Checkout tree
-    self.repo.checkout_tree(&commit.into_object(), None)
-      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to checkout tree: {}", e)))?;
-    
-    // Move HEAD to detached state
-    self.repo.set_head_detached(commit_id)
-      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to set HEAD: {}", e)))?;
-    
-    Ok(())
+    self.checkout_or_fail(&commit, None)
   }
 
   #[napi]
@@ -586,50 +421,253 @@ impl GitRepo {
     let commit = self.repo.find_commit(oid)
       .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to find commit: {}", e)))?;
     
-    // Checkout tree
-    self.repo.checkout_tree(&commit.into_object(), None)
-      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to checkout tree: {}", e)))?;
-    
-    // Move HEAD to detached state (используем oid, т.к. commit уже перемещён)
-    self.repo.set_head_detached(oid)
-      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to set HEAD: {}", e)))?;
-    
-    Ok(())
+    self.checkout_or_fail(&commit, None)
   }
-  
-  // Helper method to get diff for a commit
-  fn get_commit_diff(&self, commit: &git2::Commit) -> Result<git2::Diff> {
-    let commit_tree = commit.tree()
-      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to get commit tree: {}", e)))?;
-    
-    let parent_tree = if commit.parent_count() > 0 {
-      let parent = commit.parent(0)
-        .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to get parent: {}", e)))?;
-      
-      parent.tree()
-        .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to get parent tree: {}", e)))?
-    } else {
-      // For first commit with no parent, create an empty tree
-      self.repo.find_tree(Oid::from_str("4b825dc642cb6eb9a060e54bf8d69288fbee4904").unwrap())
-        .unwrap_or_else(|_| {
-          // If the empty tree doesn't exist, create it
-          let empty_tree_id = self.repo.treebuilder(None)
-            .and_then(|b| b.write())
-            .unwrap_or_else(|_| Oid::zero());
-          
-          self.repo.find_tree(empty_tree_id).unwrap()
-        })
+
+  fn workdir_path(&self, path: &str) -> Result<std::path::PathBuf> {
+    let workdir = self.repo.workdir()
+      .ok_or_else(|| Error::new(Status::GenericFailure, "Repository has no working directory".to_string()))?;
+    Ok(workdir.join(path))
+  }
+
+  /// Content of `path` in the working tree as it would be staged, i.e. after
+  /// the clean filters (autocrlf, `filter.*.clean`) ran, so it compares with the index.
+  fn workdir_file(&self, path: &str) -> Result<Option<Vec<u8>>> {
+    let full_path = self.workdir_path(path)?;
+    match std::fs::symlink_metadata(&full_path) {
+      Ok(_) => {},
+      Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+      Err(e) => return Err(Error::new(Status::GenericFailure, format!("Failed to read {}: {}", path, e))),
+    }
+    let blob_id = self.repo.blob_path(&full_path)
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to read {}: {}", path, e)))?;
+    let blob = self.repo.find_blob(blob_id)
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to find blob: {}", e)))?;
+    Ok(Some(blob.content().to_vec()))
+  }
+
+  /// Write `content` to `path` in the working tree through the smudge filters,
+  /// the way a checkout would, leaving the index alone.
+  fn write_workdir_file(&self, path: &str, content: &[u8]) -> Result<()> {
+    let mode = if self.workdir_path(path)?.exists() { self.workdir_mode(path) } else { self.index_mode(path) };
+    let blob_id = self.repo.blob(content)
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to write blob: {}", e)))?;
+    let empty_tree_id = self.repo.treebuilder(None)
+      .and_then(|builder| builder.write())
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to write tree: {}", e)))?;
+    let empty_tree = self.repo.find_tree(empty_tree_id)
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to find tree: {}", e)))?;
+    let file_mode = if mode == 0o100755 { FileMode::BlobExecutable } else { FileMode::Blob };
+    let tree_id = TreeUpdateBuilder::new()
+      .upsert(path, blob_id, file_mode)
+      .create_updated(&self.repo, &empty_tree)
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to write tree: {}", e)))?;
+    let tree = self.repo.find_object(tree_id, None)
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to find tree: {}", e)))?;
+
+    // The tree holds only this file, so the pathspec must not match anything else
+    let mut checkout = CheckoutBuilder::new();
+    checkout.force().update_index(false).path(literal_pathspec(path));
+    self.repo.checkout_tree(&tree, Some(&mut checkout))
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to write {}: {}", path, e)))
+  }
+
+  #[cfg(unix)]
+  fn workdir_mode(&self, path: &str) -> u32 {
+    use std::os::unix::fs::PermissionsExt;
+
+    let executable = self.workdir_path(path).ok()
+      .and_then(|full_path| std::fs::metadata(full_path).ok())
+      .is_some_and(|metadata| metadata.permissions().mode() & 0o111 != 0);
+    if executable { 0o100755 } else { 0o100644 }
+  }
+
+  #[cfg(not(unix))]
+  fn workdir_mode(&self, path: &str) -> u32 {
+    self.index_mode(path)
+  }
+
+  fn index_mode(&self, path: &str) -> u32 {
+    self.repo.index().ok()
+      .and_then(|index| index.get_path(Path::new(path), 0))
+      .map_or(0o100644, |entry| entry.mode)
+  }
+
+  fn index_blob(&self, path: &str) -> Result<Option<Vec<u8>>> {
+    let index = self.repo.index()
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to get index: {}", e)))?;
+    let Some(entry) = index.get_path(Path::new(path), 0) else {
+      return Ok(None);
     };
-    
-    self.repo.diff_tree_to_tree(Some(&parent_tree), Some(&commit_tree), None)
-      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to diff trees: {}", e)))
+    let blob = self.repo.find_blob(entry.id)
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to find blob: {}", e)))?;
+    Ok(Some(blob.content().to_vec()))
+  }
+
+  /// Content and file mode of `path` in HEAD, or `None` when it is not committed.
+  fn head_blob(&self, path: &str) -> Result<Option<(Vec<u8>, u32)>> {
+    let Ok(tree) = self.repo.head().and_then(|head| head.peel_to_tree()) else {
+      return Ok(None);
+    };
+    let Ok(entry) = tree.get_path(Path::new(path)) else {
+      return Ok(None);
+    };
+    let blob = self.repo.find_blob(entry.id())
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to find blob: {}", e)))?;
+    Ok(Some((blob.content().to_vec(), entry.filemode() as u32)))
+  }
+
+  fn write_index_entry(&self, path: &str, content: &[u8], mode: u32) -> Result<()> {
+    let mut index = self.repo.index()
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to get index: {}", e)))?;
+
+    // Zeroed stat data makes git re-hash the working file instead of trusting it matches
+    let entry = IndexEntry {
+      ctime: IndexTime::new(0, 0),
+      mtime: IndexTime::new(0, 0),
+      dev: 0,
+      ino: 0,
+      mode,
+      uid: 0,
+      gid: 0,
+      file_size: 0,
+      id: Oid::zero(),
+      flags: 0,
+      flags_extended: 0,
+      path: path.as_bytes().to_vec(),
+    };
+    index.add_frombuffer(&entry, content)
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to update index: {}", e)))?;
+
+    // libgit2 records the blob size, which differs from the file's once filters
+    // such as autocrlf apply; size zero makes git compare the contents instead
+    if let Some(mut written) = index.get_path(Path::new(path), 0) {
+      written.file_size = 0;
+      index.add(&written)
+        .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to update index: {}", e)))?;
+    }
+
+    index.write()
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to write index: {}", e)))
+  }
+
+  fn remove_index_entry(&self, path: &str) -> Result<()> {
+    let mut index = self.repo.index()
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to get index: {}", e)))?;
+    index.remove_path(Path::new(path))
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to update index: {}", e)))?;
+    index.write()
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to write index: {}", e)))
+  }
+}
+
+/// Rebuild a file from the diff between `old` and `new`: the selected changes
+/// are taken from `new` when `apply_selected` is set, otherwise every change
+/// except the selected ones is.
+fn select_changes(path: &str, old: &[u8], new: &[u8], selector: &HunkSelector, apply_selected: bool) -> Result<Vec<u8>> {
+  let patch = Patch::from_buffers(old, Some(Path::new(path)), new, Some(Path::new(path)), None)
+    .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to diff {}: {}", path, e)))?;
+  if patch.delta().flags().is_binary() {
+    return Err(Error::new(Status::GenericFailure, format!("Cannot select changes in binary file {}", path)));
+  }
+
+  let header = selector.header.as_deref().map(hunk_range);
+  let range = match (selector.start_line, selector.end_line) {
+    (Some(start), end) => Some((start, end.unwrap_or(start))),
+    (None, Some(end)) => Some((end, end)),
+    (None, None) => None,
+  };
+  if header.is_none() && range.is_none() {
+    return Err(Error::new(Status::InvalidArg, "Hunk selector needs a header or a line range".to_string()));
+  }
+  let new_line_count = new.split(|b| *b == b'\n').count() as u32;
+
+  let old_lines: Vec<&[u8]> = old.split_inclusive(|b| *b == b'\n').collect();
+  let mut result = Vec::with_capacity(new.len().max(old.len()));
+  let mut cursor = 0;
+  let mut matched = false;
+
+  for hunk_idx in 0..patch.num_hunks() {
+    let (hunk, line_count) = patch.hunk(hunk_idx)
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to read hunk: {}", e)))?;
+    let whole_hunk = header.is_some_and(|header| header == hunk_range(&String::from_utf8_lossy(hunk.header())));
+
+    // A hunk that removes nothing is inserted after `old_start`, otherwise it starts there
+    let hunk_start = if hunk.old_lines() == 0 { hunk.old_start() } else { hunk.old_start().saturating_sub(1) } as usize;
+    copy_lines(&mut result, &old_lines[cursor..hunk_start.min(old_lines.len())]);
+    cursor = cursor.max(hunk_start.min(old_lines.len()));
+
+    let mut last_new = if hunk.new_lines() == 0 { hunk.new_start() } else { hunk.new_start().saturating_sub(1) };
+    for line_idx in 0..line_count {
+      let line = patch.line_in_hunk(hunk_idx, line_idx)
+        .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to read diff line: {}", e)))?;
+
+      let in_range = |line_number: u32| range.is_some_and(|(start, end)| start <= line_number && line_number <= end);
+      match line.origin() {
+        ' ' => {
+          copy_lines(&mut result, &old_lines[cursor..cursor + 1]);
+          cursor += 1;
+          last_new += 1;
+        },
+        '-' => {
+          // Deletions sit just before the next new-side line
+          let selected = whole_hunk || in_range((last_new + 1).min(new_line_count.max(1)));
+          matched |= selected;
+          if selected != apply_selected {
+            copy_lines(&mut result, &old_lines[cursor..cursor + 1]);
+          }
+          cursor += 1;
+        },
+        '+' => {
+          last_new += 1;
+          let selected = whole_hunk || in_range(last_new);
+          matched |= selected;
+          if selected == apply_selected {
+            copy_lines(&mut result, &[line.content()]);
+          }
+        },
+        _ => {},
+      }
+    }
+  }
+  copy_lines(&mut result, &old_lines[cursor.min(old_lines.len())..]);
+
+  if !matched {
+    return Err(Error::new(Status::GenericFailure, format!("No matching changes in {}", path)));
+  }
+  Ok(result)
+}
+
+/// Append whole lines, restoring the newline a formerly last line did not have.
+fn copy_lines(result: &mut Vec<u8>, lines: &[&[u8]]) {
+  for line in lines {
+    if result.last().is_some_and(|b| *b != b'\n') {
+      result.push(b'\n');
+    }
+    result.extend_from_slice(line);
   }
 }
 
+/// The `@@ -a,b +c,d @@` part of a hunk header, without the trailing function context.
+fn hunk_range(header: &str) -> &str {
+  let header = header.trim();
+  match header.get(2..).and_then(|rest| rest.find("@@")) {
+    Some(end) => &header[..end + 4],
+    None => header,
+  }
+}
+
+/// `path` with its glob characters escaped, so a pathspec matches only that path.
+pub(crate) fn literal_pathspec(path: &str) -> String {
+  path.chars()
+    .flat_map(|c| if matches!(c, '*' | '?' | '[' | '\\') { vec!['\\', c] } else { vec![c] })
+    .collect()
+}
+
 #[napi]
 pub fn clone_repository(url: String, path: String) -> Result<GitRepo> {
   match Repository::clone(&url, Path::new(&path)) {
-    Ok(repo) => Ok(GitRepo { repo }),
+    Ok(repo) => Ok(GitRepo { repo, metadata_cache: Default::default() }),
     Err(e) => Err(Error::new(Status::GenericFailure, format!("Failed to clone repository: {}", e))),
   }
 }
@@ -637,7 +675,7 @@ pub fn clone_repository(url: String, path: String) -> Result<GitRepo> {
 #[napi]
 pub fn init_repository(path: String) -> Result<GitRepo> {
   match Repository::init(Path::new(&path)) {
-    Ok(repo) => Ok(GitRepo { repo }),
+    Ok(repo) => Ok(GitRepo { repo, metadata_cache: Default::default() }),
     Err(e) => Err(Error::new(Status::GenericFailure, format!("Failed to initialize repository: {}", e))),
   }
 }
@@ -653,7 +691,7 @@ pub fn find_repository(start_path: String) -> Result<GitRepo> {
     if git_dir.exists() && git_dir.is_dir() {
       // Нашли .git, открываем репозиторий
       match Repository::open(&current_path) {
-        Ok(repo) => return Ok(GitRepo { repo }),
+        Ok(repo) => return Ok(GitRepo { repo, metadata_cache: Default::default() }),
         Err(e) => return Err(Error::new(Status::GenericFailure, format!("Found .git directory but failed to open repository: {}", e))),
       }
     }
@@ -0,0 +1,366 @@
+use std::collections::BTreeSet;
+
+use git2::{Commit, DiffOptions as GitDiffOptions, Oid, RevparseMode, Revwalk, Sort};
+use napi::{Error, Result, Status};
+
+use crate::GitRepo;
+
+#[napi(object)]
+#[derive(Debug, Clone, Default)]
+pub struct LogOptions {
+  /// Revisions to start from, like `main`, `v1.0..v2.0`, `main...feature` or
+  /// `^old` to exclude; defaults to HEAD
+  pub revisions: Option<Vec<String>>,
+  /// Start from every branch, remote branch and tag as well
+  pub all: Option<bool>,
+  /// Case-insensitive substring of the author name or email
+  pub author: Option<String>,
+  /// Case-insensitive substring of the commit message
+  pub message: Option<String>,
+  /// Only commits that changed one of these paths or pathspecs
+  pub paths: Option<Vec<String>>,
+  /// Only follow the first parent of merge commits
+  pub first_parent: Option<bool>,
+  /// Matching commits to skip, for pagination
+  pub skip: Option<u32>,
+  /// Stop after this many matching commits
+  pub limit: Option<u32>,
+  /// Compute a graph row for each commit; cannot be combined with filters
+  pub graph: Option<bool>,
+}
+
+/// A line in the graph from a lane in one row to a lane in the next row.
+#[napi(object)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct GraphEdge {
+  pub from_lane: u32,
+  pub to_lane: u32,
+}
+
+/// Where a commit sits in the graph and the lines leaving its row downwards.
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct GraphRow {
+  /// Zero-based column of the commit's node
+  pub lane: u32,
+  pub edges: Vec<GraphEdge>,
+}
+
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct LogEntry {
+  pub hash: String,
+  /// Parent hashes, first parent first
+  pub parents: Vec<String>,
+  pub author_name: String,
+  pub author_email: String,
+  pub author_time: i64,
+  pub committer_name: String,
+  pub committer_email: String,
+  pub commit_time: i64,
+  /// First line of the message
+  pub summary: String,
+  pub message: String,
+  /// Set when `graph` was requested
+  pub graph: Option<GraphRow>,
+}
+
+#[napi]
+impl GitRepo {
+  /// Commits in topological order, newest first, like `git log --topo-order`.
+  #[napi]
+  pub fn log(&self, options: Option<LogOptions>) -> Result<Vec<LogEntry>> {
+    let options = options.unwrap_or_default();
+    let with_graph = options.graph.unwrap_or(false);
+    if with_graph && (options.author.is_some() || options.message.is_some() || options.paths.is_some()) {
+      return Err(Error::new(
+        Status::InvalidArg,
+        "The graph cannot be combined with author, message or path filters".to_string(),
+      ));
+    }
+
+    let revwalk = self.log_revwalk(&options)?;
+    let author = options.author.as_deref().map(str::to_lowercase);
+    let message = options.message.as_deref().map(str::to_lowercase);
+    let first_parent = options.first_parent.unwrap_or(false);
+    let mut skip = options.skip.unwrap_or(0);
+    let limit = options.limit.map_or(usize::MAX, |limit| limit as usize);
+
+    let mut graph = GraphLayout::default();
+    let mut entries: Vec<LogEntry> = Vec::new();
+    let mut lanes_after = Vec::new();
+    let mut next_page = None;
+    for oid_result in revwalk {
+      let oid = oid_result
+        .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to get commit oid: {}", e)))?;
+      let commit = self.repo.find_commit(oid)
+        .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to find commit: {}", e)))?;
+      let parents: Vec<Oid> = if first_parent {
+        commit.parent_ids().take(1).collect()
+      } else {
+        commit.parent_ids().collect()
+      };
+
+      // The layout has to see every commit from the top, including skipped ones
+      let row = with_graph.then(|| graph.place(oid, &parents));
+
+      // One commit past the page is laid out so the last row's lines bend correctly
+      if entries.len() == limit {
+        next_page = row.map(|(lane, _)| (oid, lane));
+        break;
+      }
+
+      if !matches_filters(&commit, author.as_deref(), message.as_deref()) || !self.touches_paths(&commit, &options)? {
+        continue;
+      }
+      if skip > 0 {
+        skip -= 1;
+        continue;
+      }
+
+      if let Some((lane, lanes)) = row {
+        entries.push(log_entry(&commit, &parents, Some(GraphRow { lane, edges: Vec::new() })));
+        lanes_after.push(lanes);
+      } else {
+        entries.push(log_entry(&commit, &parents, None));
+      }
+    }
+
+    if with_graph {
+      connect_rows(&mut entries, &lanes_after, next_page);
+    }
+    Ok(entries)
+  }
+}
+
+impl GitRepo {
+  fn log_revwalk(&self, options: &LogOptions) -> Result<Revwalk<'_>> {
+    let mut revwalk = self.repo.revwalk()
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to create revwalk: {}", e)))?;
+    revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::TIME)
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to sort revwalk: {}", e)))?;
+    if options.first_parent.unwrap_or(false) {
+      revwalk.simplify_first_parent()
+        .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to configure revwalk: {}", e)))?;
+    }
+
+    let revisions = options.revisions.clone().unwrap_or_default();
+    if options.all.unwrap_or(false) {
+      for glob in ["refs/heads", "refs/remotes", "refs/tags"] {
+        revwalk.push_glob(glob)
+          .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to push {}: {}", glob, e)))?;
+      }
+    } else if revisions.iter().all(|revision| revision.starts_with('^')) {
+      revwalk.push_head()
+        .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to push HEAD to revwalk: {}", e)))?;
+    }
+
+    for revision in &revisions {
+      let failed = |e: git2::Error| Error::new(Status::GenericFailure, format!("Failed to resolve {}: {}", revision, e));
+      if let Some(hidden) = revision.strip_prefix('^') {
+        let commit = self.repo.revparse_single(hidden).and_then(|object| object.peel_to_commit()).map_err(failed)?;
+        revwalk.hide(commit.id()).map_err(failed)?;
+        continue;
+      }
+
+      let spec = self.repo.revparse(revision).map_err(failed)?;
+      let from = spec.from().map(|object| object.peel_to_commit()).transpose().map_err(failed)?;
+      let to = spec.to().map(|object| object.peel_to_commit()).transpose().map_err(failed)?;
+      match (from, to) {
+        (Some(from), Some(to)) if spec.mode().contains(RevparseMode::MERGE_BASE) => {
+          let base = self.repo.merge_base(from.id(), to.id()).map_err(failed)?;
+          revwalk.push(from.id()).map_err(failed)?;
+          revwalk.push(to.id()).map_err(failed)?;
+          revwalk.hide(base).map_err(failed)?;
+        }
+        (Some(from), Some(to)) => {
+          revwalk.hide(from.id()).map_err(failed)?;
+          revwalk.push(to.id()).map_err(failed)?;
+        }
+        (Some(commit), None) | (None, Some(commit)) => revwalk.push(commit.id()).map_err(failed)?,
+        (None, None) => {}
+      }
+    }
+
+    Ok(revwalk)
+  }
+
+  /// Whether `commit` changed one of `options.paths`, compared to each of its parents.
+  fn touches_paths(&self, commit: &Commit, options: &LogOptions) -> Result<bool> {
+    let Some(paths) = &options.paths else {
+      return Ok(true);
+    };
+
+    let mut diff_options = GitDiffOptions::new();
+    for path in paths {
+      diff_options.pathspec(path);
+    }
+    let tree = commit.tree()
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to get commit tree: {}", e)))?;
+    if commit.parent_count() == 0 {
+      let diff = self.repo.diff_tree_to_tree(None, Some(&tree), Some(&mut diff_options))
+        .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to diff trees: {}", e)))?;
+      return Ok(diff.deltas().len() > 0);
+    }
+
+    let parent_count = if options.first_parent.unwrap_or(false) { 1 } else { commit.parent_count() };
+    for parent in commit.parents().take(parent_count) {
+      let parent_tree = parent.tree()
+        .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to get parent tree: {}", e)))?;
+      let diff = self.repo.diff_tree_to_tree(Some(&parent_tree), Some(&tree), Some(&mut diff_options))
+        .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to diff trees: {}", e)))?;
+      if diff.deltas().len() == 0 {
+        return Ok(false);
+      }
+    }
+    Ok(true)
+  }
+}
+
+fn matches_filters(commit: &Commit, author: Option<&str>, message: Option<&str>) -> bool {
+  if let Some(author) = author {
+    let signature = commit.author();
+    let name = signature.name().unwrap_or("").to_lowercase();
+    let email = signature.email().unwrap_or("").to_lowercase();
+    if !name.contains(author) && !email.contains(author) {
+      return false;
+    }
+  }
+  if let Some(message) = message {
+    if !commit.message().unwrap_or("").to_lowercase().contains(message) {
+      return false;
+    }
+  }
+  true
+}
+
+fn log_entry(commit: &Commit, parents: &[Oid], graph: Option<GraphRow>) -> LogEntry {
+  let author = commit.author();
+  let committer = commit.committer();
+  LogEntry {
+    hash: commit.id().to_string(),
+    parents: parents.iter().map(Oid::to_string).collect(),
+    author_name: author.name().unwrap_or("").to_string(),
+    author_email: author.email().unwrap_or("").to_string(),
+    author_time: author.when().seconds(),
+    committer_name: committer.name().unwrap_or("").to_string(),
+    committer_email: committer.email().unwrap_or("").to_string(),
+    commit_time: commit.time().seconds(),
+    summary: commit.summary().unwrap_or("").to_string(),
+    message: commit.message().unwrap_or("").to_string(),
+    graph,
+  }
+}
+
+/// Lane assignment for `git log --graph` style drawing.
+///
+/// Each lane holds the commit it is waiting for. A commit takes the leftmost
+/// lane waiting for it, or the first free one, and hands that lane to its
+/// first parent; further parents get lanes of their own unless one already
+/// waits for them. Lanes waiting for the same commit merge into its lane.
+#[derive(Debug, Default)]
+struct GraphLayout {
+  lanes: Vec<Option<Oid>>,
+}
+
+/// A lane after a row: the commit it waits for, whether a line to it leaves
+/// the row's node, and whether a line also passes straight through from above.
+#[derive(Debug, Clone, Copy)]
+struct LaneState {
+  waiting: Oid,
+  from_node: bool,
+  through: bool,
+}
+
+impl GraphLayout {
+  fn place(&mut self, oid: Oid, parents: &[Oid]) -> (u32, Vec<Option<LaneState>>) {
+    let lane = match self.lanes.iter().position(|waiting| *waiting == Some(oid)) {
+      Some(lane) => lane,
+      None => self.free_lane(),
+    };
+
+    // Other lanes waiting for this commit end at its node
+    for waiting in self.lanes.iter_mut() {
+      if *waiting == Some(oid) {
+        *waiting = None;
+      }
+    }
+
+    let mut from_node = BTreeSet::new();
+    let mut new_lanes = BTreeSet::new();
+    for (i, parent) in parents.iter().enumerate() {
+      // The first parent continues the node's lane; if another lane already
+      // waits for it, the two lines join when the parent is reached
+      let target = match self.lanes.iter().position(|waiting| *waiting == Some(*parent)) {
+        Some(existing) if i > 0 => existing,
+        _ => {
+          let target = if i == 0 { lane } else { self.free_lane() };
+          self.lanes[target] = Some(*parent);
+          new_lanes.insert(target);
+          target
+        }
+      };
+      from_node.insert(target);
+    }
+
+    let row = self.lanes.iter().enumerate()
+      .map(|(i, waiting)| {
+        waiting.map(|waiting| LaneState {
+          waiting,
+          from_node: from_node.contains(&i),
+          through: !new_lanes.contains(&i) && i != lane,
+        })
+      })
+      .collect();
+
+    while self.lanes.last() == Some(&None) {
+      self.lanes.pop();
+    }
+    (lane as u32, row)
+  }
+
+  fn free_lane(&mut self) -> usize {
+    match self.lanes.iter().position(Option::is_none) {
+      Some(lane) => lane,
+      None => {
+        self.lanes.push(None);
+        self.lanes.len() - 1
+      }
+    }
+  }
+}
+
+/// Fill in each row's edges now that the next row's node is known: a lane
+/// waiting for the next commit bends into that commit's lane.
+fn connect_rows(entries: &mut [LogEntry], lanes_after: &[Vec<Option<LaneState>>], next_page: Option<(Oid, u32)>) {
+  let mut next_nodes: Vec<Option<(Oid, u32)>> = entries.iter()
+    .skip(1)
+    .map(|next| Some((Oid::from_str(&next.hash).ok()?, next.graph.as_ref()?.lane)))
+    .collect();
+  next_nodes.push(next_page);
+
+  for ((entry, lanes), next) in entries.iter_mut().zip(lanes_after).zip(next_nodes) {
+    let Some(row) = entry.graph.as_mut() else {
+      continue;
+    };
+
+    let mut edges = BTreeSet::new();
+    for (i, state) in lanes.iter().enumerate() {
+      let Some(state) = state else {
+        continue;
+      };
+      let lane = i as u32;
+      let to_lane = match next {
+        Some((next_oid, next_lane)) if next_oid == state.waiting => next_lane,
+        _ => lane,
+      };
+      if state.from_node {
+        edges.insert(GraphEdge { from_lane: row.lane, to_lane });
+      }
+      if state.through || !state.from_node {
+        edges.insert(GraphEdge { from_lane: lane, to_lane });
+      }
+    }
+    row.edges = edges.into_iter().collect();
+  }
+}
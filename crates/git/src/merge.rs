@@ -0,0 +1,306 @@
+use git2::build::CheckoutBuilder;
+use git2::{AnnotatedCommit, Commit, ObjectType, Oid, RepositoryState};
+use napi::{Error, Result, Status};
+
+use crate::checkout::CheckoutStrategy;
+use crate::GitRepo;
+
+#[napi(object)]
+#[derive(Debug, Clone, Default)]
+pub struct MergeOptions {
+  /// Always create a merge commit, like `git merge --no-ff`
+  pub no_ff: Option<bool>,
+  /// Fail unless the merge is a fast-forward, like `git merge --ff-only`
+  pub ff_only: Option<bool>,
+  /// Message of the merge commit; defaults to `Merge branch '<name>'`
+  pub message: Option<String>,
+}
+
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct MergeResult {
+  /// `upToDate`, `fastForward`, `merged` or `conflicts`
+  pub status: String,
+  /// HEAD after the merge
+  pub head: String,
+  /// Files left with conflict markers when the status is `conflicts`
+  pub conflicts: Vec<String>,
+}
+
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct ConflictRegion {
+  /// Zero-based line of the `<<<<<<<` marker in the working file
+  pub start_line: u32,
+  /// Line of the `|||||||` marker, missing only in files merged without the diff3 style
+  pub base_line: Option<u32>,
+  /// Line of the `=======` marker
+  pub separator_line: u32,
+  /// Line of the `>>>>>>>` marker
+  pub end_line: u32,
+}
+
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct ConflictInfo {
+  pub path: String,
+  /// Content in the common ancestor; missing when both sides added the file
+  pub ancestor: Option<String>,
+  /// Content on the current branch; missing when it deleted the file
+  pub ours: Option<String>,
+  /// Content on the branch being merged; missing when it deleted the file
+  pub theirs: Option<String>,
+  /// Whether any side is binary, in which case no content is returned
+  pub is_binary: bool,
+  /// Conflict markers in the working file
+  pub regions: Vec<ConflictRegion>,
+}
+
+#[napi]
+impl GitRepo {
+  /// Merge a branch, tag or commit into HEAD.
+  ///
+  /// Conflicts are left in the working tree and index with the status
+  /// `conflicts`; resolve them with `markResolved` and `commit` to conclude.
+  #[napi]
+  pub fn merge(&self, target: String, options: Option<MergeOptions>) -> Result<MergeResult> {
+    let options = options.unwrap_or_default();
+    if options.no_ff.unwrap_or(false) && options.ff_only.unwrap_or(false) {
+      return Err(Error::new(Status::InvalidArg, "noFf and ffOnly cannot be combined".to_string()));
+    }
+
+    let (theirs, description) = match self.repo.resolve_reference_from_short_name(&target) {
+      Ok(reference) => {
+        let kind = if reference.is_branch() {
+          "branch"
+        } else if reference.is_remote() {
+          "remote-tracking branch"
+        } else if reference.is_tag() {
+          "tag"
+        } else {
+          "reference"
+        };
+        let description = format!("{} '{}'", kind, reference.shorthand().unwrap_or(&target));
+        let commit = self.repo.reference_to_annotated_commit(&reference)
+          .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to resolve {}: {}", target, e)))?;
+        (commit, description)
+      }
+      Err(_) => {
+        let commit = self.repo.revparse_single(&target)
+          .and_then(|object| object.peel_to_commit())
+          .and_then(|commit| self.repo.find_annotated_commit(commit.id()))
+          .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to resolve {}: {}", target, e)))?;
+        (commit, format!("commit '{}'", target))
+      }
+    };
+
+    self.merge_annotated(&theirs, &description, &options)
+  }
+
+  /// The three versions of a conflicted file and the conflict markers in its
+  /// working copy, for a three-way merge view.
+  #[napi]
+  pub fn get_conflict(&self, path: String) -> Result<ConflictInfo> {
+    self.refresh_index()?;
+    let index = self.repo.index()
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to get index: {}", e)))?;
+    let conflicts = index.conflicts()
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to read conflicts: {}", e)))?;
+
+    for conflict in conflicts {
+      let conflict = conflict
+        .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to read conflict: {}", e)))?;
+      let entry_path = conflict.our.as_ref().or(conflict.their.as_ref()).or(conflict.ancestor.as_ref())
+        .map(|entry| String::from_utf8_lossy(&entry.path).to_string());
+      if entry_path.as_deref() != Some(path.as_str()) {
+        continue;
+      }
+
+      let mut is_binary = false;
+      let mut sides = Vec::new();
+      for entry in [&conflict.ancestor, &conflict.our, &conflict.their] {
+        let content = match entry {
+          Some(entry) => {
+            let blob = self.repo.find_blob(entry.id)
+              .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to find blob: {}", e)))?;
+            is_binary |= blob.is_binary();
+            Some(String::from_utf8_lossy(blob.content()).to_string())
+          }
+          None => None,
+        };
+        sides.push(content);
+      }
+      if is_binary {
+        sides = vec![None, None, None];
+      }
+      let mut sides = sides.into_iter();
+
+      let working = self.workdir_file(&path)?.unwrap_or_default();
+      let regions = if is_binary { Vec::new() } else { conflict_regions(&String::from_utf8_lossy(&working)) };
+
+      return Ok(ConflictInfo {
+        path,
+        ancestor: sides.next().flatten(),
+        ours: sides.next().flatten(),
+        theirs: sides.next().flatten(),
+        is_binary,
+        regions,
+      });
+    }
+
+    Err(Error::new(Status::GenericFailure, format!("{} has no conflict", path)))
+  }
+
+  /// Stage the working copy of a conflicted file as its resolution, or the
+  /// deletion when the file was removed.
+  #[napi]
+  pub fn mark_resolved(&self, path: String) -> Result<()> {
+    self.refresh_index()?;
+    let mut index = self.repo.index()
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to get index: {}", e)))?;
+    if self.workdir_path(&path)?.exists() {
+      index.add_path(std::path::Path::new(&path))
+    } else {
+      index.remove_path(std::path::Path::new(&path))
+    }
+    .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to stage {}: {}", path, e)))?;
+
+    index.write()
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to write index: {}", e)))
+  }
+}
+
+impl GitRepo {
+  /// Merge `theirs` into HEAD; `description` names it in the commit message,
+  /// like `branch 'feature'`.
+  pub(crate) fn merge_annotated(
+    &self,
+    theirs: &AnnotatedCommit,
+    description: &str,
+    options: &MergeOptions,
+  ) -> Result<MergeResult> {
+    self.refresh_index()?;
+    let (analysis, _) = self.repo.merge_analysis(&[theirs])
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to analyze merge: {}", e)))?;
+
+    if analysis.is_up_to_date() {
+      return self.merge_result("upToDate", Vec::new());
+    }
+    if analysis.is_unborn() || (analysis.is_fast_forward() && !options.no_ff.unwrap_or(false)) {
+      self.fast_forward(theirs, description)?;
+      return self.merge_result("fastForward", Vec::new());
+    }
+    if options.ff_only.unwrap_or(false) {
+      return Err(Error::new(
+        Status::GenericFailure,
+        format!("Cannot fast-forward to {}; the branches have diverged", description),
+      ));
+    }
+
+    let mut checkout = CheckoutBuilder::new();
+    checkout.safe().allow_conflicts(true).conflict_style_diff3(true);
+    self.repo.merge(&[theirs], None, Some(&mut checkout))
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to merge {}: {}", description, e)))?;
+
+    let conflicts = self.conflicted_paths()?;
+    if !conflicts.is_empty() {
+      return self.merge_result("conflicts", conflicts);
+    }
+
+    let signature = self.repo.signature()
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to get signature: {}", e)))?;
+    let tree = self.repo.index()
+      .and_then(|mut index| index.write_tree())
+      .and_then(|tree_id| self.repo.find_tree(tree_id))
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to write tree: {}", e)))?;
+    let head = self.repo.head()
+      .and_then(|head| head.peel_to_commit())
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to get head commit: {}", e)))?;
+    let theirs = self.repo.find_commit(theirs.id())
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to find commit: {}", e)))?;
+
+    let message = options.message.clone().unwrap_or_else(|| format!("Merge {}", description));
+    self.repo.commit(Some("HEAD"), &signature, &signature, &message, &tree, &[&head, &theirs])
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to commit merge: {}", e)))?;
+    self.repo.cleanup_state()
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to clean up merge state: {}", e)))?;
+
+    self.merge_result("merged", Vec::new())
+  }
+
+  /// Move HEAD, and the branch it points at, to `target` without a merge commit.
+  fn fast_forward(&self, target: &AnnotatedCommit, description: &str) -> Result<()> {
+    let commit = self.repo.find_commit(target.id())
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to find commit: {}", e)))?;
+    let head = self.repo.find_reference("HEAD")
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to get HEAD: {}", e)))?;
+    let branch_ref = head.symbolic_target().map(str::to_string);
+
+    let checkout = self.checkout_commit_with(&commit, branch_ref.as_deref(), CheckoutStrategy::Safe, |_, _, _| {})?;
+    if !checkout.success {
+      return Err(Error::new(
+        Status::GenericFailure,
+        format!("Merge would overwrite local changes: {}", checkout.conflicts.join(", ")),
+      ));
+    }
+    if let Some(branch_ref) = &branch_ref {
+      self.repo.reference(branch_ref, commit.id(), true, &format!("merge {}: Fast-forward", description))
+        .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to update {}: {}", branch_ref, e)))?;
+    }
+    Ok(())
+  }
+
+  /// Commits being merged into HEAD while a conflicted merge is in progress.
+  pub(crate) fn merge_heads(&self) -> Result<Vec<Commit<'_>>> {
+    if self.repo.state() != RepositoryState::Merge {
+      return Ok(Vec::new());
+    }
+    let merge_head = std::fs::read_to_string(self.repo.path().join("MERGE_HEAD"))
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to read MERGE_HEAD: {}", e)))?;
+
+    merge_head.lines()
+      .map(|line| Oid::from_str(line.trim())
+        .and_then(|id| self.repo.find_commit(id))
+        .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to find merge head {}: {}", line, e))))
+      .collect()
+  }
+
+  /// Pick up changes made to the index by other processes since it was last read.
+  pub(crate) fn refresh_index(&self) -> Result<()> {
+    self.repo.index()
+      .and_then(|mut index| index.read(false))
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to read index: {}", e)))
+  }
+
+  fn merge_result(&self, status: &str, conflicts: Vec<String>) -> Result<MergeResult> {
+    let head = self.repo.head()
+      .and_then(|head| head.peel(ObjectType::Commit))
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to get head commit: {}", e)))?;
+    Ok(MergeResult { status: status.to_string(), head: head.id().to_string(), conflicts })
+  }
+}
+
+fn conflict_regions(text: &str) -> Vec<ConflictRegion> {
+  let is_marker = |line: &str, marker: &str| {
+    line.strip_prefix(marker).is_some_and(|rest| rest.is_empty() || rest.starts_with(' '))
+  };
+
+  let mut regions = Vec::new();
+  let mut current: Option<ConflictRegion> = None;
+  for (number, line) in text.lines().enumerate() {
+    let number = number as u32;
+    if is_marker(line, "<<<<<<<") {
+      current = Some(ConflictRegion { start_line: number, base_line: None, separator_line: 0, end_line: 0 });
+    } else if let Some(region) = current.as_mut() {
+      if is_marker(line, "|||||||") {
+        region.base_line = Some(number);
+      } else if is_marker(line, "=======") {
+        region.separator_line = number;
+      } else if is_marker(line, ">>>>>>>") && region.separator_line > region.start_line {
+        region.end_line = number;
+        regions.extend(current.take());
+      }
+    }
+  }
+  regions
+}
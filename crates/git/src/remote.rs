@@ -0,0 +1,476 @@
+use std::cell::{Cell, RefCell};
+use std::path::Path;
+
+use git2::{
+  AnnotatedCommit, AutotagOption, BranchType, Cred, CredentialType, ErrorCode, FetchOptions as GitFetchOptions, FetchPrune,
+  PushOptions as GitPushOptions, RebaseOptions, RemoteCallbacks,
+};
+use napi::threadsafe_function::{ThreadsafeFunction, ThreadsafeFunctionCallMode};
+use napi::{Env, Error, JsFunction, JsObject, Result, Status};
+
+use crate::merge::MergeOptions;
+use crate::worker::{async_callback, call_async, open, spawn_promise};
+use crate::GitRepo;
+
+/// How many credentials are tried for one operation before giving up
+const MAX_CREDENTIAL_ATTEMPTS: u32 = 3;
+
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct CredentialRequest {
+  pub url: String,
+  /// User name embedded in the URL, like `git` in `git@github.com:org/repo.git`
+  pub username_from_url: Option<String>,
+  /// Kinds of credentials the server accepts: `sshKey`, `userPass`, `username` or `default`
+  pub allowed_types: Vec<String>,
+  /// Starts at 1 and goes up each time the previous credentials were rejected
+  pub attempt: u32,
+}
+
+#[napi(object)]
+#[derive(Debug, Clone, Default)]
+pub struct Credentials {
+  /// Defaults to the user name from the URL
+  pub username: Option<String>,
+  /// Password or HTTPS access token
+  pub password: Option<String>,
+  /// Private key file for SSH
+  pub ssh_key_path: Option<String>,
+  /// Public key file for SSH; derived from the private key when not given
+  pub ssh_public_key_path: Option<String>,
+  /// Passphrase of the private key
+  pub passphrase: Option<String>,
+  /// Ask the running SSH agent instead of reading a key file
+  pub use_ssh_agent: Option<bool>,
+  /// Ask the credential helper configured in git, like `git credential fill`
+  pub use_credential_helper: Option<bool>,
+}
+
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct TransferProgress {
+  /// Objects received while fetching, or sent while pushing
+  pub completed_objects: u32,
+  pub total_objects: u32,
+  /// Received objects that have been indexed; equals `completedObjects` when pushing
+  pub indexed_objects: u32,
+  pub bytes: i64,
+}
+
+#[napi(object)]
+#[derive(Debug, Clone, Default)]
+pub struct FetchOptions {
+  /// Remove remote branches that no longer exist on the remote
+  pub prune: Option<bool>,
+}
+
+#[napi(object)]
+#[derive(Debug, Clone, Default)]
+pub struct PullOptions {
+  /// Remote branch to pull, like `main`; defaults to the upstream of the current branch
+  pub branch: Option<String>,
+  /// `merge` (default), `ff-only` or `rebase`
+  pub strategy: Option<String>,
+}
+
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct PullResult {
+  /// `upToDate`, `fastForward`, `merged`, `rebased` or `conflicts`
+  pub status: String,
+  /// HEAD after the pull
+  pub head: String,
+  /// Files left with conflict markers when the status is `conflicts`
+  pub conflicts: Vec<String>,
+}
+
+#[napi(object)]
+#[derive(Debug, Clone, Default)]
+pub struct PushOptions {
+  /// Overwrite the remote branch even when it is not an ancestor, like `git push --force`
+  pub force: Option<bool>,
+  /// Track the pushed branch afterwards, like `git push -u`
+  pub set_upstream: Option<bool>,
+}
+
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct PushRejection {
+  /// Remote reference that was not updated, like `refs/heads/main`
+  pub reference: String,
+  pub reason: String,
+}
+
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct PushResult {
+  pub success: bool,
+  pub rejected: Vec<PushRejection>,
+}
+
+/// Answers a `CredentialRequest` with a promise of credentials, or of null to give up
+type CredentialsCallback = ThreadsafeFunction<CredentialRequest>;
+type ProgressCallback = ThreadsafeFunction<TransferProgress>;
+
+/// JS callbacks for a transfer, which runs on a thread of its own.
+#[derive(Clone)]
+struct TransferCallbacks {
+  credentials: Option<CredentialsCallback>,
+  progress: Option<ProgressCallback>,
+}
+
+impl TransferCallbacks {
+  fn new(env: &Env, credentials: Option<JsFunction>, progress: Option<ProgressCallback>) -> Result<Self> {
+    let credentials = match credentials {
+      Some(callback) => Some(async_callback(env, callback)?),
+      None => None,
+    };
+    Ok(TransferCallbacks { credentials, progress })
+  }
+}
+
+#[napi]
+impl GitRepo {
+  /// Download objects and update the remote branches of `remote`, which
+  /// defaults to the remote of the current branch's upstream, else `origin`.
+  ///
+  /// `credentials` is called when the server asks for authentication and may
+  /// return a promise; without it the SSH agent and the configured credential
+  /// helper are tried.
+  #[napi(ts_return_type = "Promise<void>")]
+  pub fn fetch(
+    &self,
+    env: Env,
+    remote: Option<String>,
+    options: Option<FetchOptions>,
+    credentials: Option<JsFunction>,
+    progress: Option<ProgressCallback>,
+  ) -> Result<JsObject> {
+    let git_dir = self.repo.path().to_path_buf();
+    let options = options.unwrap_or_default();
+    let callbacks = TransferCallbacks::new(&env, credentials, progress)?;
+    spawn_promise(&env, move || {
+      let repo = open(&git_dir)?;
+      let remote = match remote {
+        Some(remote) => remote,
+        None => repo.default_remote()?,
+      };
+      repo.fetch_remote(&remote, &options, &callbacks)
+    })
+  }
+
+  /// Fetch and integrate the upstream of the current branch.
+  ///
+  /// Conflicts from a merge or rebase are left in the working tree to be
+  /// resolved, with the status `conflicts`.
+  #[napi(ts_return_type = "Promise<PullResult>")]
+  pub fn pull(
+    &self,
+    env: Env,
+    remote: Option<String>,
+    options: Option<PullOptions>,
+    credentials: Option<JsFunction>,
+    progress: Option<ProgressCallback>,
+  ) -> Result<JsObject> {
+    let git_dir = self.repo.path().to_path_buf();
+    let options = options.unwrap_or_default();
+    let callbacks = TransferCallbacks::new(&env, credentials, progress)?;
+    spawn_promise(&env, move || open(&git_dir)?.pull_with(remote, &options, &callbacks))
+  }
+
+  /// Push local branch `branchName` to the branch of the same name on `remoteName`.
+  ///
+  /// Rejected updates, like a non-fast-forward push, come back in `rejected`
+  /// instead of throwing.
+  #[napi(ts_return_type = "Promise<PushResult>")]
+  pub fn push(
+    &self,
+    env: Env,
+    remote_name: String,
+    branch_name: String,
+    options: Option<PushOptions>,
+    credentials: Option<JsFunction>,
+    progress: Option<ProgressCallback>,
+  ) -> Result<JsObject> {
+    let git_dir = self.repo.path().to_path_buf();
+    let options = options.unwrap_or_default();
+    let callbacks = TransferCallbacks::new(&env, credentials, progress)?;
+    spawn_promise(&env, move || open(&git_dir)?.push_with(&remote_name, &branch_name, &options, &callbacks))
+  }
+}
+
+impl GitRepo {
+  /// Remote of the current branch's upstream, else `origin`.
+  fn default_remote(&self) -> Result<String> {
+    let remote = self.repo.head().ok()
+      .and_then(|head| head.name().map(str::to_string))
+      .and_then(|name| self.repo.branch_upstream_remote(&name).ok())
+      .and_then(|remote| remote.as_str().map(str::to_string));
+    Ok(remote.unwrap_or_else(|| "origin".to_string()))
+  }
+
+  fn pull_with(&self, remote: Option<String>, options: &PullOptions, callbacks: &TransferCallbacks) -> Result<PullResult> {
+    let strategy = options.strategy.as_deref().unwrap_or("merge");
+    if !matches!(strategy, "merge" | "ff-only" | "rebase") {
+      return Err(Error::new(Status::InvalidArg, format!("Unknown pull strategy: {}", strategy)));
+    }
+
+    let head = self.repo.head()
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to get HEAD: {}", e)))?;
+    if !head.is_branch() {
+      return Err(Error::new(Status::GenericFailure, "Cannot pull with a detached HEAD".to_string()));
+    }
+    let branch_ref = head.name().unwrap_or("").to_string();
+    let branch_name = head.shorthand().unwrap_or("").to_string();
+
+    let remote = match remote {
+      Some(remote) => remote,
+      None => self.default_remote()?,
+    };
+    self.fetch_remote(&remote, &FetchOptions::default(), callbacks)?;
+
+    let upstream_ref = match &options.branch {
+      Some(branch) => format!("refs/remotes/{}/{}", remote, branch),
+      None => self.repo.branch_upstream_name(&branch_ref)
+        .ok()
+        .and_then(|name| name.as_str().map(str::to_string))
+        .unwrap_or_else(|| format!("refs/remotes/{}/{}", remote, branch_name)),
+    };
+    let upstream = self.repo.find_reference(&upstream_ref)
+      .and_then(|reference| self.repo.reference_to_annotated_commit(&reference))
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to find {}: {}", upstream_ref, e)))?;
+
+    if strategy == "rebase" {
+      self.refresh_index()?;
+      let (analysis, _) = self.repo.merge_analysis(&[&upstream])
+        .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to analyze merge: {}", e)))?;
+      if !analysis.is_up_to_date() && !analysis.is_fast_forward() {
+        return self.pull_rebase(&branch_ref, &upstream);
+      }
+    }
+
+    let name = upstream_ref.strip_prefix("refs/remotes/").unwrap_or(&upstream_ref);
+    let options = MergeOptions { ff_only: Some(strategy == "ff-only"), ..Default::default() };
+    let merged = self.merge_annotated(&upstream, &format!("remote-tracking branch '{}'", name), &options)?;
+    Ok(PullResult { status: merged.status, head: merged.head, conflicts: merged.conflicts })
+  }
+
+  fn push_with(
+    &self,
+    remote_name: &str,
+    branch_name: &str,
+    options: &PushOptions,
+    transfer: &TransferCallbacks,
+  ) -> Result<PushResult> {
+    let mut remote = self.repo.find_remote(remote_name)
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to find remote {}: {}", remote_name, e)))?;
+
+    let rejected = RefCell::new(Vec::new());
+    let mut callbacks = self.remote_callbacks(transfer.credentials.as_ref());
+    if let Some(progress) = transfer.progress.as_ref() {
+      callbacks.push_transfer_progress(|current, total, bytes| {
+        let update = TransferProgress {
+          completed_objects: current as u32,
+          total_objects: total as u32,
+          indexed_objects: current as u32,
+          bytes: bytes as i64,
+        };
+        progress.call(Ok(update), ThreadsafeFunctionCallMode::NonBlocking);
+      });
+    }
+    callbacks.push_update_reference(|reference, status| {
+      if let Some(reason) = status {
+        rejected.borrow_mut().push(PushRejection { reference: reference.to_string(), reason: reason.to_string() });
+      }
+      Ok(())
+    });
+
+    let force = if options.force.unwrap_or(false) { "+" } else { "" };
+    let refspec = format!("{0}refs/heads/{1}:refs/heads/{1}", force, branch_name);
+    let mut push_options = GitPushOptions::new();
+    push_options.remote_callbacks(callbacks);
+    if let Err(e) = remote.push(&[&refspec], Some(&mut push_options)) {
+      // The local check for non-fast-forward pushes fails before anything is sent
+      if e.code() != ErrorCode::NotFastForward {
+        return Err(Error::new(Status::GenericFailure, format!("Failed to push: {}", e)));
+      }
+      rejected.borrow_mut().push(PushRejection {
+        reference: format!("refs/heads/{}", branch_name),
+        reason: e.message().to_string(),
+      });
+    }
+    drop(push_options);
+
+    let rejected = rejected.into_inner();
+    if rejected.is_empty() && options.set_upstream.unwrap_or(false) {
+      let mut branch = self.repo.find_branch(branch_name, BranchType::Local)
+        .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to find branch {}: {}", branch_name, e)))?;
+      branch.set_upstream(Some(&format!("{}/{}", remote_name, branch_name)))
+        .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to set upstream of {}: {}", branch_name, e)))?;
+    }
+
+    Ok(PushResult { success: rejected.is_empty(), rejected })
+  }
+
+  fn fetch_remote(&self, remote_name: &str, options: &FetchOptions, transfer: &TransferCallbacks) -> Result<()> {
+    let mut remote = self.repo.find_remote(remote_name)
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to find remote {}: {}", remote_name, e)))?;
+
+    let mut callbacks = self.remote_callbacks(transfer.credentials.as_ref());
+    if let Some(progress) = transfer.progress.as_ref() {
+      callbacks.transfer_progress(|stats| {
+        let update = TransferProgress {
+          completed_objects: stats.received_objects() as u32,
+          total_objects: stats.total_objects() as u32,
+          indexed_objects: stats.indexed_objects() as u32,
+          bytes: stats.received_bytes() as i64,
+        };
+        progress.call(Ok(update), ThreadsafeFunctionCallMode::NonBlocking);
+        true
+      });
+    }
+
+    let mut fetch_options = GitFetchOptions::new();
+    fetch_options.remote_callbacks(callbacks).download_tags(AutotagOption::Auto);
+    if options.prune.unwrap_or(false) {
+      fetch_options.prune(FetchPrune::On);
+    }
+    // An empty refspec list uses the configured ones, like `git fetch origin`
+    remote.fetch::<&str>(&[], Some(&mut fetch_options), None)
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to fetch from {}: {}", remote_name, e)))
+  }
+
+  /// Credentials for the remote, from `callback` when given and otherwise
+  /// from the SSH agent or the configured credential helper.
+  ///
+  /// Only credentials actually handed to the server count as attempts, and
+  /// the SSH agent always gets a turn before authentication is given up.
+  fn remote_callbacks<'a>(&'a self, callback: Option<&'a CredentialsCallback>) -> RemoteCallbacks<'a> {
+    let attempts = Cell::new(0);
+    let agent_tried = Cell::new(false);
+    let mut callbacks = RemoteCallbacks::new();
+    callbacks.credentials(move |url, username_from_url, allowed| {
+      let give_up = |reason: &str| {
+        if allowed.contains(CredentialType::SSH_KEY) && !agent_tried.replace(true) {
+          return Cred::ssh_key_from_agent(username_from_url.unwrap_or("git"));
+        }
+        Err(git2::Error::from_str(reason))
+      };
+      // A user name alone only picks the account, so it is not an attempt
+      let counts = !allowed.contains(CredentialType::USERNAME);
+      if counts && attempts.get() >= MAX_CREDENTIAL_ATTEMPTS {
+        return give_up("Authentication failed");
+      }
+
+      let credentials = match callback {
+        Some(callback) => {
+          let request = CredentialRequest {
+            url: url.to_string(),
+            username_from_url: username_from_url.map(str::to_string),
+            allowed_types: credential_type_names(allowed),
+            attempt: attempts.get() + 1,
+          };
+          match request_credentials(callback, request)? {
+            Some(credentials) => credentials,
+            None => return give_up("Authentication cancelled"),
+          }
+        }
+        // Without a callback there is nothing new to offer on a second attempt
+        None if attempts.get() > 0 => return give_up("Authentication failed"),
+        None => Credentials {
+          use_ssh_agent: Some(allowed.contains(CredentialType::SSH_KEY)),
+          use_credential_helper: Some(true),
+          ..Default::default()
+        },
+      };
+
+      match self.credential(&credentials, url, username_from_url, allowed, &agent_tried) {
+        Ok(cred) => {
+          if counts {
+            attempts.set(attempts.get() + 1);
+          }
+          Ok(cred)
+        }
+        Err(e) => give_up(e.message()),
+      }
+    });
+    callbacks
+  }
+
+  fn credential(
+    &self,
+    credentials: &Credentials,
+    url: &str,
+    username_from_url: Option<&str>,
+    allowed: CredentialType,
+    agent_tried: &Cell<bool>,
+  ) -> std::result::Result<Cred, git2::Error> {
+    let username = credentials.username.as_deref().or(username_from_url);
+
+    if allowed.contains(CredentialType::USERNAME) {
+      return Cred::username(username.unwrap_or("git"));
+    }
+    if allowed.contains(CredentialType::SSH_KEY) {
+      if let Some(key_path) = &credentials.ssh_key_path {
+        return Cred::ssh_key(
+          username.unwrap_or("git"),
+          credentials.ssh_public_key_path.as_deref().map(Path::new),
+          Path::new(key_path),
+          credentials.passphrase.as_deref(),
+        );
+      }
+      if credentials.use_ssh_agent.unwrap_or(false) {
+        agent_tried.set(true);
+        return Cred::ssh_key_from_agent(username.unwrap_or("git"));
+      }
+    }
+    if allowed.contains(CredentialType::USER_PASS_PLAINTEXT) {
+      if let Some(password) = &credentials.password {
+        return Cred::userpass_plaintext(username.unwrap_or(""), password);
+      }
+      if credentials.use_credential_helper.unwrap_or(false) {
+        let config = self.repo.config()?;
+        return Cred::credential_helper(&config, url, username);
+      }
+    }
+    if allowed.contains(CredentialType::DEFAULT) {
+      return Cred::default();
+    }
+    Err(git2::Error::from_str(&format!("No usable credentials for {}", url)))
+  }
+
+  fn pull_rebase(&self, branch_ref: &str, upstream: &AnnotatedCommit) -> Result<PullResult> {
+    let branch = self.repo.find_reference(branch_ref)
+      .and_then(|reference| self.repo.reference_to_annotated_commit(&reference))
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to find {}: {}", branch_ref, e)))?;
+    let mut rebase = self.repo.rebase(Some(&branch), Some(upstream), None, Some(&mut RebaseOptions::new()))
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to start rebase: {}", e)))?;
+
+    let result = self.run_rebase(&mut rebase)?;
+    let status = if result.status == "completed" { "rebased" } else { result.status.as_str() };
+    Ok(PullResult { status: status.to_string(), head: result.head, conflicts: result.conflicts })
+  }
+}
+
+fn credential_type_names(allowed: CredentialType) -> Vec<String> {
+  let names = [
+    (CredentialType::SSH_KEY, "sshKey"),
+    (CredentialType::USER_PASS_PLAINTEXT, "userPass"),
+    (CredentialType::USERNAME, "username"),
+    (CredentialType::DEFAULT, "default"),
+  ];
+  names.iter()
+    .filter(|(kind, _)| allowed.contains(*kind))
+    .map(|(_, name)| name.to_string())
+    .collect()
+}
+
+/// Ask the JS credentials callback from the worker thread and wait for its
+/// answer, which may be a promise.
+fn request_credentials(
+  callback: &CredentialsCallback,
+  request: CredentialRequest,
+) -> std::result::Result<Option<Credentials>, git2::Error> {
+  call_async(callback, request)
+    .map_err(|e| git2::Error::from_str(&format!("Credentials callback failed: {}", e)))
+}
@@ -0,0 +1,409 @@
+use std::collections::HashSet;
+
+use git2::build::CheckoutBuilder;
+use git2::{
+  CherrypickOptions, Commit, ErrorCode, ObjectType, Rebase, RebaseOptions, RepositoryState, ResetType, RevertOptions,
+  Status as GitStatus, StatusOptions,
+};
+use napi::{Error, Result, Status};
+
+use crate::{literal_pathspec, GitRepo};
+
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct SequencerResult {
+  /// `completed`, `empty` when the changes were already on HEAD, `conflicts`,
+  /// or `edit` when an interactive rebase stopped to amend a commit
+  pub status: String,
+  /// HEAD after the operation
+  pub head: String,
+  /// Files left with conflict markers when the status is `conflicts`
+  pub conflicts: Vec<String>,
+}
+
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct RepositoryStateInfo {
+  /// `clean`, `merge`, `rebase`, `cherryPick`, `revert`, `bisect` or `applyMailbox`
+  pub state: String,
+  /// Files with unresolved conflicts
+  pub conflicts: Vec<String>,
+  /// One-based number of the commit being rebased
+  pub rebase_step: Option<u32>,
+  /// Number of commits in the rebase
+  pub rebase_total: Option<u32>,
+  /// Branch being rebased, like `refs/heads/feature`
+  pub rebase_branch: Option<String>,
+  /// Action of the current rebase step, like `pick` or `edit`
+  pub rebase_action: Option<String>,
+  /// Commit the current rebase step applies
+  pub rebase_commit: Option<String>,
+}
+
+#[napi]
+impl GitRepo {
+  /// Apply the changes of `commit` on top of HEAD and commit them with its
+  /// author and message. For a merge commit, `mainline` is the one-based
+  /// parent to diff against.
+  ///
+  /// Conflicts are left to be resolved and concluded with `commit`.
+  #[napi]
+  pub fn cherry_pick(&self, commit: String, mainline: Option<u32>) -> Result<SequencerResult> {
+    self.refresh_index()?;
+    let commit = self.find_revision_commit(&commit)?;
+    let mut options = CherrypickOptions::new();
+    options.checkout_builder(pick_checkout());
+    if let Some(mainline) = mainline {
+      options.mainline(mainline);
+    }
+    let picked = self.repo.cherrypick(&commit, Some(&mut options));
+    self.check_picked(picked, &commit, mainline, "Cherry-pick")?;
+
+    let conflicts = self.conflicted_paths()?;
+    if !conflicts.is_empty() {
+      return self.sequencer_result("conflicts", conflicts);
+    }
+    let author = commit.author();
+    self.commit_picked(&author, commit.message().unwrap_or(""))
+  }
+
+  /// Commit the inverse of the changes of `commit` on top of HEAD. For a
+  /// merge commit, `mainline` is the one-based parent to keep.
+  ///
+  /// Conflicts are left to be resolved and concluded with `commit`.
+  #[napi]
+  pub fn revert(&self, commit: String, mainline: Option<u32>) -> Result<SequencerResult> {
+    self.refresh_index()?;
+    let commit = self.find_revision_commit(&commit)?;
+    let mut options = RevertOptions::new();
+    options.checkout_builder(pick_checkout());
+    if let Some(mainline) = mainline {
+      options.mainline(mainline);
+    }
+    let reverted = self.repo.revert(&commit, Some(&mut options));
+    self.check_picked(reverted, &commit, mainline, "Revert")?;
+
+    let conflicts = self.conflicted_paths()?;
+    if !conflicts.is_empty() {
+      return self.sequencer_result("conflicts", conflicts);
+    }
+    let signature = self.repo.signature()
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to get signature: {}", e)))?;
+    let message = format!("Revert \"{}\"\n\nThis reverts commit {}.\n", commit.summary().unwrap_or(""), commit.id());
+    self.commit_picked(&signature, &message)
+  }
+
+  /// Replay the commits of the current branch that are not on `upstream` on top of it.
+  ///
+  /// When a commit conflicts, the rebase stops with the status `conflicts`.
+  /// Resolve them with `markResolved`, then call `rebaseContinue`, or
+  /// `rebaseSkip` to drop the commit, or `rebaseAbort` to go back.
+  #[napi]
+  pub fn rebase(&self, upstream: String) -> Result<SequencerResult> {
+    self.refresh_index()?;
+    let upstream = self.repo.revparse_single(&upstream)
+      .and_then(|object| object.peel_to_commit())
+      .and_then(|commit| self.repo.find_annotated_commit(commit.id()))
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to resolve {}: {}", upstream, e)))?;
+
+    let mut rebase = self.repo.rebase(None, Some(&upstream), None, Some(&mut rebase_options()))
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to start rebase: {}", e)))?;
+    self.run_rebase(&mut rebase)
+  }
+
+  /// Commit the resolved changes of the stopped commit, or amend the commit of
+  /// an `edit` step with staged changes, and carry on rebasing.
+  #[napi]
+  pub fn rebase_continue(&self) -> Result<SequencerResult> {
+    if self.interactive_rebase_in_progress() {
+      return self.continue_plan();
+    }
+    self.check_foreign_rebase()?;
+    self.refresh_index()?;
+    let mut rebase = self.open_rebase()?;
+    let conflicts = self.conflicted_paths()?;
+    if !conflicts.is_empty() {
+      return Err(Error::new(
+        Status::GenericFailure,
+        format!("Resolve conflicts before continuing: {}", conflicts.join(", ")),
+      ));
+    }
+    if rebase.operation_current().is_some() {
+      self.commit_rebase_operation(&mut rebase)?;
+    }
+    self.run_rebase(&mut rebase)
+  }
+
+  /// Drop the stopped commit and carry on rebasing.
+  #[napi]
+  pub fn rebase_skip(&self) -> Result<SequencerResult> {
+    if self.interactive_rebase_in_progress() {
+      return self.skip_plan_step();
+    }
+    self.check_foreign_rebase()?;
+    let mut rebase = self.open_rebase()?;
+    let head = self.repo.head()
+      .and_then(|head| head.peel(ObjectType::Commit))
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to get head commit: {}", e)))?;
+    self.repo.reset(&head, ResetType::Hard, None)
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to discard changes: {}", e)))?;
+    self.run_rebase(&mut rebase)
+  }
+
+  /// Stop rebasing and restore the branch as it was before the rebase.
+  #[napi]
+  pub fn rebase_abort(&self) -> Result<()> {
+    if self.interactive_rebase_in_progress() {
+      return self.abort_plan();
+    }
+    self.check_foreign_rebase()?;
+    self.open_rebase()?
+      .abort()
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to abort rebase: {}", e)))
+  }
+
+  /// Abort the merge, cherry-pick, revert or rebase in progress, restoring
+  /// the files it changed to how they were before it started. Like
+  /// `git reset --merge`, local changes to other files are kept.
+  #[napi]
+  pub fn abort_operation(&self) -> Result<()> {
+    match self.repo.state() {
+      RepositoryState::Clean => Err(Error::new(Status::GenericFailure, "No operation in progress".to_string())),
+      RepositoryState::Rebase | RepositoryState::RebaseInteractive | RepositoryState::RebaseMerge => self.rebase_abort(),
+      RepositoryState::Merge
+      | RepositoryState::CherryPick
+      | RepositoryState::CherryPickSequence
+      | RepositoryState::Revert
+      | RepositoryState::RevertSequence => {
+        self.reset_operation_paths()?;
+        self.repo.cleanup_state()
+          .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to clean up state: {}", e)))
+      }
+      state => Err(Error::new(Status::GenericFailure, format!("Cannot abort {:?}", state))),
+    }
+  }
+
+  /// The operation in progress, if any, so it can be continued or aborted.
+  #[napi]
+  pub fn repository_state(&self) -> Result<RepositoryStateInfo> {
+    self.refresh_index()?;
+    let state = match self.repo.state() {
+      RepositoryState::Clean => "clean",
+      RepositoryState::Merge => "merge",
+      RepositoryState::Revert | RepositoryState::RevertSequence => "revert",
+      RepositoryState::CherryPick | RepositoryState::CherryPickSequence => "cherryPick",
+      RepositoryState::Bisect => "bisect",
+      RepositoryState::Rebase | RepositoryState::RebaseInteractive | RepositoryState::RebaseMerge => "rebase",
+      RepositoryState::ApplyMailbox | RepositoryState::ApplyMailboxOrRebase => "applyMailbox",
+    };
+
+    let mut info = RepositoryStateInfo {
+      state: state.to_string(),
+      conflicts: self.conflicted_paths()?,
+      rebase_step: None,
+      rebase_total: None,
+      rebase_branch: None,
+      rebase_action: None,
+      rebase_commit: None,
+    };
+    if self.interactive_rebase_in_progress() {
+      self.interactive_rebase_state(&mut info)?;
+    } else if state == "rebase" {
+      // Rebases started by `git rebase --apply` cannot be opened and have no progress
+      if let Ok(mut rebase) = self.repo.open_rebase(None) {
+        info.rebase_step = rebase.operation_current().map(|current| current as u32 + 1);
+        info.rebase_total = Some(rebase.len() as u32);
+        info.rebase_branch = rebase.orig_head_name().map(str::to_string);
+        if let Some(operation) = rebase.operation_current().and_then(|current| rebase.nth(current)) {
+          info.rebase_action = Some("pick".to_string());
+          info.rebase_commit = Some(operation.id().to_string());
+        }
+      }
+    }
+    Ok(info)
+  }
+}
+
+impl GitRepo {
+  /// Apply the remaining operations of `rebase`, committing each one, until
+  /// it is done or stops on conflicts.
+  pub(crate) fn run_rebase(&self, rebase: &mut Rebase) -> Result<SequencerResult> {
+    while let Some(operation) = rebase.next() {
+      operation.map_err(|e| Error::new(Status::GenericFailure, format!("Failed to apply commit: {}", e)))?;
+      let conflicts = self.conflicted_paths()?;
+      if !conflicts.is_empty() {
+        return self.sequencer_result("conflicts", conflicts);
+      }
+      self.commit_rebase_operation(rebase)?;
+    }
+
+    let signature = self.repo.signature()
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to get signature: {}", e)))?;
+    rebase.finish(Some(&signature))
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to finish rebase: {}", e)))?;
+    self.sequencer_result("completed", Vec::new())
+  }
+
+  fn commit_rebase_operation(&self, rebase: &mut Rebase) -> Result<()> {
+    let signature = self.repo.signature()
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to get signature: {}", e)))?;
+    match rebase.commit(None, &signature, None) {
+      Ok(_) => Ok(()),
+      // The upstream already contains this change
+      Err(e) if e.code() == ErrorCode::Applied => Ok(()),
+      Err(e) => Err(Error::new(Status::GenericFailure, format!("Failed to commit rebased change: {}", e))),
+    }
+  }
+
+  /// Reset the files an operation staged or left conflicted to HEAD, in the
+  /// index and the working tree. Since operations refuse to start over local
+  /// changes to the files they touch, these are exactly the files it changed.
+  fn reset_operation_paths(&self) -> Result<()> {
+    let head = self.repo.head()
+      .and_then(|head| head.peel_to_commit())
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to get head commit: {}", e)))?;
+    let head_tree = head.tree()
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to get head tree: {}", e)))?;
+    let diff = self.repo.diff_tree_to_index(Some(&head_tree), None, None)
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to diff index: {}", e)))?;
+    let mut paths: HashSet<String> = diff.deltas()
+      .flat_map(|delta| [delta.old_file().path(), delta.new_file().path()])
+      .flatten()
+      .map(|path| path.to_string_lossy().to_string())
+      .collect();
+    paths.extend(self.conflicted_paths()?);
+    if paths.is_empty() {
+      return Ok(());
+    }
+
+    let mut checkout = CheckoutBuilder::new();
+    checkout.force();
+    for path in &paths {
+      checkout.path(literal_pathspec(path));
+    }
+    self.repo.checkout_tree(head.as_object(), Some(&mut checkout))
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to restore files: {}", e)))?;
+    self.repo.reset_default(Some(head.as_object()), paths.iter())
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to reset index: {}", e)))
+  }
+
+  fn open_rebase(&self) -> Result<Rebase<'_>> {
+    self.repo.open_rebase(Some(&mut rebase_options()))
+      .map_err(|e| Error::new(Status::GenericFailure, format!("No rebase in progress: {}", e)))
+  }
+
+  fn find_revision_commit(&self, revision: &str) -> Result<Commit<'_>> {
+    self.repo.revparse_single(revision)
+      .and_then(|object| object.peel_to_commit())
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to resolve {}: {}", revision, e)))
+  }
+
+  fn check_picked(
+    &self,
+    picked: std::result::Result<(), git2::Error>,
+    commit: &Commit,
+    mainline: Option<u32>,
+    operation: &str,
+  ) -> Result<()> {
+    match picked {
+      Ok(()) => Ok(()),
+      Err(e) if e.code() == ErrorCode::Conflict => {
+        let blocking = self.blocking_changes(commit, mainline)?;
+        // Staged changes block the operation even when they touch other files
+        let message = if blocking.is_empty() {
+          format!("{} cannot run with staged changes: {}", operation, self.staged_changes()?.join(", "))
+        } else {
+          format!("{} would overwrite local changes: {}", operation, blocking.join(", "))
+        };
+        Err(Error::new(Status::GenericFailure, message))
+      }
+      Err(e) => Err(Error::new(Status::GenericFailure, format!("{} failed: {}", operation, e))),
+    }
+  }
+
+  /// Local changes to files that `commit` touches, which keep it from being applied.
+  fn blocking_changes(&self, commit: &Commit, mainline: Option<u32>) -> Result<Vec<String>> {
+    let parent_tree = match commit.parent(mainline.unwrap_or(1).saturating_sub(1) as usize) {
+      Ok(parent) => Some(parent.tree()
+        .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to get parent tree: {}", e)))?),
+      Err(_) => None,
+    };
+    let tree = commit.tree()
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to get commit tree: {}", e)))?;
+    let diff = self.repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None)
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to diff commit: {}", e)))?;
+    let touched: HashSet<_> = diff.deltas()
+      .flat_map(|delta| [delta.old_file().path(), delta.new_file().path()])
+      .flatten()
+      .map(|path| path.to_string_lossy().to_string())
+      .collect();
+
+    let mut options = StatusOptions::new();
+    options.include_untracked(true);
+    let statuses = self.repo.statuses(Some(&mut options))
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to get status: {}", e)))?;
+    Ok(statuses.iter()
+      .filter_map(|entry| entry.path().map(str::to_string))
+      .filter(|path| touched.contains(path))
+      .collect())
+  }
+
+  /// Files whose staged version differs from HEAD.
+  fn staged_changes(&self) -> Result<Vec<String>> {
+    let staged = GitStatus::INDEX_NEW
+      | GitStatus::INDEX_MODIFIED
+      | GitStatus::INDEX_DELETED
+      | GitStatus::INDEX_RENAMED
+      | GitStatus::INDEX_TYPECHANGE;
+    let statuses = self.repo.statuses(None)
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to get status: {}", e)))?;
+    Ok(statuses.iter()
+      .filter(|entry| entry.status().intersects(staged))
+      .filter_map(|entry| entry.path().map(str::to_string))
+      .collect())
+  }
+
+  /// Commit the index left by a conflict-free cherry-pick or revert and end the operation.
+  fn commit_picked(&self, author: &git2::Signature, message: &str) -> Result<SequencerResult> {
+    let committer = self.repo.signature()
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to get signature: {}", e)))?;
+    let tree = self.repo.index()
+      .and_then(|mut index| index.write_tree())
+      .and_then(|tree_id| self.repo.find_tree(tree_id))
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to write tree: {}", e)))?;
+    let head = self.repo.head()
+      .and_then(|head| head.peel_to_commit())
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to get head commit: {}", e)))?;
+
+    let status = if tree.id() == head.tree_id() {
+      "empty"
+    } else {
+      self.repo.commit(Some("HEAD"), author, &committer, message, &tree, &[&head])
+        .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to commit: {}", e)))?;
+      "completed"
+    };
+    self.repo.cleanup_state()
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to clean up state: {}", e)))?;
+    self.sequencer_result(status, Vec::new())
+  }
+
+  fn sequencer_result(&self, status: &str, conflicts: Vec<String>) -> Result<SequencerResult> {
+    let head = self.repo.head()
+      .and_then(|head| head.peel(ObjectType::Commit))
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to get head commit: {}", e)))?;
+    Ok(SequencerResult { status: status.to_string(), head: head.id().to_string(), conflicts })
+  }
+}
+
+fn rebase_options<'cb>() -> RebaseOptions<'cb> {
+  let mut options = RebaseOptions::new();
+  options.checkout_options(pick_checkout());
+  options
+}
+
+/// Checkout for applying a commit: safe, with conflict markers.
+fn pick_checkout<'cb>() -> CheckoutBuilder<'cb> {
+  let mut checkout = CheckoutBuilder::new();
+  checkout.safe().conflict_style_diff3(true);
+  checkout
+}
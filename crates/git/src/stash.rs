@@ -0,0 +1,189 @@
+use std::cell::RefCell;
+
+use git2::build::CheckoutBuilder;
+use git2::{CheckoutNotificationType, ErrorCode, Oid, StashApplyOptions, StashFlags};
+use napi::{Error, Result, Status};
+
+use crate::diff::{diff_files, git_diff_options, DiffFile, DiffOptions};
+use crate::GitRepo;
+
+#[napi(object)]
+#[derive(Debug, Clone, Default)]
+pub struct StashSaveOptions {
+  /// Also stash untracked files, like `git stash -u`
+  pub include_untracked: Option<bool>,
+  /// Leave staged changes in place, like `git stash --keep-index`
+  pub keep_index: Option<bool>,
+}
+
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct StashEntry {
+  /// Position in the stash list; 0 is the most recent
+  pub index: u32,
+  /// For example `On main: wip` or `WIP on main: 1a2b3c4 Add login form`
+  pub message: String,
+  pub hash: String,
+  pub time: i64,
+}
+
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct StashApplyResult {
+  /// Whether the stash was applied. When it was not, `conflicts` lists the
+  /// local changes that were in the way and nothing was changed
+  pub applied: bool,
+  /// When applied, files left with conflict markers
+  pub conflicts: Vec<String>,
+}
+
+#[napi]
+impl GitRepo {
+  /// Stash local changes and return the stash commit, or null when there was nothing to stash.
+  #[napi]
+  pub fn stash_save(&mut self, message: Option<String>, options: Option<StashSaveOptions>) -> Result<Option<String>> {
+    let options = options.unwrap_or_default();
+    let mut flags = StashFlags::DEFAULT;
+    if options.include_untracked.unwrap_or(false) {
+      flags |= StashFlags::INCLUDE_UNTRACKED;
+    }
+    if options.keep_index.unwrap_or(false) {
+      flags |= StashFlags::KEEP_INDEX;
+    }
+
+    self.refresh_index()?;
+    let signature = self.repo.signature()
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to get signature: {}", e)))?;
+    match self.repo.stash_save(&signature, message.as_deref().unwrap_or(""), Some(flags)) {
+      Ok(id) => Ok(Some(id.to_string())),
+      Err(e) if e.code() == ErrorCode::NotFound => Ok(None),
+      Err(e) => Err(Error::new(Status::GenericFailure, format!("Failed to stash changes: {}", e))),
+    }
+  }
+
+  /// Stashes, most recent first.
+  #[napi]
+  pub fn stash_list(&mut self) -> Result<Vec<StashEntry>> {
+    let mut stashes = Vec::new();
+    self.repo.stash_foreach(|index, message, id| {
+      stashes.push((index, message.to_string(), *id));
+      true
+    })
+    .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to list stashes: {}", e)))?;
+
+    stashes.into_iter()
+      .map(|(index, message, id)| {
+        let commit = self.repo.find_commit(id)
+          .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to find stash commit: {}", e)))?;
+        Ok(StashEntry { index: index as u32, message, hash: id.to_string(), time: commit.time().seconds() })
+      })
+      .collect()
+  }
+
+  /// Apply stash `index` (0 by default) and keep it. Staged changes are
+  /// restored to the index as well with `reinstateIndex`.
+  #[napi]
+  pub fn stash_apply(&mut self, index: Option<u32>, reinstate_index: Option<bool>) -> Result<StashApplyResult> {
+    self.apply_stash(index.unwrap_or(0) as usize, reinstate_index.unwrap_or(false))
+  }
+
+  /// Apply stash `index` (0 by default) and drop it, unless applying it left
+  /// conflicts to resolve.
+  #[napi]
+  pub fn stash_pop(&mut self, index: Option<u32>, reinstate_index: Option<bool>) -> Result<StashApplyResult> {
+    let index = index.unwrap_or(0) as usize;
+    let result = self.apply_stash(index, reinstate_index.unwrap_or(false))?;
+    if result.applied && result.conflicts.is_empty() {
+      self.stash_drop(Some(index as u32))?;
+    }
+    Ok(result)
+  }
+
+  #[napi]
+  pub fn stash_drop(&mut self, index: Option<u32>) -> Result<()> {
+    let index = index.unwrap_or(0);
+    self.repo.stash_drop(index as usize)
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to drop stash@{{{}}}: {}", index, e)))
+  }
+
+  /// Changes in stash `index` (0 by default) relative to the commit it was
+  /// made on, including stashed untracked files.
+  #[napi]
+  pub fn stash_show(&mut self, index: Option<u32>, options: Option<DiffOptions>) -> Result<Vec<DiffFile>> {
+    let options = options.unwrap_or_default();
+    let id = self.stash_id(index.unwrap_or(0) as usize)?;
+    let stash = self.repo.find_commit(id)
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to find stash commit: {}", e)))?;
+    let tree = stash.tree()
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to get stash tree: {}", e)))?;
+    let base_tree = stash.parent(0)
+      .and_then(|base| base.tree())
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to get stash base: {}", e)))?;
+
+    let mut diff = self.repo.diff_tree_to_tree(Some(&base_tree), Some(&tree), Some(&mut git_diff_options(&options)))
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to diff stash: {}", e)))?;
+    // Untracked files are stored in a third parent
+    if let Ok(untracked) = stash.parent(2) {
+      let untracked_tree = untracked.tree()
+        .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to get untracked files: {}", e)))?;
+      let untracked_diff = self.repo
+        .diff_tree_to_tree(None, Some(&untracked_tree), Some(&mut git_diff_options(&options)))
+        .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to diff untracked files: {}", e)))?;
+      diff.merge(&untracked_diff)
+        .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to diff untracked files: {}", e)))?;
+    }
+    diff_files(&mut diff, &options)
+  }
+}
+
+impl GitRepo {
+  fn apply_stash(&mut self, index: usize, reinstate_index: bool) -> Result<StashApplyResult> {
+    self.refresh_index()?;
+    let blocked = RefCell::new(Vec::new());
+    let applied = {
+      let mut checkout = CheckoutBuilder::new();
+      checkout
+        .safe()
+        .conflict_style_diff3(true)
+        .notify_on(CheckoutNotificationType::CONFLICT)
+        .notify(|_, path, _, _, _| {
+          if let Some(path) = path {
+            blocked.borrow_mut().push(path.to_string_lossy().to_string());
+          }
+          true
+        });
+      let mut options = StashApplyOptions::new();
+      options.checkout_options(checkout);
+      if reinstate_index {
+        options.reinstantiate_index();
+      }
+      self.repo.stash_apply(index, Some(&mut options))
+    };
+
+    match applied {
+      Ok(()) => {}
+      // Both sides changed the same lines; the files now have conflict markers
+      Err(e) if e.code() == ErrorCode::MergeConflict => {}
+      Err(e) if e.code() == ErrorCode::Conflict => {
+        return Ok(StashApplyResult { applied: false, conflicts: blocked.into_inner() });
+      }
+      Err(e) => {
+        return Err(Error::new(Status::GenericFailure, format!("Failed to apply stash@{{{}}}: {}", index, e)));
+      }
+    }
+
+    Ok(StashApplyResult { applied: true, conflicts: self.conflicted_paths()? })
+  }
+
+  fn stash_id(&mut self, index: usize) -> Result<Oid> {
+    let mut found = None;
+    self.repo.stash_foreach(|i, _, id| {
+      if i == index {
+        found = Some(*id);
+      }
+      found.is_none()
+    })
+    .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to list stashes: {}", e)))?;
+    found.ok_or_else(|| Error::new(Status::GenericFailure, format!("No stash found at stash@{{{}}}", index)))
+  }
+}
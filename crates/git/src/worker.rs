@@ -0,0 +1,85 @@
+//! Support for work that runs off the JS thread and calls back into JS.
+
+use std::future::Future;
+use std::path::Path;
+use std::pin::pin;
+use std::sync::{mpsc, Arc};
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread::{self, Thread};
+
+use git2::Repository;
+use napi::bindgen_prelude::{FromNapiValue, Promise, ToNapiValue};
+use napi::threadsafe_function::{ThreadSafeCallContext, ThreadsafeFunction, ThreadsafeFunctionCallMode};
+use napi::{Env, Error, JsFunction, JsObject, Result, Status};
+
+use crate::GitRepo;
+
+/// Run `work` on a thread of its own and return a promise settled with its result.
+///
+/// Work that waits on JS with `call_async` must not run as an `AsyncTask`:
+/// that would park a libuv pool thread on a promise that may itself need the
+/// pool, like one from `fs.promises`, and hang once the pool is used up.
+pub(crate) fn spawn_promise<T, W>(env: &Env, work: W) -> Result<JsObject>
+where
+  T: ToNapiValue + Send + 'static,
+  W: FnOnce() -> Result<T> + Send + 'static,
+{
+  let (deferred, promise) = env.create_deferred()?;
+  thread::spawn(move || match work() {
+    Ok(value) => deferred.resolve(move |_| Ok(value)),
+    Err(e) => deferred.reject(e),
+  });
+  Ok(promise)
+}
+
+/// A separate handle for the worker thread, since a `Repository` can't be shared across threads.
+pub(crate) fn open(git_dir: &Path) -> Result<GitRepo> {
+  let repo = Repository::open(git_dir)
+    .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to open repository: {}", e)))?;
+  Ok(GitRepo { repo, metadata_cache: Default::default() })
+}
+
+/// Make `callback` callable from a worker thread with `call_async`.
+pub(crate) fn async_callback<T: ToNapiValue + 'static>(env: &Env, callback: JsFunction) -> Result<ThreadsafeFunction<T>> {
+  // Wrapped in an async function so that throwing, returning a plain value
+  // and returning a promise all come back as a promise
+  let wrap: JsFunction = env.run_script("(callback) => async (...args) => callback(...args)")?;
+  let wrapped: JsFunction = wrap.call(None, &[callback])?.try_into()?;
+  wrapped.create_threadsafe_function(0, |ctx: ThreadSafeCallContext<T>| Ok(vec![ctx.value]))
+}
+
+/// Call a callback made by `async_callback` and wait for the promise it
+/// returns. Only call this on a thread from `spawn_promise`.
+pub(crate) fn call_async<T: 'static, R: FromNapiValue + 'static>(callback: &ThreadsafeFunction<T>, arg: T) -> Result<R> {
+  let (sender, receiver) = mpsc::channel();
+  callback.call_with_return_value(Ok(arg), ThreadsafeFunctionCallMode::Blocking, move |answer: Promise<R>| {
+    let _ = sender.send(answer);
+    Ok(())
+  });
+
+  // The sender is dropped without an answer when the call could not be made
+  let answer = receiver.recv()
+    .map_err(|_| Error::new(Status::GenericFailure, "The callback could not be called".to_string()))?;
+  block_on(answer)
+}
+
+/// Wait for `future` on the current thread, which is one of our own and free to block.
+fn block_on<F: Future>(future: F) -> F::Output {
+  struct Unpark(Thread);
+
+  impl Wake for Unpark {
+    fn wake(self: Arc<Self>) {
+      self.0.unpark();
+    }
+  }
+
+  let waker = Waker::from(Arc::new(Unpark(thread::current())));
+  let mut context = Context::from_waker(&waker);
+  let mut future = pin!(future);
+  loop {
+    match future.as_mut().poll(&mut context) {
+      Poll::Ready(output) => return output,
+      Poll::Pending => thread::park(),
+    }
+  }
+}